@@ -1,5 +1,11 @@
 use std::{marker::PhantomData, ptr::null_mut};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /* types and structs */
 type Link<T> = *mut Node<T>;
 
@@ -12,7 +18,7 @@ struct Node<T> {
 impl<T> Node<T> {
     pub fn new_link(item: T) -> Link<T> {
         Box::into_raw(Box::new(Node {
-            item: item,
+            item,
             prev: null_mut(),
             next: null_mut(),
         }))
@@ -30,6 +36,12 @@ pub struct LinkedList<T> {
     len: usize,
 }
 
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
         LinkedList {
@@ -261,6 +273,25 @@ impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
     }
 }
 
+/// Serialized as a sequence of the items from front to back, and rebuilt on
+/// load by pushing them back in order.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for LinkedList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.len(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |list, item| {
+            list.push_back(item);
+            Ok(())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,14 +300,14 @@ mod tests {
     fn test() {
         let mut list = LinkedList::new();
         assert_eq!(list.pop_front(), None);
-        assert_eq!(list.is_empty(), true);
+        assert!(list.is_empty());
         assert_eq!(list.len(), 0);
 
         // push front pop front
         list.push_front(1);
         list.push_front(2);
         assert_eq!(list.len(), 2);
-        assert_eq!(list.is_empty(), false);
+        assert!(!list.is_empty());
         list.push_front(3);
 
         assert_eq!(list.pop_front(), Some(3));
@@ -285,7 +316,7 @@ mod tests {
         assert_eq!(list.pop_front(), Some(1));
         assert_eq!(list.pop_front(), None);
         assert_eq!(list.len(), 0);
-        assert_eq!(list.is_empty(), true);
+        assert!(list.is_empty());
 
         // push back pop front
         list.push_back(1);
@@ -423,4 +454,13 @@ mod tests {
 
         list.remove(2);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut list: LinkedList<i32> = serde_json::from_str("[2, 3]").unwrap();
+        list.push_front(1);
+        assert_eq!(serde_json::to_string(&list).unwrap(), "[1,2,3]");
+        assert!(serde_json::from_str::<LinkedList<i32>>(r#"{"a": 1}"#).is_err());
+    }
 }
@@ -4,12 +4,24 @@ use std::{
     ptr::null_mut,
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 pub struct Vector<T> {
     ptr: *mut T,
     cap: usize,
     len: usize,
 }
 
+impl<T> Default for Vector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Vector<T> {
     pub fn new() -> Self {
         assert!(
@@ -142,16 +154,43 @@ impl<T> Deref for Vector<T> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
+        // the pointer is null until the first allocation, which isn't a valid
+        // pointer for a slice (even an empty one)
+        if self.cap == 0 {
+            return &[];
+        }
         unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
     }
 }
 
 impl<T> DerefMut for Vector<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        if self.cap == 0 {
+            return &mut [];
+        }
         unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
     }
 }
 
+/// Serialized as a sequence of the items, and rebuilt on load by pushing
+/// them back in order.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Vector<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.len(), self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |vector, item| {
+            vector.push(item);
+            Ok(())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,7 +201,7 @@ mod tests {
         assert_eq!(v.pop(), None);
         assert_eq!(v.len(), 0);
         assert_eq!(v.capacity(), 0);
-        assert_eq!(v.is_empty(), true);
+        assert!(v.is_empty());
 
         v.push(1);
         v.push(2);
@@ -179,7 +218,7 @@ mod tests {
 
         v.push(10);
         assert_eq!(v.len(), 1);
-        assert_eq!(v.is_empty(), false);
+        assert!(!v.is_empty());
         v.push(20);
         v.push(30);
         v.push(40);
@@ -204,10 +243,19 @@ mod tests {
         v.remove(0);
         v.remove(0);
 
-        assert_eq!(v.is_empty(), true);
+        assert!(v.is_empty());
 
         v.insert(0, 100);
         assert_eq!(v.pop(), Some(100));
         assert_eq!(v.pop(), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let v: Vector<String> = serde_json::from_str(r#"["a", "b", "c"]"#).unwrap();
+        assert_eq!(v[..], ["a", "b", "c"]);
+        assert_eq!(serde_json::to_string(&v).unwrap(), r#"["a","b","c"]"#);
+        assert_eq!(serde_json::to_string(&Vector::<i32>::new()).unwrap(), "[]");
+    }
 }
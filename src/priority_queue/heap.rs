@@ -1,5 +1,15 @@
+use std::fmt::Display;
+
+use crate::utils::dot_label;
+
 use super::PriorityQueue;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /// A MinHeap (binary heap) implementation for a priority queue, using a fixed
 /// length array.
 ///
@@ -36,7 +46,7 @@ use super::PriorityQueue;
 /// given node with index k:
 ///
 ///   1) It's parent is found at `k/2` (integer division, so if odd, remove the
-///        decimal)
+///      decimal)
 ///   2) It's left child is found at `2k`
 ///   3) It's right child is found at `2k+1`
 ///
@@ -58,6 +68,12 @@ pub struct PriorityQueueBinaryHeap<T: Ord, const CAPACITY: usize> {
     size: usize,
 }
 
+impl<T: Ord, const CAPACITY: usize> Default for PriorityQueueBinaryHeap<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord, const CAPACITY: usize> PriorityQueueBinaryHeap<T, CAPACITY> {
     const INIT: Option<T> = None;
     pub fn new() -> Self {
@@ -105,6 +121,28 @@ impl<T: Ord, const CAPACITY: usize> PriorityQueueBinaryHeap<T, CAPACITY> {
     }
 }
 
+impl<T: Ord + Display, const CAPACITY: usize> PriorityQueueBinaryHeap<T, CAPACITY> {
+    /// Render the heap as a tree in the Graphviz DOT format (e.g. to view
+    /// with `dot -Tsvg`).
+    ///
+    /// ### Implementation
+    /// The nodes are numbered by their index in the array, and we add an edge
+    /// from each node to it's parent, which is at index i/2 (the same parent
+    /// that swim uses).
+    pub fn to_dot(&self) -> String {
+        let mut output = "digraph {\n    node [shape=circle];\n".to_string();
+        for i in 0..self.size {
+            let label = dot_label(self.arr[i].as_ref().unwrap());
+            output.push_str(&format!("    n{} [label={}];\n", i, label));
+        }
+        for i in 1..self.size {
+            output.push_str(&format!("    n{} -> n{};\n", i / 2, i));
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
 impl<T: Ord, const CAPACITY: usize> PriorityQueue<T> for PriorityQueueBinaryHeap<T, CAPACITY> {
     /// Push an item to the queue.
     ///
@@ -158,3 +196,26 @@ impl<T: Ord, const CAPACITY: usize> PriorityQueue<T> for PriorityQueueBinaryHeap
         self.size
     }
 }
+
+/// Serialized as a sequence of the items in the order of the heap array, and
+/// rebuilt on load by pushing them back one at a time (which fails if there
+/// are more than CAPACITY items).
+#[cfg(feature = "serde")]
+impl<T: Ord + Serialize, const CAPACITY: usize> Serialize for PriorityQueueBinaryHeap<T, CAPACITY> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            self.arr[..self.size].iter().flatten(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for PriorityQueueBinaryHeap<T, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |pq, item| pq.push(item))
+    }
+}
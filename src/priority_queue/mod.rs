@@ -24,7 +24,7 @@ mod tests {
     use super::*;
 
     fn test(pq: &mut dyn PriorityQueue<i32>) {
-        assert_eq!(pq.is_empty(), true);
+        assert!(pq.is_empty());
         assert_eq!(pq.size(), 0);
         assert_eq!(pq.pop(), None);
         assert_eq!(pq.peek(), None);
@@ -115,4 +115,47 @@ mod tests {
         assert!(pq.push(5).is_ok());
         assert!(pq.push(6).is_err());
     }
+
+    #[test]
+    fn test_binary_heap_to_dot() {
+        let mut pq: PriorityQueueBinaryHeap<&str, 8> = PriorityQueueBinaryHeap::new();
+        for item in ["c", "a", "d", "b"] {
+            pq.push(item).unwrap();
+        }
+        let expected = [
+            "digraph {",
+            "    node [shape=circle];",
+            "    n0 [label=\"a\"];",
+            "    n1 [label=\"b\"];",
+            "    n2 [label=\"d\"];",
+            "    n3 [label=\"c\"];",
+            "    n0 -> n1;",
+            "    n1 -> n2;",
+            "    n1 -> n3;",
+            "}",
+        ];
+        assert_eq!(pq.to_dot().lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut pq: PriorityQueueBinaryHeap<i32, 8> = PriorityQueueBinaryHeap::new();
+        for item in [5, 3, 8, 1, 4] {
+            pq.push(item).unwrap();
+        }
+        let json = serde_json::to_string(&pq).unwrap();
+
+        let mut loaded: PriorityQueueBinaryHeap<i32, 8> = serde_json::from_str(&json).unwrap();
+        let mut ordered: PriorityQueueOrderedArr<i32, 8> = serde_json::from_str(&json).unwrap();
+        let mut unordered: PriorityQueueUnorderedArr<i32, 8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&ordered).unwrap(), "[8,5,4,3,1]");
+        for item in [1, 3, 4, 5, 8] {
+            assert_eq!(loaded.pop(), Some(item));
+            assert_eq!(ordered.pop(), Some(item));
+            assert_eq!(unordered.pop(), Some(item));
+        }
+
+        assert!(serde_json::from_str::<PriorityQueueBinaryHeap<i32, 4>>(&json).is_err());
+    }
 }
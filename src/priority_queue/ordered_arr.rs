@@ -2,6 +2,12 @@ use crate::{search::binary_search_insert_index_rev, utils::insert_and_shift};
 
 use super::PriorityQueue;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /// An ordered fixed length array implementation for a priority queue.
 ///
 /// The approach here is to keep a fixed length arr with items in reverse
@@ -12,6 +18,12 @@ pub struct PriorityQueueOrderedArr<T: Ord, const CAPACITY: usize> {
     size: usize,
 }
 
+impl<T: Ord, const CAPACITY: usize> Default for PriorityQueueOrderedArr<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord, const CAPACITY: usize> PriorityQueueOrderedArr<T, CAPACITY> {
     const INIT: Option<T> = None;
 
@@ -76,3 +88,26 @@ impl<T: Ord, const CAPACITY: usize> PriorityQueue<T> for PriorityQueueOrderedArr
         self.size
     }
 }
+
+/// Serialized as a sequence of the items in reverse sorted order (the order
+/// of the array), and rebuilt on load by pushing them back one at a time
+/// (which fails if there are more than CAPACITY items).
+#[cfg(feature = "serde")]
+impl<T: Ord + Serialize, const CAPACITY: usize> Serialize for PriorityQueueOrderedArr<T, CAPACITY> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            self.arr[..self.size].iter().flatten(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for PriorityQueueOrderedArr<T, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |pq, item| pq.push(item))
+    }
+}
@@ -1,5 +1,11 @@
 use super::PriorityQueue;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /// An unordered fixed length array implementation for a priority queue.
 ///
 /// The approach here is to keep a fixed length arr with items in unordered.
@@ -10,6 +16,12 @@ pub struct PriorityQueueUnorderedArr<T: Ord, const CAPACITY: usize> {
     size: usize,
 }
 
+impl<T: Ord, const CAPACITY: usize> Default for PriorityQueueUnorderedArr<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord, const CAPACITY: usize> PriorityQueueUnorderedArr<T, CAPACITY> {
     const INIT: Option<T> = None;
 
@@ -83,3 +95,28 @@ impl<T: Ord, const CAPACITY: usize> PriorityQueue<T> for PriorityQueueUnorderedA
         self.size
     }
 }
+
+/// Serialized as a sequence of the items in no particular order, and rebuilt
+/// on load by pushing them back one at a time (which fails if there are more
+/// than CAPACITY items).
+#[cfg(feature = "serde")]
+impl<T: Ord + Serialize, const CAPACITY: usize> Serialize
+    for PriorityQueueUnorderedArr<T, CAPACITY>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            self.arr[..self.size].iter().flatten(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for PriorityQueueUnorderedArr<T, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |pq, item| pq.push(item))
+    }
+}
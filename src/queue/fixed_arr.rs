@@ -1,5 +1,11 @@
 use super::Queue;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /// Fixed length array implementation for a queue.
 ///
 /// This implementation uses a fixed length array with a pointer for the start
@@ -16,6 +22,12 @@ pub struct QueueFixedArr<T, const CAPACITY: usize> {
     back_i: usize,
 }
 
+impl<T, const CAPACITY: usize> Default for QueueFixedArr<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T, const CAPACITY: usize> QueueFixedArr<T, CAPACITY> {
     const INIT: Option<T> = None;
 
@@ -62,3 +74,26 @@ impl<T, const CAPACITY: usize> Queue<T> for QueueFixedArr<T, CAPACITY> {
         self.size
     }
 }
+
+/// Serialized as a sequence of the items from the front to the back of the
+/// queue, and rebuilt on load by pushing them back in order (which fails if
+/// there are more than CAPACITY items).
+#[cfg(feature = "serde")]
+impl<T: Serialize, const CAPACITY: usize> Serialize for QueueFixedArr<T, CAPACITY> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            (0..self.size).map(|i| self.arr[(self.front_i + i) % CAPACITY].as_ref().unwrap()),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for QueueFixedArr<T, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |queue, item| queue.push(item))
+    }
+}
@@ -2,6 +2,12 @@ use std::{ops::DerefMut, ptr::null_mut};
 
 use super::Queue;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /* node and link structs */
 type Link<T> = Option<Box<Node<T>>>;
 
@@ -29,6 +35,12 @@ pub struct QueueLinkedList<T> {
     size: usize,
 }
 
+impl<T> Default for QueueLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> QueueLinkedList<T> {
     pub fn new() -> Self {
         QueueLinkedList {
@@ -100,3 +112,24 @@ impl<T> Queue<T> for QueueLinkedList<T> {
         self.size
     }
 }
+
+/// Serialized as a sequence of the items from the front to the back of the
+/// queue, and rebuilt on load by pushing them back in order.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for QueueLinkedList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            std::iter::successors(self.head.as_deref(), |node| node.next.as_deref())
+                .map(|node| &node.item),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for QueueLinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |queue, item| queue.push(item))
+    }
+}
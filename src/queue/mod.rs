@@ -19,7 +19,7 @@ mod tests {
 
     fn test(queue: &mut dyn Queue<i32>) {
         assert_eq!(queue.pop(), None);
-        assert_eq!(queue.is_empty(), true);
+        assert!(queue.is_empty());
         assert_eq!(queue.size(), 0);
         assert_eq!(queue.peek(), None);
 
@@ -73,4 +73,27 @@ mod tests {
         assert!(queue.push(5).is_ok());
         assert!(queue.push(6).is_err());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        // wrap the items around the end of the array
+        let mut queue: QueueFixedArr<i32, 4> = QueueFixedArr::new();
+        for item in [0, 0, 1, 2] {
+            queue.push(item).unwrap();
+        }
+        queue.pop();
+        queue.pop();
+        queue.push(3).unwrap();
+        let json = serde_json::to_string(&queue).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let mut loaded: QueueLinkedList<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert_eq!(loaded.pop(), Some(1));
+        let mut loaded: QueueFixedArr<i32, 3> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.pop(), Some(1));
+
+        assert!(serde_json::from_str::<QueueFixedArr<i32, 2>>(&json).is_err());
+    }
 }
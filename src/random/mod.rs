@@ -1,9 +1,32 @@
 use rand::Rng;
 
 /// Fisher yates algorithm to shuffle an array in-place in O(1) time.
-pub fn shuffle_arr<T: Ord>(arr: &mut [T]) {
+pub fn shuffle_arr<T>(arr: &mut [T]) {
+    shuffle_arr_with_rng(arr, &mut rand::thread_rng());
+}
+
+/// Same as shuffle_arr, but with the given random number generator (e.g. a
+/// seeded one, so the shuffle is reproducible).
+pub fn shuffle_arr_with_rng<T, R: Rng + ?Sized>(arr: &mut [T], rng: &mut R) {
     for i in (1..arr.len()).rev() {
-        let j = rand::thread_rng().gen_range(0..=i);
+        let j = rng.gen_range(0..=i);
         arr.swap(i, j);
     }
 }
+
+/// Get a random u64, uniformly distributed over all u64 values.
+pub fn random_u64() -> u64 {
+    rand::thread_rng().gen()
+}
+
+/// Flip a fair coin until it lands on tails (or until we've flipped max
+/// heads), and return the number of heads. That is, a sample from the
+/// geometric distribution with p = 1/2 capped at max.
+pub fn coin_flips(max: usize) -> usize {
+    let mut rng = rand::thread_rng();
+    let mut heads = 0;
+    while heads < max && rng.gen_bool(0.5) {
+        heads += 1;
+    }
+    heads
+}
@@ -34,13 +34,21 @@ pub fn binary_search_insert_index_rev<T: Ord>(arr: &[T], target: &T) -> usize {
     search(arr, target, true).unwrap_or_else(|x| x)
 }
 
-fn search<T: Ord>(arr: &[T], target: &T, rev: bool) -> Result<usize, usize> {
-    let ordering = if rev {
-        Ordering::Less
-    } else {
-        Ordering::Greater
-    };
-
+/// Searches a sorted array using a comparator function in O(log n) time.
+///
+/// The comparator `f` should return the ordering of a given entry relative to
+/// the target (i.e. `Less` if the entry comes before the target). This makes it
+/// possible to search for targets of a different type than the entries (for
+/// example searching for a `&str` in an array of `String`).
+///
+/// If the target is found, Ok(index_of_match) is returned, else
+/// Err(index_of_insertion) is returned, where index_of_insertion is the index
+/// at which the target can be inserted such that the array remains sorted (see
+/// binary_search_insert_index).
+pub fn binary_search_by<T, F>(arr: &[T], mut f: F) -> Result<usize, usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
     if arr.is_empty() {
         return Err(0);
     }
@@ -49,20 +57,28 @@ fn search<T: Ord>(arr: &[T], target: &T, rev: bool) -> Result<usize, usize> {
     let mut j = arr.len() - 1;
     while i <= j {
         let m = (i + j) / 2;
-        if &arr[m] == target {
-            return Ok(m);
-        } else if arr[m].cmp(target) == ordering {
-            if m == 0 {
-                return Err(0);
+        match f(&arr[m]) {
+            Ordering::Equal => return Ok(m),
+            Ordering::Greater => {
+                if m == 0 {
+                    return Err(0);
+                }
+                j = m - 1;
             }
-            j = m - 1;
-        } else {
-            i = m + 1;
+            Ordering::Less => i = m + 1,
         }
     }
     Err(i)
 }
 
+fn search<T: Ord>(arr: &[T], target: &T, rev: bool) -> Result<usize, usize> {
+    if rev {
+        binary_search_by(arr, |x| target.cmp(x))
+    } else {
+        binary_search_by(arr, |x| x.cmp(target))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,4 +150,21 @@ mod tests {
         assert_eq!(binary_search_insert_index_rev(&arr, &7), 4);
         assert_eq!(binary_search_insert_index_rev(&arr, &8), 4);
     }
+
+    #[test]
+    fn test_binary_search_by() {
+        let arr = ["apple", "banana", "cat", "dog"].map(String::from);
+        let search = |target: &str| binary_search_by(&arr, |x| x.as_str().cmp(target));
+        assert_eq!(search("apple"), Ok(0));
+        assert_eq!(search("dog"), Ok(3));
+        assert_eq!(search("a"), Err(0));
+        assert_eq!(search("bird"), Err(2));
+        assert_eq!(search("elephant"), Err(4));
+
+        let arr = [10, 9, 9, 9, 6, 4, 4, 2, 2, 1];
+        assert_eq!(binary_search_by(&arr, |x| 6.cmp(x)), Ok(4));
+        assert_eq!(binary_search_by(&arr, |x| 5.cmp(x)), Err(5));
+        assert_eq!(binary_search_by(&arr, |x| 0.cmp(x)), Err(10));
+        assert_eq!(binary_search_by(&[0; 0], |x| 0.cmp(x)), Err(0));
+    }
 }
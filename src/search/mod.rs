@@ -1,6 +1,7 @@
 mod binary_search;
 
 pub use binary_search::binary_search;
+pub use binary_search::binary_search_by;
 pub use binary_search::binary_search_insert_index;
 pub use binary_search::binary_search_insert_index_rev;
 pub use binary_search::binary_search_rev;
@@ -1,8 +1,22 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in O(n^2) time.
+///
+/// This sort is stable, that is, equal entries keep their relative order,
+/// since only adjacent entries that are strictly out of order are swapped.
 pub fn bubble_sort<T: Ord>(arr: &mut [T]) {
+    bubble_sort_by(arr, T::cmp);
+}
+
+/// Same as bubble_sort, but the order is given by the comparator `cmp`.
+pub fn bubble_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     loop {
         let mut sorted = true;
         for i in 1..arr.len() {
-            if arr[i - 1] > arr[i] {
+            if cmp(&arr[i - 1], &arr[i]) == Ordering::Greater {
                 arr.swap(i - 1, i);
                 sorted = false;
             }
@@ -12,3 +26,12 @@ pub fn bubble_sort<T: Ord>(arr: &mut [T]) {
         }
     }
 }
+
+/// Same as bubble_sort, but sorts by the key extracted with `f`.
+pub fn bubble_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bubble_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
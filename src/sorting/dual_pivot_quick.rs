@@ -0,0 +1,129 @@
+use std::cmp::Ordering;
+
+use super::insertion_sort_by;
+
+/// Subarrays of this size or smaller are insertion sorted.
+const CUTOFF: usize = 10;
+
+/// Sorts the array with Yaroslavskiy's dual-pivot quicksort.
+///
+/// Two pivots p1 <= p2 split the array into three parts, the entries less than
+/// p1, between p1 and p2, and greater than p2, which are each sorted
+/// recursively. This does fewer swaps than single pivot quicksort, and when
+/// the pivots are equal, the middle part is all equal to them and is skipped,
+/// so duplicate keys are handled well too.
+///
+/// This sort is not stable.
+///
+/// ### Implementation
+/// Like Java's DualPivotQuicksort, the pivots are the 2nd and 4th of a sorted
+/// sample of five evenly spaced entries (see choose_pivots), which is the two
+/// pivot version of median-of-three. This keeps the parts balanced on inputs
+/// that are already (or reverse) sorted, without shuffling. Subarrays of at
+/// most CUTOFF entries are insertion sorted instead of partitioned.
+///
+/// See: https://algs4.cs.princeton.edu/23quicksort/QuickDualPivot.java.html
+pub fn dual_pivot_quick_sort<T: Ord>(arr: &mut [T]) {
+    dual_pivot_quick_sort_by(arr, T::cmp);
+}
+
+/// Same as dual_pivot_quick_sort, but the order is given by the comparator
+/// `cmp`.
+pub fn dual_pivot_quick_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort(arr, &mut cmp);
+}
+
+/// Same as dual_pivot_quick_sort, but sorts by the key extracted with `f`.
+pub fn dual_pivot_quick_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    dual_pivot_quick_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+fn sort<T, F>(arr: &mut [T], cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() <= CUTOFF {
+        insertion_sort_by(arr, &mut *cmp);
+        return;
+    }
+
+    let (lt, gt) = partition(arr, cmp);
+    sort(&mut arr[..lt], cmp);
+    if cmp(&arr[lt], &arr[gt]) == Ordering::Less {
+        sort(&mut arr[lt + 1..gt], cmp);
+    }
+    sort(&mut arr[gt + 1..], cmp);
+}
+
+/// Partition the array around two pivots, returning the final positions
+/// (lt, gt) of the pivots p1 = arr[lt] and p2 = arr[gt], such that
+/// arr[..lt] < p1 <= arr[lt + 1..gt] <= p2 < arr[gt + 1..].
+///
+/// ### Implementation
+/// Choose the pivots (see choose_pivots) and move them to the ends of the
+/// array. Then scan i from left to right, keeping arr[1..lt] < p1, arr[lt..i]
+/// between the pivots and arr[gt..len - 1] > p2. Finally, swap the pivots into
+/// place at the boundaries of the middle part.
+fn partition<T, F>(arr: &mut [T], cmp: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = arr.len();
+    let (p1, p2) = choose_pivots(arr, cmp);
+    arr.swap(0, p1);
+    arr.swap(n - 1, p2);
+
+    let mut lt = 1;
+    let mut i = 1;
+    let mut gt = n - 1;
+    while i < gt {
+        if cmp(&arr[i], &arr[0]) == Ordering::Less {
+            arr.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if cmp(&arr[i], &arr[n - 1]) == Ordering::Greater {
+            gt -= 1;
+            arr.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+
+    lt -= 1;
+    arr.swap(0, lt);
+    arr.swap(n - 1, gt);
+    (lt, gt)
+}
+
+/// Sort the five entries spaced a seventh of the array apart around the
+/// middle (in place, with insertion sort), and return the indices of the 2nd
+/// and 4th, so the first pivot is <= the second. The array must have more than
+/// CUTOFF entries, so the five indices are distinct.
+fn choose_pivots<T, F>(arr: &mut [T], cmp: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (mid, seventh) = (arr.len() / 2, arr.len() / 7);
+    let sample = [
+        mid - 2 * seventh,
+        mid - seventh,
+        mid,
+        mid + seventh,
+        mid + 2 * seventh,
+    ];
+    for i in 1..sample.len() {
+        let mut j = i;
+        while j > 0 && cmp(&arr[sample[j]], &arr[sample[j - 1]]) == Ordering::Less {
+            arr.swap(sample[j], sample[j - 1]);
+            j -= 1;
+        }
+    }
+    (sample[1], sample[3])
+}
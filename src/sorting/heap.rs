@@ -1,31 +1,57 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in O(n log n) time using no extra space.
+///
+/// This sort is not stable, since sinking entries through the heap scrambles
+/// the order of equal entries.
 pub fn heap_sort<T: Ord>(arr: &mut [T]) {
+    heap_sort_by(arr, T::cmp);
+}
+
+/// Same as heap_sort, but the order is given by the comparator `cmp`.
+pub fn heap_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     // heapify
     for i in (0..arr.len()).rev() {
-        sink(arr, i);
+        sink(arr, i, &mut cmp);
     }
 
     // sort down
     for i in (0..arr.len()).rev() {
         arr.swap(0, i);
-        sink(&mut arr[..i], 0);
+        sink(&mut arr[..i], 0, &mut cmp);
     }
     arr.reverse()
 }
 
-fn sink<T: Ord>(arr: &mut [T], i: usize) {
+/// Same as heap_sort, but sorts by the key extracted with `f`.
+pub fn heap_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    heap_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+fn sink<T, F>(arr: &mut [T], i: usize, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     let l = 2 * i;
     let r = 2 * i + 1;
 
     if l < arr.len() {
-        let min_child_i = if r < arr.len() && arr[r] < arr[l] {
+        let min_child_i = if r < arr.len() && cmp(&arr[r], &arr[l]) == Ordering::Less {
             r
         } else {
             l
         };
 
-        if arr[i] > arr[min_child_i] {
+        if cmp(&arr[i], &arr[min_child_i]) == Ordering::Greater {
             arr.swap(i, min_child_i);
-            sink(arr, min_child_i);
+            sink(arr, min_child_i, cmp);
         }
     }
 }
@@ -1,9 +1,34 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in O(n^2) time (O(n) if the array is already close to
+/// sorted).
+///
+/// This sort is stable, that is, equal entries keep their relative order. An
+/// entry is only ever swapped past a strictly greater entry, so it never moves
+/// before an equal one.
 pub fn insertion_sort<T: Ord>(arr: &mut [T]) {
+    insertion_sort_by(arr, T::cmp);
+}
+
+/// Same as insertion_sort, but the order is given by the comparator `cmp`.
+pub fn insertion_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     for i in 1..arr.len() {
         let mut j = i;
-        while j > 0 && arr[j] < arr[j - 1] {
+        while j > 0 && cmp(&arr[j], &arr[j - 1]) == Ordering::Less {
             arr.swap(j, j - 1);
             j -= 1;
         }
     }
 }
+
+/// Same as insertion_sort, but sorts by the key extracted with `f`.
+pub fn insertion_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    insertion_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
@@ -0,0 +1,97 @@
+use std::cmp::Ordering;
+
+use super::{heap_sort_by, insertion_sort_by, quick::median_of_three};
+
+/// Subarrays of this size or smaller are insertion sorted.
+const CUTOFF: usize = 16;
+
+/// Sorts the array with introsort, in O(n log n) worst case time without any
+/// randomness.
+///
+/// Introsort is quicksort (with median-of-three pivots instead of a shuffle),
+/// that keeps track of the recursion depth. Quicksort only goes quadratic when
+/// the partitions are consistently unbalanced, which makes the recursion deep,
+/// so once the depth passes 2 lg n, the subarray is heap sorted instead. For
+/// typical inputs, the limit is never hit, and it runs like quicksort.
+///
+/// The sort is deterministic, so the same input always gives the same output
+/// (including the order of equal entries). It is not stable.
+///
+/// ### Implementation
+/// Partitioning is Hoare's scheme (as in algs4 Quick), which stops the scans on
+/// entries equal to the pivot, so arrays with lots of duplicates still split
+/// evenly. Subarrays of at most CUTOFF entries are insertion sorted.
+///
+/// See: https://en.wikipedia.org/wiki/Introsort
+pub fn intro_sort<T: Ord>(arr: &mut [T]) {
+    intro_sort_by(arr, T::cmp);
+}
+
+/// Same as intro_sort, but the order is given by the comparator `cmp`.
+pub fn intro_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let depth_limit = 2 * arr.len().max(1).ilog2() as usize;
+    sort(arr, depth_limit, &mut cmp);
+}
+
+/// Same as intro_sort, but sorts by the key extracted with `f`.
+pub fn intro_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    intro_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+fn sort<T, F>(arr: &mut [T], depth_limit: usize, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() <= CUTOFF {
+        insertion_sort_by(arr, &mut *cmp);
+    } else if depth_limit == 0 {
+        heap_sort_by(arr, &mut *cmp);
+    } else {
+        let k = partition(arr, cmp);
+        sort(&mut arr[..k], depth_limit - 1, cmp);
+        sort(&mut arr[k + 1..], depth_limit - 1, cmp);
+    }
+}
+
+/// Partition the array around the median-of-three pivot, returning the final
+/// position k of the pivot, such that arr[..k] <= arr[k] <= arr[k + 1..].
+///
+/// ### Implementation
+/// Move the pivot to the front, and scan i up from the left (stopping at
+/// entries >= pivot) and j down from the right (stopping at entries <= pivot),
+/// swapping the entries they stop at, until the scans cross. The pivot at the
+/// front stops the j scan, so neither scan needs a bounds check on the left.
+fn partition<T, F>(arr: &mut [T], cmp: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let m = median_of_three(arr, cmp);
+    arr.swap(0, m);
+
+    let mut i = 0;
+    let mut j = arr.len();
+    loop {
+        i += 1;
+        while i < arr.len() && cmp(&arr[i], &arr[0]) == Ordering::Less {
+            i += 1;
+        }
+        j -= 1;
+        while cmp(&arr[0], &arr[j]) == Ordering::Less {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        arr.swap(i, j);
+    }
+
+    arr.swap(0, j);
+    j
+}
@@ -1,29 +1,214 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in O(n log n) time using O(n) extra space.
+///
+/// This sort is stable, that is, equal entries keep their relative order.
+/// Merging always takes from the left half when the front entries of both
+/// halves are equal, which is what keeps it stable.
 pub fn merge_sort<T: Ord + Clone>(arr: &mut [T]) {
+    merge_sort_by(arr, T::cmp);
+}
+
+/// Same as merge_sort, but the order is given by the comparator `cmp`.
+pub fn merge_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
     let aux = &mut arr.to_vec()[..];
-    sort(arr, aux);
+    sort(arr, aux, &mut cmp);
+}
+
+/// Same as merge_sort, but sorts by the key extracted with `f`.
+pub fn merge_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_by(arr, |a, b| f(a).cmp(&f(b)));
 }
 
-fn sort<T: Ord + Clone>(arr: &mut [T], aux: &mut [T]) {
+/// Sorts the array with a bottom-up (non-recursive) merge sort, in O(n log n)
+/// time using O(n) extra space.
+///
+/// Instead of recursively splitting the array, we make passes over it merging
+/// subarrays of size 1 into sorted subarrays of size 2, then those into size
+/// 4, and so on, until the whole array is one sorted subarray. Like
+/// merge_sort, this sort is stable.
+///
+/// ### Implementation
+/// Rather than copying into the aux array before every merge, each pass
+/// merges from one of arr and aux into the other, switching directions every
+/// pass, so every pass clones each entry exactly once. If the last pass
+/// merged into aux, it is copied back into arr at the end.
+///
+/// See: https://algs4.cs.princeton.edu/22mergesort/MergeBU.java.html
+pub fn bottom_up_merge_sort<T: Ord + Clone>(arr: &mut [T]) {
+    bottom_up_merge_sort_by(arr, T::cmp);
+}
+
+/// Same as bottom_up_merge_sort, but the order is given by the comparator
+/// `cmp`.
+pub fn bottom_up_merge_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = arr.len();
+    let mut aux = arr.to_vec();
+    let mut in_aux = false;
+    let mut width = 1;
+    while width < n {
+        let (src, dst) = if in_aux {
+            (&aux[..], &mut arr[..])
+        } else {
+            (&arr[..], &mut aux[..])
+        };
+        for lo in (0..n).step_by(2 * width) {
+            let mid = (lo + width).min(n);
+            let hi = (lo + 2 * width).min(n);
+            merge(&mut dst[lo..hi], &src[lo..mid], &src[mid..hi], &mut cmp);
+        }
+        in_aux = !in_aux;
+        width *= 2;
+    }
+    if in_aux {
+        arr.clone_from_slice(&aux);
+    }
+}
+
+/// Same as bottom_up_merge_sort, but sorts by the key extracted with `f`.
+pub fn bottom_up_merge_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bottom_up_merge_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the array with a natural merge sort, which takes advantage of runs
+/// (sorted subarrays) that already exist in the input.
+///
+/// Like bottom_up_merge_sort, but instead of starting from subarrays of size
+/// 1, we start from the runs in the input, and each pass merges adjacent pairs
+/// of runs. With r runs this takes O(n log r) time, so an array that is
+/// already sorted takes a single O(n) scan, and an array made of a few sorted
+/// pieces is sorted in a few passes. This sort is stable.
+///
+/// ### Implementation
+/// Runs are either non-descending, or strictly descending (which are reversed
+/// in place). Descending runs must be strict, otherwise reversing them would
+/// swap the order of equal entries. Passes alternate between arr and aux as in
+/// bottom_up_merge_sort.
+///
+/// See: https://algs4.cs.princeton.edu/22mergesort/ (exercise 2.2.16)
+pub fn natural_merge_sort<T: Ord + Clone>(arr: &mut [T]) {
+    natural_merge_sort_by(arr, T::cmp);
+}
+
+/// Same as natural_merge_sort, but the order is given by the comparator
+/// `cmp`.
+pub fn natural_merge_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = arr.len();
+
+    // bounds[i]..bounds[i + 1] is the ith run
+    let mut bounds = vec![0];
+    while bounds[bounds.len() - 1] < n {
+        let lo = bounds[bounds.len() - 1];
+        bounds.push(lo + count_run(&mut arr[lo..], &mut cmp));
+    }
+
+    let mut aux = arr.to_vec();
+    let mut in_aux = false;
+    while bounds.len() > 2 {
+        let (src, dst) = if in_aux {
+            (&aux[..], &mut arr[..])
+        } else {
+            (&arr[..], &mut aux[..])
+        };
+        let mut merged = vec![0];
+        for pair in bounds[1..].chunks(2) {
+            // an odd run out at the end is just copied over (mid == hi)
+            let lo = merged[merged.len() - 1];
+            let (mid, hi) = (pair[0], pair[pair.len() - 1]);
+            merge(&mut dst[lo..hi], &src[lo..mid], &src[mid..hi], &mut cmp);
+            merged.push(hi);
+        }
+        bounds = merged;
+        in_aux = !in_aux;
+    }
+    if in_aux {
+        arr.clone_from_slice(&aux);
+    }
+}
+
+/// Same as natural_merge_sort, but sorts by the key extracted with `f`.
+pub fn natural_merge_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    natural_merge_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+/// The length of the run at the start of the (non-empty) array, reversing it
+/// first if it is strictly descending, so that arr[..len] is sorted.
+pub(super) fn count_run<T, F>(arr: &mut [T], cmp: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() < 2 {
+        return arr.len();
+    }
+
+    let mut len = 2;
+    if cmp(&arr[1], &arr[0]) == Ordering::Less {
+        while len < arr.len() && cmp(&arr[len], &arr[len - 1]) == Ordering::Less {
+            len += 1;
+        }
+        arr[..len].reverse();
+    } else {
+        while len < arr.len() && cmp(&arr[len], &arr[len - 1]) != Ordering::Less {
+            len += 1;
+        }
+    }
+    len
+}
+
+fn sort<T, F>(arr: &mut [T], aux: &mut [T], cmp: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
     if arr.len() > 1 {
         let m = arr.len() / 2;
-        sort(&mut arr[..m], &mut aux[..m]);
-        sort(&mut arr[m..], &mut aux[m..]);
-        merge(arr, &aux[..m], &aux[m..]);
+        sort(&mut arr[..m], &mut aux[..m], cmp);
+        sort(&mut arr[m..], &mut aux[m..], cmp);
+        merge(arr, &aux[..m], &aux[m..], cmp);
         aux.clone_from_slice(arr);
     }
 }
 
-fn merge<T: Ord + Clone>(arr: &mut [T], left: &[T], right: &[T]) {
+fn merge<T, F>(arr: &mut [T], left: &[T], right: &[T], cmp: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
     let mut i = 0;
     let mut j = 0;
     while i + j < arr.len() {
+        // on ties we take from the left run, so equal entries keep their order
         if i >= left.len() {
             arr[i + j] = right[j].clone();
             j += 1;
-        } else if j >= right.len() {
-            arr[i + j] = left[i].clone();
-            i += 1;
-        } else if left[i] < right[j] {
+        } else if j >= right.len() || cmp(&left[i], &right[j]) != Ordering::Greater {
             arr[i + j] = left[i].clone();
             i += 1;
         } else {
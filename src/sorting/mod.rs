@@ -1,28 +1,75 @@
 mod bubble;
+mod dual_pivot_quick;
 mod heap;
 mod insertion;
+mod intro;
 mod merge;
 mod quick;
+mod quick3way;
 mod selection;
 mod shell;
+mod tim;
 
-pub use bubble::bubble_sort;
-pub use heap::heap_sort;
-pub use insertion::insertion_sort;
-pub use merge::merge_sort;
-pub use quick::quick_sort;
-pub use selection::selection_sort;
-pub use shell::shell_sort;
+pub use bubble::{bubble_sort, bubble_sort_by, bubble_sort_by_key};
+pub use dual_pivot_quick::{
+    dual_pivot_quick_sort, dual_pivot_quick_sort_by, dual_pivot_quick_sort_by_key,
+};
+pub use heap::{heap_sort, heap_sort_by, heap_sort_by_key};
+pub use insertion::{insertion_sort, insertion_sort_by, insertion_sort_by_key};
+pub use intro::{intro_sort, intro_sort_by, intro_sort_by_key};
+pub use merge::{
+    bottom_up_merge_sort, bottom_up_merge_sort_by, bottom_up_merge_sort_by_key, merge_sort,
+    merge_sort_by, merge_sort_by_key, natural_merge_sort, natural_merge_sort_by,
+    natural_merge_sort_by_key,
+};
+pub use quick::{
+    quick_sort, quick_sort_by, quick_sort_by_key, quick_sort_by_with_rng, quick_sort_with_rng,
+};
+pub use quick3way::{quick3way_sort, quick3way_sort_by, quick3way_sort_by_key};
+pub use selection::{selection_sort, selection_sort_by, selection_sort_by_key};
+pub use shell::{shell_sort, shell_sort_by, shell_sort_by_key};
+pub use tim::{tim_sort, tim_sort_by, tim_sort_by_key};
 
 #[cfg(test)]
 mod tests {
-    use rand::Rng;
+    use std::cmp::Ordering;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     use super::*;
 
-    fn test_sort_fn<F>(sort_fn: F)
+    /// A record that is ordered only by it's key, so records with equal keys
+    /// compare as equal even if their payloads differ.
+    #[derive(Clone, Debug)]
+    struct Record {
+        key: i32,
+        payload: usize,
+    }
+
+    impl PartialEq for Record {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for Record {}
+
+    impl PartialOrd for Record {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Record {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    fn test_sort_fn<F, G>(sort_fn: F, sort_records: G, is_stable: bool)
     where
         F: Fn(&mut [i32]),
+        G: Fn(&mut [Record]),
     {
         let test_example = |input: &mut [i32], output: &[i32]| {
             sort_fn(input);
@@ -53,41 +100,367 @@ mod tests {
         let mut ans: [i32; SIZE] = [0; SIZE];
         ans.copy_from_slice(&arr);
         ans.sort();
-        test_example(&mut arr, &ans)
+        test_example(&mut arr, &ans);
+
+        // lots of equal keys, with the payload recording the original position
+        // of each record, so a stable sort keeps the payloads in increasing
+        // order within each run of equal keys
+        let mut records: Vec<Record> = (0..50)
+            .map(|payload| Record {
+                key: rand::thread_rng().gen_range(0..5),
+                payload,
+            })
+            .collect();
+        sort_records(&mut records);
+        assert!(records.windows(2).all(|w| w[0].key <= w[1].key));
+        if is_stable {
+            assert!(records
+                .windows(2)
+                .all(|w| w[0].key < w[1].key || w[0].payload < w[1].payload));
+        }
+        let mut payloads: Vec<usize> = records.iter().map(|r| r.payload).collect();
+        payloads.sort();
+        assert_eq!(payloads, (0..50).collect::<Vec<_>>());
+    }
+
+    fn test_sort_by_fn<F, G, H>(sort_desc: F, sort_floats: G, sort_by_age: H)
+    where
+        F: Fn(&mut [i32]),
+        G: Fn(&mut [f64]),
+        H: Fn(&mut [(&str, u32)]),
+    {
+        let mut arr = [1, 1, 5, 1, 6, 7, -3, 0, 0, 9];
+        sort_desc(&mut arr);
+        assert_eq!(arr, [9, 7, 6, 5, 1, 1, 1, 0, 0, -3]);
+
+        let mut arr = [2.5, -1.0, f64::INFINITY, 0.0, -0.0, 1e-9];
+        sort_floats(&mut arr);
+        assert_eq!(arr, [-1.0, -0.0, 0.0, 1e-9, 2.5, f64::INFINITY]);
+        assert!(arr[1].is_sign_negative());
+
+        let mut arr = [("carol", 35), ("alice", 30), ("dave", 25), ("bob", 40)];
+        sort_by_age(&mut arr);
+        assert_eq!(arr.map(|(name, _)| name), ["dave", "alice", "carol", "bob"]);
+
+        const SIZE: usize = 20;
+        let mut arr: [i32; SIZE] = std::array::from_fn(|_| rand::thread_rng().gen_range(-50..=50));
+        let mut ans = arr;
+        ans.sort_by(|a, b| b.cmp(a));
+        sort_desc(&mut arr);
+        assert_eq!(arr, ans);
+    }
+
+    /// Larger inputs of records for the stable sorts, with the structure that
+    /// the run based sorts look for (runs, partially sorted data, and runs
+    /// that barely overlap, where galloping kicks in). The payloads must come
+    /// out in exactly the same order as with the std stable sort.
+    fn test_large_stable_inputs<F>(sort_fn: F)
+    where
+        F: Fn(&mut [Record]),
+    {
+        const SIZE: usize = 5000;
+        let mut rng = rand::thread_rng();
+        let mut almost_sorted: Vec<i32> = (0..SIZE as i32).map(|i| i / 4).collect();
+        for _ in 0..20 {
+            almost_sorted.swap(rng.gen_range(0..SIZE), rng.gen_range(0..SIZE));
+        }
+        let mut chunks: Vec<i32> = (0..SIZE).map(|_| rng.gen_range(0..1000)).collect();
+        for chunk in chunks.chunks_mut(SIZE / 8) {
+            chunk.sort();
+        }
+        let inputs: [Vec<i32>; 6] = [
+            (0..SIZE).map(|_| rng.gen_range(0..100)).collect(),
+            almost_sorted,
+            chunks,
+            (0..SIZE as i32).rev().map(|i| i / 3).collect(),
+            (0..SIZE as i32).map(|i| i % 1000).collect(),
+            // two sorted halves that only overlap in a few places
+            (0..SIZE as i32)
+                .map(|i| if i < SIZE as i32 / 2 { i } else { i - 10 })
+                .collect(),
+        ];
+        for keys in inputs {
+            let mut records: Vec<Record> = keys
+                .into_iter()
+                .enumerate()
+                .map(|(payload, key)| Record { key, payload })
+                .collect();
+            let mut ans = records.clone();
+            ans.sort_by_key(|r| r.key);
+            sort_fn(&mut records);
+            assert!(records
+                .iter()
+                .zip(&ans)
+                .all(|(a, b)| a.key == b.key && a.payload == b.payload));
+        }
+    }
+
+    /// The run based sorts should only need a single scan (n - 1 compares) for
+    /// an array that is already sorted or reverse sorted, and not many more
+    /// for an array that is sorted except for a few entries at the end.
+    fn test_presorted_compares<F>(sort_by: F)
+    where
+        F: Fn(&mut [i32], &mut dyn FnMut(&i32, &i32) -> Ordering),
+    {
+        const SIZE: usize = 10_000;
+        let count_compares = |mut arr: Vec<i32>| {
+            let mut compares = 0;
+            sort_by(&mut arr, &mut |a, b| {
+                compares += 1;
+                a.cmp(b)
+            });
+            assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+            compares
+        };
+        assert_eq!(count_compares((0..SIZE as i32).collect()), SIZE - 1);
+        assert_eq!(count_compares((0..SIZE as i32).rev().collect()), SIZE - 1);
+        let appended = (0..SIZE as i32).chain([5, 500, 5000]).collect();
+        assert!(count_compares(appended) < 2 * SIZE);
+    }
+
+    /// Larger inputs than test_sort_fn, including the ones that are the worst
+    /// case for a naive quicksort (all equal, few distinct and sorted keys), so
+    /// these would time out for a quadratic sort.
+    fn test_large_inputs<F>(sort_fn: F)
+    where
+        F: Fn(&mut [i32]),
+    {
+        const SIZE: usize = 20_000;
+        let mut rng = rand::thread_rng();
+        let inputs: [Vec<i32>; 5] = [
+            vec![0; SIZE],
+            (0..SIZE).map(|_| rng.gen_range(0..4)).collect(),
+            (0..SIZE).map(|_| rng.gen()).collect(),
+            (0..SIZE as i32).collect(),
+            (0..SIZE as i32).rev().collect(),
+        ];
+        for mut arr in inputs {
+            let mut ans = arr.clone();
+            ans.sort();
+            sort_fn(&mut arr);
+            assert_eq!(arr, ans);
+        }
     }
 
     #[test]
     fn test_bubble_sort() {
-        test_sort_fn(bubble_sort);
+        test_sort_fn(bubble_sort, bubble_sort, true);
+        test_sort_by_fn(
+            |arr| bubble_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| bubble_sort_by(arr, f64::total_cmp),
+            |arr| bubble_sort_by_key(arr, |&(_, age)| age),
+        );
     }
 
     #[test]
     fn test_insertion_sort() {
-        test_sort_fn(insertion_sort);
+        test_sort_fn(insertion_sort, insertion_sort, true);
+        test_sort_by_fn(
+            |arr| insertion_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| insertion_sort_by(arr, f64::total_cmp),
+            |arr| insertion_sort_by_key(arr, |&(_, age)| age),
+        );
     }
 
     #[test]
     fn test_selection_sort() {
-        test_sort_fn(selection_sort);
+        test_sort_fn(selection_sort, selection_sort, false);
+        test_sort_by_fn(
+            |arr| selection_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| selection_sort_by(arr, f64::total_cmp),
+            |arr| selection_sort_by_key(arr, |&(_, age)| age),
+        );
     }
 
     #[test]
     fn test_shell_sort() {
-        test_sort_fn(shell_sort);
+        test_sort_fn(shell_sort, shell_sort, false);
+        test_sort_by_fn(
+            |arr| shell_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| shell_sort_by(arr, f64::total_cmp),
+            |arr| shell_sort_by_key(arr, |&(_, age)| age),
+        );
     }
 
     #[test]
     fn test_quick_sort() {
-        test_sort_fn(quick_sort);
+        test_sort_fn(quick_sort, quick_sort, false);
+        test_sort_by_fn(
+            |arr| quick_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| quick_sort_by(arr, f64::total_cmp),
+            |arr| quick_sort_by_key(arr, |&(_, age)| age),
+        );
+    }
+
+    #[test]
+    fn test_quick3way_sort() {
+        test_sort_fn(quick3way_sort, quick3way_sort, false);
+        test_sort_by_fn(
+            |arr| quick3way_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| quick3way_sort_by(arr, f64::total_cmp),
+            |arr| quick3way_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(quick3way_sort);
+    }
+
+    #[test]
+    fn test_dual_pivot_quick_sort() {
+        test_sort_fn(dual_pivot_quick_sort, dual_pivot_quick_sort, false);
+        test_sort_by_fn(
+            |arr| dual_pivot_quick_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| dual_pivot_quick_sort_by(arr, f64::total_cmp),
+            |arr| dual_pivot_quick_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(dual_pivot_quick_sort);
+    }
+
+    #[test]
+    fn test_quick_sort_with_rng() {
+        test_sort_fn(
+            |arr| quick_sort_with_rng(arr, &mut StdRng::seed_from_u64(0)),
+            |arr| quick_sort_with_rng(arr, &mut StdRng::seed_from_u64(0)),
+            false,
+        );
+
+        // the same seed puts equal entries in the same order
+        let records: Vec<Record> = (0..100)
+            .map(|payload| Record {
+                key: (payload % 3) as i32,
+                payload,
+            })
+            .collect();
+        let payloads = |seed: u64| {
+            let mut records = records.clone();
+            quick_sort_by_with_rng(&mut records, Record::cmp, &mut StdRng::seed_from_u64(seed));
+            records.into_iter().map(|r| r.payload).collect::<Vec<_>>()
+        };
+        assert_eq!(payloads(1), payloads(1));
+        assert_ne!(payloads(1), payloads(2));
+    }
+
+    #[test]
+    fn test_intro_sort() {
+        test_sort_fn(intro_sort, intro_sort, false);
+        test_sort_by_fn(
+            |arr| intro_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| intro_sort_by(arr, f64::total_cmp),
+            |arr| intro_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(intro_sort);
+
+        // no randomness, so equal entries always end up in the same order
+        let records: Vec<Record> = (0..100)
+            .map(|payload| Record {
+                key: (payload % 3) as i32,
+                payload,
+            })
+            .collect();
+        let mut a = records.clone();
+        let mut b = records;
+        intro_sort(&mut a);
+        intro_sort(&mut b);
+        assert!(a.iter().zip(&b).all(|(a, b)| a.payload == b.payload));
+
+        // McIlroy's adversary: the values start out as "gas" (greater than
+        // everything), and are frozen to increasing values only as the sort
+        // compares them, in a way that makes each pivot as small as possible.
+        // This builds a worst case input for any quicksort on the fly, so
+        // without the depth limit this would take ~n^2/2 compares.
+        const SIZE: usize = 4096;
+        let gas = SIZE;
+        let mut vals = [gas; SIZE];
+        let mut frozen = 0;
+        let mut candidate = 0;
+        let mut compares = 0;
+        let mut arr: Vec<usize> = (0..SIZE).collect();
+        intro_sort_by(&mut arr, |&x, &y| {
+            compares += 1;
+            if vals[x] == gas && vals[y] == gas {
+                let z = if x == candidate { x } else { y };
+                vals[z] = frozen;
+                frozen += 1;
+            }
+            if vals[x] == gas {
+                candidate = x;
+            } else if vals[y] == gas {
+                candidate = y;
+            }
+            vals[x].cmp(&vals[y])
+        });
+        assert!(arr.windows(2).all(|w| vals[w[0]] <= vals[w[1]]));
+        assert!(compares <= 4 * SIZE * SIZE.ilog2() as usize);
     }
 
     #[test]
     fn test_merge_sort() {
-        test_sort_fn(merge_sort);
+        test_sort_fn(merge_sort, merge_sort, true);
+        test_sort_by_fn(
+            |arr| merge_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| merge_sort_by(arr, f64::total_cmp),
+            |arr| merge_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_stable_inputs(merge_sort);
+    }
+
+    #[test]
+    fn test_bottom_up_merge_sort() {
+        test_sort_fn(bottom_up_merge_sort, bottom_up_merge_sort, true);
+        test_sort_by_fn(
+            |arr| bottom_up_merge_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| bottom_up_merge_sort_by(arr, f64::total_cmp),
+            |arr| bottom_up_merge_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(bottom_up_merge_sort);
+        test_large_stable_inputs(bottom_up_merge_sort);
+    }
+
+    #[test]
+    fn test_natural_merge_sort() {
+        test_sort_fn(natural_merge_sort, natural_merge_sort, true);
+        test_sort_by_fn(
+            |arr| natural_merge_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| natural_merge_sort_by(arr, f64::total_cmp),
+            |arr| natural_merge_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(natural_merge_sort);
+        test_large_stable_inputs(natural_merge_sort);
+        test_presorted_compares(|arr, cmp| natural_merge_sort_by(arr, cmp));
+    }
+
+    #[test]
+    fn test_tim_sort() {
+        test_sort_fn(tim_sort, tim_sort, true);
+        test_sort_by_fn(
+            |arr| tim_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| tim_sort_by(arr, f64::total_cmp),
+            |arr| tim_sort_by_key(arr, |&(_, age)| age),
+        );
+        test_large_inputs(tim_sort);
+        test_large_stable_inputs(tim_sort);
+        test_presorted_compares(|arr, cmp| tim_sort_by(arr, cmp));
+
+        // two runs made of interleaved blocks of 1000, so the merge takes
+        // long streaks from each run, which galloping does in O(log 1000)
+        // compares per block instead of 1000
+        const SIZE: usize = 20_000;
+        let blocks = |parity| (0..SIZE).filter(move |i| (i / 1000) % 2 == parity);
+        let mut arr: Vec<usize> = blocks(0).chain(blocks(1)).collect();
+        let mut compares = 0;
+        tim_sort_by(&mut arr, |a, b| {
+            compares += 1;
+            a.cmp(b)
+        });
+        assert_eq!(arr, (0..SIZE).collect::<Vec<_>>());
+        assert!(compares < SIZE + SIZE / 10);
     }
 
     #[test]
     fn test_heap_sort() {
-        test_sort_fn(heap_sort);
+        test_sort_fn(heap_sort, heap_sort, false);
+        test_sort_by_fn(
+            |arr| heap_sort_by(arr, |a, b| b.cmp(a)),
+            |arr| heap_sort_by(arr, f64::total_cmp),
+            |arr| heap_sort_by_key(arr, |&(_, age)| age),
+        );
     }
 }
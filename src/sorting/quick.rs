@@ -1,24 +1,77 @@
-use crate::random::shuffle_arr;
+use std::cmp::Ordering;
 
+use rand::Rng;
+
+use crate::random::shuffle_arr_with_rng;
+
+/// Sorts the array in O(n log n) expected time, shuffling it first so the
+/// worst case O(n^2) is vanishingly unlikely.
+///
+/// This sort is not stable, both the shuffle and partitioning reorder equal
+/// entries.
+///
+/// Since the shuffle uses the thread's random number generator, the order of
+/// equal entries (and the running time) differs between runs. See
+/// quick_sort_with_rng to use a seeded generator instead, or intro_sort for a
+/// sort that needs no randomness at all.
 pub fn quick_sort<T: Ord>(arr: &mut [T]) {
-    shuffle_arr(arr);
-    sort(arr);
+    quick_sort_by(arr, T::cmp);
+}
+
+/// Same as quick_sort, but the order is given by the comparator `cmp`.
+pub fn quick_sort_by<T, F>(arr: &mut [T], cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_sort_by_with_rng(arr, cmp, &mut rand::thread_rng());
+}
+
+/// Same as quick_sort, but the shuffle uses the given random number generator,
+/// so that with a seeded generator the result is reproducible.
+pub fn quick_sort_with_rng<T: Ord, R: Rng + ?Sized>(arr: &mut [T], rng: &mut R) {
+    quick_sort_by_with_rng(arr, T::cmp, rng);
+}
+
+/// Same as quick_sort_by, but the shuffle uses the given random number
+/// generator (see quick_sort_with_rng).
+pub fn quick_sort_by_with_rng<T, F, R>(arr: &mut [T], mut cmp: F, rng: &mut R)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    R: Rng + ?Sized,
+{
+    shuffle_arr_with_rng(arr, rng);
+    sort(arr, &mut cmp);
+}
+
+/// Same as quick_sort, but sorts by the key extracted with `f`.
+pub fn quick_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    quick_sort_by(arr, |a, b| f(a).cmp(&f(b)));
 }
 
-fn sort<T: Ord>(arr: &mut [T]) {
+fn sort<T, F>(arr: &mut [T], cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     if arr.len() > 1 {
-        let k = partition(arr);
-        sort(&mut arr[..k]);
-        sort(&mut arr[k + 1..]);
+        let k = partition(arr, cmp);
+        sort(&mut arr[..k], cmp);
+        sort(&mut arr[k + 1..], cmp);
     }
 }
 
-fn partition<T: Ord>(arr: &mut [T]) -> usize {
+fn partition<T, F>(arr: &mut [T], cmp: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     let k = arr.len() - 1;
 
     let mut i = 0;
     for j in 0..k {
-        if arr[j] < arr[k] {
+        if cmp(&arr[j], &arr[k]) == Ordering::Less {
             arr.swap(i, j);
             i += 1;
         }
@@ -27,3 +80,29 @@ fn partition<T: Ord>(arr: &mut [T]) -> usize {
     arr.swap(i, k);
     i
 }
+
+/// The index of the median of the first, middle and last entries of the array
+/// (which must be non-empty). Used as the pivot, this makes the worst case
+/// much less likely on inputs that are already (or reverse) sorted.
+pub(super) fn median_of_three<T, F>(arr: &[T], cmp: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (i, j, k) = (0, arr.len() / 2, arr.len() - 1);
+    let less = |a: usize, b: usize, cmp: &mut F| cmp(&arr[a], &arr[b]) == Ordering::Less;
+    if less(i, j, cmp) {
+        if less(j, k, cmp) {
+            j
+        } else if less(i, k, cmp) {
+            k
+        } else {
+            i
+        }
+    } else if less(i, k, cmp) {
+        i
+    } else if less(j, k, cmp) {
+        k
+    } else {
+        j
+    }
+}
@@ -0,0 +1,98 @@
+use std::cmp::Ordering;
+
+use crate::random::shuffle_arr;
+
+use super::{insertion_sort_by, quick::median_of_three};
+
+/// Subarrays of this size or smaller are insertion sorted.
+const CUTOFF: usize = 10;
+
+/// Sorts the array with quicksort, using Dijkstra's 3-way partitioning.
+///
+/// Each partition splits the array into the entries less than, equal to and
+/// greater than the pivot, and only the less and greater parts are sorted
+/// recursively. Entries equal to the pivot are never looked at again, so
+/// arrays with lots of duplicate keys are sorted in close to linear time (an
+/// array of all equal entries takes a single pass), where quick_sort would be
+/// quadratic.
+///
+/// This sort is not stable.
+///
+/// ### Implementation
+/// Like quick_sort, the array is shuffled first, so the worst case O(n^2) is
+/// vanishingly unlikely. On top of that, the pivot is the median of the first,
+/// middle and last entries (which gives more balanced partitions), and
+/// subarrays of at most CUTOFF entries are insertion sorted instead of
+/// partitioned.
+///
+/// See: https://algs4.cs.princeton.edu/23quicksort/Quick3way.java.html
+pub fn quick3way_sort<T: Ord>(arr: &mut [T]) {
+    quick3way_sort_by(arr, T::cmp);
+}
+
+/// Same as quick3way_sort, but the order is given by the comparator `cmp`.
+pub fn quick3way_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shuffle_arr(arr);
+    sort(arr, &mut cmp);
+}
+
+/// Same as quick3way_sort, but sorts by the key extracted with `f`.
+pub fn quick3way_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    quick3way_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+fn sort<T, F>(arr: &mut [T], cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() <= CUTOFF {
+        insertion_sort_by(arr, &mut *cmp);
+        return;
+    }
+
+    let (lt, gt) = partition(arr, cmp);
+    sort(&mut arr[..lt], cmp);
+    sort(&mut arr[gt..], cmp);
+}
+
+/// Partition the array around the pivot, returning (lt, gt) such that
+/// arr[..lt] < pivot, arr[lt..gt] == pivot and arr[gt..] > pivot.
+///
+/// ### Implementation
+/// Scan i from left to right, keeping arr[lt..i] equal to the pivot (so the
+/// pivot is always at arr[lt]) and arr[gt..] greater than the pivot. Smaller
+/// entries are swapped down to lt, greater entries are swapped up to gt, and
+/// equal entries are skipped over.
+fn partition<T, F>(arr: &mut [T], cmp: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let m = median_of_three(arr, cmp);
+    arr.swap(0, m);
+
+    let mut lt = 0;
+    let mut i = 1;
+    let mut gt = arr.len();
+    while i < gt {
+        match cmp(&arr[i], &arr[lt]) {
+            Ordering::Less => {
+                arr.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                arr.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
@@ -1,11 +1,34 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in O(n^2) time.
+///
+/// This sort is not stable. Swapping the minimum into place can move the
+/// entry it's swapped with past other entries equal to it.
 pub fn selection_sort<T: Ord>(arr: &mut [T]) {
+    selection_sort_by(arr, T::cmp);
+}
+
+/// Same as selection_sort, but the order is given by the comparator `cmp`.
+pub fn selection_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     for i in 1..arr.len() {
         let mut min_idx = i - 1;
         for j in i..arr.len() {
-            if arr[j] < arr[min_idx] {
+            if cmp(&arr[j], &arr[min_idx]) == Ordering::Less {
                 min_idx = j;
             }
         }
         arr.swap(i - 1, min_idx);
     }
 }
+
+/// Same as selection_sort, but sorts by the key extracted with `f`.
+pub fn selection_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    selection_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
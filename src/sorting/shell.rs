@@ -1,15 +1,42 @@
+use std::cmp::Ordering;
+
+/// Sorts the array in roughly O(n^(3/2)) time with the 3x+1 increment
+/// sequence.
+///
+/// This sort is not stable, since h-sorting swaps entries that are h apart,
+/// jumping over any equal entries in between.
 pub fn shell_sort<T: Ord>(arr: &mut [T]) {
+    shell_sort_by(arr, T::cmp);
+}
+
+/// Same as shell_sort, but the order is given by the comparator `cmp`.
+pub fn shell_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     let mut h = calc_max_h(arr.len());
     while h > 0 {
-        h_sort(arr, h);
+        h_sort(arr, h, &mut cmp);
         h /= 3;
     }
 }
 
-fn h_sort<T: Ord>(arr: &mut [T], h: usize) {
+/// Same as shell_sort, but sorts by the key extracted with `f`.
+pub fn shell_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    shell_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+fn h_sort<T, F>(arr: &mut [T], h: usize, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     for i in h..arr.len() {
         let mut j = i;
-        while j >= h && arr[j] < arr[j - h] {
+        while j >= h && cmp(&arr[j], &arr[j - h]) == Ordering::Less {
             arr.swap(j, j - h);
             j -= h;
         }
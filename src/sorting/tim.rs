@@ -0,0 +1,317 @@
+use std::cmp::Ordering;
+
+use super::merge::count_run;
+
+/// Runs shorter than this are never merged with galloping, they're extended
+/// to at least min_run entries with binary insertion sort.
+const MIN_MERGE: usize = 64;
+
+/// The initial number of consecutive wins by one run before a merge switches
+/// to galloping mode.
+const MIN_GALLOP: usize = 7;
+
+/// Sorts the array with TimSort, a natural merge sort tuned for real world
+/// data, which is often partially sorted.
+///
+/// The array is split into runs (as in natural_merge_sort), where short runs
+/// are extended to a minimum length with binary insertion sort. Runs are
+/// pushed on a stack and merged with their neighbours as soon as the stack
+/// would become unbalanced, which keeps merges between runs of similar size
+/// (like merge sort), while still exploiting the runs that exist in the input.
+///
+/// When merging, if one run keeps "winning" (contributing the next entry),
+/// the merge switches to galloping mode, where it finds how many entries in a
+/// row come from that run with an exponential search, and moves them over in
+/// one go. On data like two sorted runs that barely overlap, this makes
+/// merging take O(log n) compares instead of O(n).
+///
+/// This takes O(n log n) time in the worst case and O(n) for an array that is
+/// already (or reverse) sorted, using O(n) extra space. This sort is stable.
+///
+/// ### Implementation
+/// This follows the description in listsort.txt, with a few simplifications.
+/// Merges always copy the left run to a temporary array and merge from the
+/// front (merge_lo, there is no merge_hi), and searches always start at the
+/// front of the run instead of at a hint. The stack invariants are checked for
+/// the top four runs, which fixes the bug found in the original in 2015.
+///
+/// See: https://github.com/python/cpython/blob/main/Objects/listsort.txt
+pub fn tim_sort<T: Ord + Clone>(arr: &mut [T]) {
+    tim_sort_by(arr, T::cmp);
+}
+
+/// Same as tim_sort, but the order is given by the comparator `cmp`.
+pub fn tim_sort_by<T, F>(arr: &mut [T], mut cmp: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = arr.len();
+    let min_run = min_run(n);
+    let mut sorter = TimSort {
+        arr,
+        cmp: &mut cmp,
+        runs: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+
+    let mut lo = 0;
+    while lo < n {
+        let mut len = count_run(&mut sorter.arr[lo..], sorter.cmp);
+        if len < min_run {
+            let end = (lo + min_run).min(n);
+            binary_insertion_sort(&mut sorter.arr[lo..end], len, sorter.cmp);
+            len = end - lo;
+        }
+        sorter.runs.push(Run { start: lo, len });
+        sorter.merge_collapse();
+        lo += len;
+    }
+    sorter.merge_force_collapse();
+}
+
+/// Same as tim_sort, but sorts by the key extracted with `f`.
+pub fn tim_sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    tim_sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+/// A sorted run arr[start..start + len].
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct TimSort<'a, T, F> {
+    arr: &'a mut [T],
+    cmp: &'a mut F,
+    /// The stack of runs that are waiting to be merged, in order from left to
+    /// right in the array.
+    runs: Vec<Run>,
+    /// How many consecutive wins before we start galloping. This adapts to the
+    /// data, going down while galloping pays off and up while it doesn't.
+    min_gallop: usize,
+}
+
+impl<T, F> TimSort<'_, T, F>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    /// Merge runs on the top of the stack until, for the top four runs
+    /// A, B, C, D (D on top), we have A > B + C, B > C + D and C > D. This
+    /// keeps the run lengths growing at least as fast as the fibonacci numbers
+    /// going down the stack, so the stack has O(log n) runs, and merges are
+    /// between runs of similar lengths.
+    fn merge_collapse(&mut self) {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            let len = |i: usize| self.runs[i].len;
+            if (n >= 3 && len(n - 3) <= len(n - 2) + len(n - 1))
+                || (n >= 4 && len(n - 4) <= len(n - 3) + len(n - 2))
+            {
+                if len(n - 3) < len(n - 1) {
+                    self.merge_at(n - 3);
+                } else {
+                    self.merge_at(n - 2);
+                }
+            } else if len(n - 2) <= len(n - 1) {
+                self.merge_at(n - 2);
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Merge all the runs on the stack into one, once all the runs are found.
+    fn merge_force_collapse(&mut self) {
+        while self.runs.len() > 1 {
+            let n = self.runs.len();
+            if n >= 3 && self.runs[n - 3].len < self.runs[n - 1].len {
+                self.merge_at(n - 3);
+            } else {
+                self.merge_at(n - 2);
+            }
+        }
+    }
+
+    /// Merge the runs at i and i + 1 on the stack.
+    ///
+    /// ### Implementation
+    /// Before merging, we gallop to find where the first entry of the right
+    /// run goes in the left run, and where the last entry of the left run goes
+    /// in the right run. The entries outside of those are already in place, so
+    /// only the part in between needs to be merged.
+    fn merge_at(&mut self, i: usize) {
+        let right = self.runs.remove(i + 1);
+        let left = &mut self.runs[i];
+        let lo = left.start;
+        let mid = right.start;
+        let hi = right.start + right.len;
+        left.len += right.len;
+
+        let cmp = &mut *self.cmp;
+        let arr = &mut *self.arr;
+        let lo = lo + gallop(&arr[lo..mid], |x| cmp(x, &arr[mid]) != Ordering::Greater);
+        if lo == mid {
+            return;
+        }
+        let hi = mid + gallop(&arr[mid..hi], |x| cmp(x, &arr[mid - 1]) == Ordering::Less);
+        self.merge_lo(lo, mid, hi);
+    }
+
+    /// Merge the sorted runs arr[lo..mid] and arr[mid..hi], where
+    /// arr[lo] > arr[mid] and arr[mid - 1] > arr[hi - 1] (so both runs are
+    /// non-empty and neither run is used up before the merge starts).
+    ///
+    /// ### Implementation
+    /// The left run is copied to a temporary array, and merged with the right
+    /// run into arr from the front. The write position never passes the read
+    /// position in the right run, so nothing is overwritten before it's read.
+    ///
+    /// The merge starts out one entry at a time, counting how many times in a
+    /// row each run wins. Once one run wins min_gallop times, we switch to
+    /// galloping, where each step finds (with an exponential search) how many
+    /// entries in a row come from the left run, then from the right run, and
+    /// moves them all over. Once galloping stops paying off (both counts are
+    /// less than MIN_GALLOP), we switch back. Ties always go to the left run,
+    /// which keeps the merge stable.
+    fn merge_lo(&mut self, lo: usize, mid: usize, hi: usize) {
+        let cmp = &mut *self.cmp;
+        let arr = &mut self.arr[lo..hi];
+        let left = arr[..mid - lo].to_vec();
+        let n = arr.len();
+        let (mut i, mut j, mut k) = (0, mid - lo, 0);
+
+        'merge: loop {
+            let mut left_wins = 0;
+            let mut right_wins = 0;
+            while left_wins < self.min_gallop && right_wins < self.min_gallop {
+                if cmp(&arr[j], &left[i]) == Ordering::Less {
+                    arr[k] = arr[j].clone();
+                    j += 1;
+                    right_wins += 1;
+                    left_wins = 0;
+                } else {
+                    arr[k] = left[i].clone();
+                    i += 1;
+                    left_wins += 1;
+                    right_wins = 0;
+                }
+                k += 1;
+                if i == left.len() || j == n {
+                    break 'merge;
+                }
+            }
+
+            loop {
+                let from_left = gallop(&left[i..], |x| cmp(x, &arr[j]) != Ordering::Greater);
+                arr[k..k + from_left].clone_from_slice(&left[i..i + from_left]);
+                i += from_left;
+                k += from_left;
+                if i == left.len() {
+                    break 'merge;
+                }
+                arr[k] = arr[j].clone();
+                j += 1;
+                k += 1;
+                if j == n {
+                    break 'merge;
+                }
+
+                let from_right = gallop(&arr[j..], |x| cmp(x, &left[i]) == Ordering::Less);
+                for _ in 0..from_right {
+                    arr[k] = arr[j].clone();
+                    j += 1;
+                    k += 1;
+                }
+                if j == n {
+                    break 'merge;
+                }
+                arr[k] = left[i].clone();
+                i += 1;
+                k += 1;
+                if i == left.len() {
+                    break 'merge;
+                }
+
+                if from_left < MIN_GALLOP && from_right < MIN_GALLOP {
+                    // galloping isn't paying off, make it harder to get back in
+                    self.min_gallop += 1;
+                    break;
+                }
+                self.min_gallop = self.min_gallop.saturating_sub(1).max(1);
+            }
+        }
+
+        // the rest of the right run is already in place
+        arr[k..k + left.len() - i].clone_from_slice(&left[i..]);
+    }
+}
+
+/// The number of entries at the start of the run for which `is_before` is
+/// true (where the run is partitioned, so it's true for a prefix of the run).
+///
+/// ### Implementation
+/// An exponential search, check the entries at 0, 2, 6, 14, ... (doubling the
+/// gap each time) until we overshoot, and then binary search between the last
+/// two checks. This takes O(log k) compares for an answer of k, so it's cheap
+/// when k is small (unlike a plain binary search over the whole run).
+fn gallop<T, P>(run: &[T], mut is_before: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    // run[..lo] are known to be before, and run[hi..] (if hi is in bounds) are
+    // known to not be
+    let mut lo = 0;
+    let mut hi = 1;
+    while hi <= run.len() && is_before(&run[hi - 1]) {
+        lo = hi;
+        hi = 2 * hi + 1;
+    }
+    let mut hi = (hi - 1).min(run.len());
+    while lo < hi {
+        let m = (lo + hi) / 2;
+        if is_before(&run[m]) {
+            lo = m + 1;
+        } else {
+            hi = m;
+        }
+    }
+    lo
+}
+
+/// Sort the array, given that arr[..sorted] is already sorted, by inserting
+/// the rest of the entries one at a time. Each insertion point is found with
+/// gallop (after any equal entries, so the sort is stable), which makes the
+/// compares O(n log n), although the moves are still O(n^2).
+fn binary_insertion_sort<T, F>(arr: &mut [T], sorted: usize, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in sorted..arr.len() {
+        let (before, rest) = arr.split_at(i);
+        let j = gallop(before, |x| cmp(x, &rest[0]) != Ordering::Greater);
+        arr[j..=i].rotate_right(1);
+    }
+}
+
+/// The minimum run length for an array of length n. For small arrays this is
+/// just n (so the whole array is binary insertion sorted). Otherwise it's
+/// a number in [MIN_MERGE / 2, MIN_MERGE] such that n / min_run is a power of
+/// two, or a little less than one, so the final merges are balanced.
+///
+/// ### Implementation
+/// Take the top 6 bits of n, and add one if any of the remaining bits are set.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
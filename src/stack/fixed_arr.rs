@@ -1,5 +1,11 @@
 use super::Stack;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
 /// Stack implementation using a fixed length array.
 ///
 /// We push elements onto the end of the array, and pop elements from the end,
@@ -9,6 +15,12 @@ pub struct StackFixedArray<T, const CAPACITY: usize> {
     size: usize,
 }
 
+impl<T, const CAPACITY: usize> Default for StackFixedArray<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T, const CAPACITY: usize> StackFixedArray<T, CAPACITY> {
     const INIT: Option<T> = None;
 
@@ -65,3 +77,26 @@ impl<T, const CAPACITY: usize> Stack<T> for StackFixedArray<T, CAPACITY> {
         self.size
     }
 }
+
+/// Serialized as a sequence of the items from the bottom to the top of the
+/// stack, and rebuilt on load by pushing them back in order (which fails if
+/// there are more than CAPACITY items).
+#[cfg(feature = "serde")]
+impl<T: Serialize, const CAPACITY: usize> Serialize for StackFixedArray<T, CAPACITY> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size,
+            self.arr[..self.size].iter().flatten(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for StackFixedArray<T, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |stack, item| stack.push(item))
+    }
+}
@@ -1,5 +1,14 @@
 use super::Stack;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::{
+    list::LinkedList,
+    utils::{deserialize_seq, serialize_seq},
+};
+
 type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T> {
@@ -22,6 +31,12 @@ pub struct StackLinkedList<T> {
     size: usize,
 }
 
+impl<T> Default for StackLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> StackLinkedList<T> {
     pub fn new() -> Self {
         StackLinkedList {
@@ -69,3 +84,26 @@ impl<T> Stack<T> for StackLinkedList<T> {
         self.size
     }
 }
+
+/// Serialized as a sequence of the items from the bottom to the top of the
+/// stack, and rebuilt on load by pushing them back in order.
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for StackLinkedList<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // the list goes from the top of the stack to the bottom
+        let mut items = LinkedList::new();
+        let mut link = &self.head;
+        while let Some(node) = link {
+            items.push_front(&node.item);
+            link = &node.next;
+        }
+        serialize_seq(serializer, items.len(), &items)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for StackLinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |stack, item| stack.push(item))
+    }
+}
@@ -21,7 +21,7 @@ mod test {
         assert_eq!(stack.pop(), None);
         assert_eq!(stack.peek(), None);
         assert_eq!(stack.size(), 0);
-        assert_eq!(stack.is_empty(), true);
+        assert!(stack.is_empty());
 
         stack.push(1).unwrap();
         stack.push(2).unwrap();
@@ -29,7 +29,7 @@ mod test {
         stack.push(4).unwrap();
 
         assert_eq!(stack.size(), 4);
-        assert_eq!(stack.is_empty(), false);
+        assert!(!stack.is_empty());
 
         assert_eq!(stack.peek(), Some(&4));
         assert_eq!(stack.pop(), Some(4));
@@ -37,7 +37,7 @@ mod test {
         assert_eq!(stack.pop(), Some(2));
 
         assert_eq!(stack.size(), 1);
-        assert_eq!(stack.is_empty(), false);
+        assert!(!stack.is_empty());
         assert_eq!(stack.peek(), Some(&1));
 
         stack.push(5).unwrap();
@@ -51,7 +51,7 @@ mod test {
         assert_eq!(stack.pop(), None);
         assert_eq!(stack.peek(), None);
         assert_eq!(stack.size(), 0);
-        assert_eq!(stack.is_empty(), true);
+        assert!(stack.is_empty());
     }
 
     #[test]
@@ -72,4 +72,24 @@ mod test {
         assert!(stack.push(5).is_ok());
         assert!(stack.push(6).is_err());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut stack = StackLinkedList::new();
+        for item in [1, 2, 3] {
+            stack.push(item).unwrap();
+        }
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let mut loaded: StackFixedArray<i32, 4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert_eq!(loaded.pop(), Some(3));
+        let bytes = bincode::serialize(&stack).unwrap();
+        let mut loaded: StackLinkedList<i32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.pop(), Some(3));
+
+        assert!(serde_json::from_str::<StackFixedArray<i32, 2>>(&json).is_err());
+    }
 }
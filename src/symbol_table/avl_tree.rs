@@ -0,0 +1,802 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Deref, DerefMut, RangeBounds},
+};
+
+use crate::{
+    list::LinkedList,
+    stack::{Stack, StackLinkedList},
+};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+pub struct Node<K: KeyT, V> {
+    key: K,
+    val: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    height: usize,
+    size: usize,
+}
+
+impl<K: KeyT, V> Node<K, V> {
+    fn new(key: K, val: V) -> Self {
+        Node {
+            key,
+            val,
+            left: None,
+            right: None,
+            height: 1,
+            size: 1,
+        }
+    }
+
+    /// Recompute the height and size of the subtree rooted at this node from
+    /// those of it's children. Must be called on the way back up the tree
+    /// whenever the children of a node change.
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    /// Height of the left subtree minus the height of the right subtree.
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+/// Height of the subtree, that is the number of nodes on the longest path from
+/// it's root to a leaf (0 for a null link).
+fn height<K: KeyT, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+/// Number of nodes in the subtree (0 for a null link).
+fn size<K: KeyT, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// An AVL tree that stores key-value pairs.
+///
+/// An AVL tree is a binary search tree with the constraint that for any given
+/// node, the heights of it's left and right subtrees differ by at most one.
+/// Each node stores the height of it's subtree, and after an insert or delete
+/// we walk back up the search path, using rotations to fix any node whose
+/// subtrees have drifted out of balance.
+///
+/// The balance condition is stricter than that of a red black tree: the
+/// height of an AVL tree is at most ~1.44 log n, compared to ~2 log n for a
+/// red black tree. So searches are a bit faster, in exchange for doing a bit
+/// more work to rebalance on insert and delete. All operations are O(log n).
+///
+/// As with the other trees, each node also stores the number of nodes in it's
+/// subtree for rank and select.
+///
+/// See: https://en.wikipedia.org/wiki/AVL_tree
+pub struct AVLTree<K: KeyT, V> {
+    root: Link<K, V>,
+}
+
+impl<K: KeyT, V> Default for AVLTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V> AVLTree<K, V> {
+    pub fn new() -> Self {
+        AVLTree { root: None }
+    }
+
+    /// Height of the tree, that is the number of nodes on the longest path
+    /// from the root to a leaf (0 if the tree is empty). This is O(1), since
+    /// each node stores the height of it's subtree.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    ///
+    /// ### Implementation
+    /// We collect the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n) time and space.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        fn visit<'a, K: KeyT, V>(
+            link: &'a mut Link<K, V>,
+            list: &mut LinkedList<(&'a K, &'a mut V)>,
+        ) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = node.deref_mut();
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(&mut self.root, &mut list);
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// Same as for RedBlackBST, we only visit O(height + number of entries in
+    /// the range) nodes.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+
+    /// Given a node n whose right subtree is taller, rotate the structure so
+    /// that it's right child x becomes the root of the subtree:
+    ///
+    /// ```text
+    ///        n                         x
+    ///      /   \                     /   \
+    ///     a     x        --->       n     c
+    ///         /   \               /   \
+    ///        b     c             a     b
+    /// ```
+    ///
+    /// This preserves the symmetric order of the keys, and moves one level of
+    /// height from the right side to the left side.
+    fn rotate_left(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut x = n.right.take().unwrap();
+        n.right = x.left.take();
+        n.update();
+        x.left = Some(n);
+        x.update();
+        x
+    }
+
+    /// Mirror image of rotate_left.
+    fn rotate_right(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let mut x = n.left.take().unwrap();
+        n.left = x.right.take();
+        n.update();
+        x.right = Some(n);
+        x.update();
+        x
+    }
+
+    /// Restores the AVL property at node n, assuming it's subtrees are valid
+    /// AVL trees whose heights differ by at most two (which is the case after
+    /// inserting or deleting a single node). There are four cases:
+    ///
+    ///   1) left-left: the left subtree is too tall, and so is it's left
+    ///      subtree, so we rotate n right
+    ///   2) left-right: the left subtree is too tall, but it's right subtree
+    ///      is taller, so we first rotate the left child left (making it a
+    ///      left-left case) and then rotate n right
+    ///   3) right-right: mirror image of 1)
+    ///   4) right-left: mirror image of 2)
+    ///
+    /// Also updates the height and size of n.
+    fn balance(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        n.update();
+        if n.balance_factor() > 1 {
+            if n.left.as_ref().unwrap().balance_factor() < 0 {
+                n.left = Some(Self::rotate_left(n.left.take().unwrap()));
+            }
+            n = Self::rotate_right(n);
+        } else if n.balance_factor() < -1 {
+            if n.right.as_ref().unwrap().balance_factor() > 0 {
+                n.right = Some(Self::rotate_right(n.right.take().unwrap()));
+            }
+            n = Self::rotate_left(n);
+        }
+        n
+    }
+
+    fn insert(link: Link<K, V>, node_to_insert: Node<K, V>) -> Box<Node<K, V>> {
+        match link {
+            None => Box::new(node_to_insert),
+            Some(mut node) => {
+                match node_to_insert.key.cmp(&node.key) {
+                    Ordering::Equal => {
+                        node.val = node_to_insert.val;
+                        return node;
+                    }
+                    Ordering::Less => {
+                        node.left = Some(Self::insert(node.left.take(), node_to_insert))
+                    }
+                    Ordering::Greater => {
+                        node.right = Some(Self::insert(node.right.take(), node_to_insert))
+                    }
+                }
+                Self::balance(node)
+            }
+        }
+    }
+
+    /// Deletes the minimum node in the subtree rooted at n, returning the new
+    /// subtree and the deleted node.
+    fn delete_min(mut n: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+        match n.left.take() {
+            None => (n.right.take(), n),
+            Some(left) => {
+                let deleted;
+                (n.left, deleted) = Self::delete_min(left);
+                (Some(Self::balance(n)), deleted)
+            }
+        }
+    }
+
+    /// Deletes the maximum node in the subtree rooted at n, returning the new
+    /// subtree and the deleted node.
+    fn delete_max(mut n: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+        match n.right.take() {
+            None => (n.left.take(), n),
+            Some(right) => {
+                let deleted;
+                (n.right, deleted) = Self::delete_max(right);
+                (Some(Self::balance(n)), deleted)
+            }
+        }
+    }
+
+    /// Deletes the node with the given key in the subtree rooted at n,
+    /// returning the new subtree and the deleted node (None if the key does
+    /// not exist).
+    fn delete<Q: ?Sized + Ord>(mut n: Box<Node<K, V>>, key: &Q) -> (Link<K, V>, Link<K, V>)
+    where
+        K: Borrow<Q>,
+    {
+        let deleted;
+        match key.cmp(n.key.borrow()) {
+            Ordering::Equal => {
+                // replace n with it's successor (see BinarySearchTree::pop)
+                return match n.right.take() {
+                    None => (n.left.take(), Some(n)),
+                    Some(right) => {
+                        let (right, mut successor) = Self::delete_min(right);
+                        successor.left = n.left.take();
+                        successor.right = right;
+                        (Some(Self::balance(successor)), Some(n))
+                    }
+                };
+            }
+            Ordering::Less => match n.left.take() {
+                None => deleted = None,
+                Some(left) => (n.left, deleted) = Self::delete(left, key),
+            },
+            Ordering::Greater => match n.right.take() {
+                None => deleted = None,
+                Some(right) => (n.right, deleted) = Self::delete(right, key),
+            },
+        }
+        (Some(Self::balance(n)), deleted)
+    }
+
+    fn search<'a, Q: ?Sized + Ord>(link: &'a Link<K, V>, key: &Q) -> Option<&'a Node<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        match link {
+            None => None,
+            Some(node) => match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(node.deref()),
+                Ordering::Greater => Self::search(&node.right, key),
+                Ordering::Less => Self::search(&node.left, key),
+            },
+        }
+    }
+}
+
+impl<K: KeyT, V> SymbolTable<K, V> for AVLTree<K, V> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// Insert as in a regular BST, and then rebalance each node on the way
+    /// back up the search path (see balance). Put is O(log n).
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        self.root = Some(Self::insert(self.root.take(), Node::new(key, val)));
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self) -> usize {
+        size(&self.root)
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for AVLTree<K, V>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST. Since the tree is balanced, get is O(log n).
+    fn get(&self, key: &Q) -> Option<&V> {
+        Some(&Self::search(&self.root, key)?.val)
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// Delete as in a regular BST (replacing the node with it's successor if
+    /// it has two children), and then rebalance each node on the way back up
+    /// the search path (see balance). Pop is O(log n).
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let root = self.root.take()?;
+        let deleted;
+        (self.root, deleted) = Self::delete(root, key);
+        Some(deleted?.val)
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for AVLTree<K, V> {
+    /// Get the smallest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The smallest key is the leftmost node in the tree.
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    /// Get the largest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The largest key is the rightmost node in the tree.
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    /// Get the largest key that is <= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, key),
+                Ordering::Greater => visit(&node.right, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the smallest key that is >= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as floor, but with left and right swapped.
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Greater => visit(&node.right, key),
+                Ordering::Less => visit(&node.left, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the number of keys in the tree that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST, using the subtree sizes stored in each node.
+    /// Since the tree is balanced, rank is O(log n).
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(link: &Link<K, V>, key: &Q) -> usize {
+            match link {
+                None => 0,
+                Some(node) => match key.cmp(node.key.borrow()) {
+                    Ordering::Equal => size(&node.left),
+                    Ordering::Less => visit(&node.left, key),
+                    Ordering::Greater => 1 + size(&node.left) + visit(&node.right, key),
+                },
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the key with the given rank, that is the key such that exactly
+    /// `rank` keys in the tree are less than it (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST, using the subtree sizes stored in each node.
+    /// Since the tree is balanced, select is O(log n).
+    fn select(&self, rank: usize) -> Option<&K> {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, rank: usize) -> Option<&K> {
+            let node = link.as_ref()?;
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, rank),
+                Ordering::Greater => visit(&node.right, rank - left_size - 1),
+            }
+        }
+
+        visit(&self.root, rank)
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// tree is empty).
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let root = self.root.take()?;
+        let deleted;
+        (self.root, deleted) = Self::delete_min(root);
+        Some((deleted.key, deleted.val))
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// tree is empty).
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        let root = self.root.take()?;
+        let deleted;
+        (self.root, deleted) = Self::delete_max(root);
+        Some((deleted.key, deleted.val))
+    }
+}
+
+/// An iterator over the entries of an AVLTree in sorted order (see
+/// RedBlackBST's Iter for details).
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
+impl<'a, K: KeyT, V> IntoIterator for &'a AVLTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut AVLTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for AVLTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume the tree, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// We move the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n).
+    fn into_iter(self) -> Self::IntoIter {
+        fn visit<K: KeyT, V>(link: Link<K, V>, list: &mut LinkedList<(K, V)>) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = *node;
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.root, &mut list);
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for AVLTree<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for AVLTree<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::random::shuffle_arr;
+
+    use super::*;
+
+    /// Checks that the tree is a valid AVL tree, that is:
+    ///
+    ///   1) keys are in symmetric order
+    ///   2) the heights of the subtrees of every node differ by at most one
+    ///   3) the heights and sizes stored in each node are consistent
+    fn check_invariants<K: KeyT, V>(st: &AVLTree<K, V>) {
+        /// Returns (height, node count) of the subtree.
+        fn visit<K: KeyT, V>(link: &Link<K, V>, lo: Option<&K>, hi: Option<&K>) -> (usize, usize) {
+            match link {
+                None => (0, 0),
+                Some(node) => {
+                    assert!(lo.is_none_or(|lo| &node.key > lo), "keys out of order");
+                    assert!(hi.is_none_or(|hi| &node.key < hi), "keys out of order");
+
+                    let (left_height, left_count) = visit(&node.left, lo, Some(&node.key));
+                    let (right_height, right_count) = visit(&node.right, Some(&node.key), hi);
+                    assert!(left_height.abs_diff(right_height) <= 1, "not balanced");
+
+                    let height = 1 + left_height.max(right_height);
+                    let count = 1 + left_count + right_count;
+                    assert_eq!(node.height, height, "inconsistent subtree height");
+                    assert_eq!(node.size, count, "inconsistent subtree size");
+                    (height, count)
+                }
+            }
+        }
+
+        let (_, count) = visit(&st.root, None, None);
+        assert_eq!(count, st.size());
+    }
+
+    #[test]
+    fn test_avl_tree() {
+        let mut st = AVLTree::new();
+        assert_eq!(st.height(), 0);
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            st.put(key, i).unwrap();
+            check_invariants(&st);
+        }
+        assert_eq!(st.size(), 10);
+        assert_eq!(st.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(st.get(&'E'), Some(&12));
+        assert_eq!(st.get(&'A'), Some(&8));
+        assert_eq!(st.get(&'B'), None);
+        assert_eq!(st.height(), 4);
+
+        assert_eq!(st.pop(&'B'), None);
+        assert_eq!(st.pop(&'S'), Some(0));
+        check_invariants(&st);
+        assert_eq!(st.pop_min(), Some(('A', 8)));
+        check_invariants(&st);
+        assert_eq!(st.pop_max(), Some(('X', 7)));
+        check_invariants(&st);
+        assert_eq!(st.keys().collect::<String>(), "CEHLMPR");
+    }
+
+    #[test]
+    fn test_random_put_pop() {
+        let mut rng = rand::thread_rng();
+        let mut st = AVLTree::new();
+        let mut keys: Vec<i32> = (0..500).collect();
+        shuffle_arr(&mut keys);
+        for &key in keys.iter() {
+            st.put(key, -key).unwrap();
+            check_invariants(&st);
+        }
+
+        for _ in 0..1000 {
+            let key = rng.gen_range(-10..510);
+            match rng.gen_range(0..4) {
+                0 => {
+                    st.put(key, key).unwrap();
+                }
+                1 => {
+                    st.pop_min();
+                }
+                2 => {
+                    st.pop_max();
+                }
+                _ => {
+                    let expected = st.get(&key).copied();
+                    assert_eq!(st.pop(&key), expected);
+                    assert_eq!(st.get(&key), None);
+                }
+            }
+            check_invariants(&st);
+        }
+    }
+
+    #[test]
+    fn test_height() {
+        // inserting keys in order is the worst case for a regular BST, but an
+        // AVL tree stays perfectly balanced
+        let mut st = AVLTree::new();
+        for i in 0..1023 {
+            st.put(i, i).unwrap();
+        }
+        check_invariants(&st);
+        assert_eq!(st.height(), 10);
+
+        // in general, the height is at most ~1.44 log n
+        let mut st = AVLTree::new();
+        let mut keys: Vec<i32> = (0..1000).collect();
+        shuffle_arr(&mut keys);
+        for &key in keys.iter() {
+            st.put(key, key).unwrap();
+        }
+        assert!(st.height() as f64 <= 1.44 * (st.size() as f64 + 2.0).log2());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut st = AVLTree::new();
+        for i in 0..100 {
+            st.put(i, -i).unwrap();
+        }
+        let keys: Vec<i32> = st.range(10..20).map(|(&k, _)| k).collect();
+        assert_eq!(keys, (10..20).collect::<Vec<_>>());
+        let keys: Vec<i32> = st.range(95..).rev().map(|(&k, _)| k).collect();
+        assert_eq!(keys, [99, 98, 97, 96, 95]);
+        assert_eq!(st.range(50..50).count(), 0);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = AVLTree::new();
+        for i in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            st.put(i, i * 10).unwrap();
+        }
+        for (_, val) in st.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(
+            st.values().copied().collect::<Vec<_>>(),
+            [11, 21, 31, 41, 51, 61, 71, 81, 91]
+        );
+        assert_eq!(
+            st.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+    }
+}
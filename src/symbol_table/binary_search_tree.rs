@@ -1,8 +1,28 @@
-use std::{cmp::Ordering, ops::Deref};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Deref, DerefMut, RangeBounds},
+};
 
-use crate::stack::{Stack, StackLinkedList};
+use crate::{
+    list::{LinkedList, Vector},
+    stack::{Stack, StackLinkedList},
+    utils::dot_label,
+};
 
-use super::{KeyT, SymbolTable};
+use super::{
+    entry::{Entry, OccupiedEntry, VacantEntry, VacantInsert},
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
 
 type Link<K, V> = Option<Box<Node<K, V>>>;
 
@@ -11,6 +31,31 @@ pub struct Node<K, V> {
     val: V,
     left: Link<K, V>,
     right: Link<K, V>,
+    size: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, val: V) -> Self {
+        Node {
+            key,
+            val,
+            left: None,
+            right: None,
+            size: 1,
+        }
+    }
+
+    /// Recompute the size of the subtree rooted at this node from the sizes of
+    /// it's children. Must be called on the way back up the tree whenever the
+    /// children of a node change.
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Number of nodes in the subtree (0 for a null link).
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
 }
 
 /// A binary search tree that stores key-value pairs.
@@ -22,18 +67,203 @@ pub struct Node<K, V> {
 /// an O(log n) operation. If the tree is not balanced, these operations become
 /// O(n).
 ///
+/// Each node also stores the number of nodes in it's subtree, which lets us
+/// implement the ordered operations rank and select in O(height) time.
+///
 /// See: https://algs4.cs.princeton.edu/32bst/
 pub struct BinarySearchTree<K: KeyT, V> {
     root: Link<K, V>,
-    size: usize,
+}
+
+impl<K: KeyT, V> Default for BinarySearchTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<K: KeyT, V> BinarySearchTree<K, V> {
     pub fn new() -> Self {
-        BinarySearchTree {
-            root: None,
-            size: 0,
+        BinarySearchTree { root: None }
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    ///
+    /// ### Implementation
+    /// We collect the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n) time and space.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        fn visit<'a, K: KeyT, V>(
+            link: &'a mut Link<K, V>,
+            list: &mut LinkedList<(&'a K, &'a mut V)>,
+        ) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = node.deref_mut();
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
         }
+
+        let mut list = LinkedList::new();
+        visit(&mut self.root, &mut list);
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// We walk down from the root towards the start of the range, pushing the
+    /// nodes that are in the range onto the front stack of the iterator (see
+    /// Iter), and likewise towards the end of the range for the back stack.
+    /// From there we iterate in-order, stopping once we've yielded
+    /// range_count entries. This way, we only visit O(height + number of
+    /// entries in the range) nodes.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+
+    /// Get the entry for the given key, for in-place updates (see Entry).
+    ///
+    /// ### Implementation
+    /// We search the tree for the key once. If it exists, the entry holds
+    /// references to the key and value of the matching node, so updating it
+    /// requires no further traversal. Otherwise, inserting into the vacant
+    /// entry walks down the tree once more to place the new node. So an
+    /// occupied entry costs one walk down the tree and a vacant one two.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        // the borrow checker won't let us reuse self after a search_mut that
+        // only returns it's borrow on a hit (even though on a miss nothing is
+        // borrowed), so we search through a raw pointer instead. This is
+        // sound since the two borrows are on disjoint branches.
+        let st: *mut Self = self;
+        match unsafe { &mut *st }.search_mut(&key) {
+            Some((key, val)) => Entry::Occupied(OccupiedEntry::new(key, val)),
+            None => Entry::Vacant(VacantEntry::new(self, key)),
+        }
+    }
+
+    /// Check that the tree is a valid BST, returning an Err describing the
+    /// first violation found. That is, the keys are in symmetric order and
+    /// the subtree sizes stored in each node are consistent. This is O(n), so
+    /// it's meant for tests and debugging.
+    pub fn check(&self) -> Result<(), String> {
+        fn visit<K: KeyT, V>(
+            link: &Link<K, V>,
+            lo: Option<&K>,
+            hi: Option<&K>,
+        ) -> Result<(), String> {
+            let Some(node) = link else {
+                return Ok(());
+            };
+            if lo.is_some_and(|lo| &node.key <= lo) || hi.is_some_and(|hi| &node.key >= hi) {
+                return Err("keys out of order".to_string());
+            }
+            if node.size != 1 + size(&node.left) + size(&node.right) {
+                return Err("inconsistent subtree size".to_string());
+            }
+            visit(&node.left, lo, Some(&node.key))?;
+            visit(&node.right, Some(&node.key), hi)
+        }
+
+        visit(&self.root, None, None)
+    }
+
+    /// Height of the tree, that is the number of nodes on the longest path
+    /// from the root to a leaf (0 if the tree is empty). O(n).
+    ///
+    /// The tree isn't balanced, so this ranges from ~log n (e.g. for keys
+    /// inserted in random order) to n (e.g. for keys inserted in sorted order).
+    pub fn height(&self) -> usize {
+        self.depth_counts().len()
+    }
+
+    /// The average depth of the nodes in the tree, where the root has depth 0
+    /// (None if the tree is empty). A search hit for a random key compares
+    /// against average depth + 1 nodes, which is ~1.39 log n for keys
+    /// inserted in random order. O(n).
+    pub fn average_depth(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let total: usize = self
+            .depth_counts()
+            .iter()
+            .enumerate()
+            .map(|(depth, count)| depth * count)
+            .sum();
+        Some(total as f64 / self.size() as f64)
+    }
+
+    /// The number of nodes at each depth of the tree, where the root has
+    /// depth 0 (so the length is the height of the tree). O(n).
+    pub fn depth_counts(&self) -> Vector<usize> {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, depth: usize, counts: &mut Vector<usize>) {
+            if let Some(node) = link {
+                if depth == counts.len() {
+                    counts.push(0);
+                }
+                counts[depth] += 1;
+                visit(&node.left, depth + 1, counts);
+                visit(&node.right, depth + 1, counts);
+            }
+        }
+
+        let mut counts = Vector::new();
+        visit(&self.root, 0, &mut counts);
+        counts
     }
 
     fn insert(&mut self, node_to_insert: Node<K, V>) {
@@ -46,6 +276,7 @@ impl<K: KeyT, V> BinarySearchTree<K, V> {
                         Ordering::Less => node.left = visit(&mut node.left, node_to_insert),
                         Ordering::Greater => node.right = visit(&mut node.right, node_to_insert),
                     }
+                    node.update_size();
                     Some(node)
                 }
             }
@@ -53,14 +284,19 @@ impl<K: KeyT, V> BinarySearchTree<K, V> {
 
         let mut root = self.root.take();
         self.root = visit(&mut root, node_to_insert);
-        self.size += 1;
     }
 
-    fn search(&self, key: K) -> Option<&Node<K, V>> {
-        fn visit<'a, K: KeyT, V>(link: &'a Link<K, V>, key: K) -> Option<&'a Node<K, V>> {
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&Node<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a Node<K, V>> {
             match link {
                 None => None,
-                Some(node) => match key.cmp(&node.key) {
+                Some(node) => match key.cmp(node.key.borrow()) {
                     Ordering::Equal => Some(node.deref()),
                     Ordering::Greater => visit(&node.right, key),
                     Ordering::Less => visit(&node.left, key),
@@ -71,15 +307,44 @@ impl<K: KeyT, V> BinarySearchTree<K, V> {
         visit(&self.root, key)
     }
 
-    fn delete(&mut self, key: K) -> Option<Node<K, V>> {
-        fn visit<K: KeyT, V>(mut node: Node<K, V>, key: K) -> (Link<K, V>, Option<Node<K, V>>) {
-            match key.cmp(&node.key) {
+    fn search_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            let Node {
+                key: node_key,
+                val,
+                left,
+                right,
+                ..
+            } = node.deref_mut();
+            match key.cmp((*node_key).borrow()) {
+                Ordering::Equal => return Some((node_key, val)),
+                Ordering::Less => link = left,
+                Ordering::Greater => link = right,
+            }
+        }
+        None
+    }
+
+    fn delete<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<Node<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<K: Borrow<Q>, Q: ?Sized + Ord, V>(
+            mut node: Node<K, V>,
+            key: &Q,
+        ) -> (Link<K, V>, Option<Node<K, V>>) {
+            match key.cmp(node.key.borrow()) {
                 Ordering::Equal => match node.right.take() {
                     None => (node.left.take(), Some(node)),
                     Some(right) => {
-                        let (right, mut successor) = find_successor(*right);
+                        let (right, mut successor) = delete_min(*right);
                         successor.left = node.left.take();
                         successor.right = right;
+                        successor.update_size();
                         (Some(Box::new(successor)), Some(node))
                     }
                 },
@@ -88,6 +353,7 @@ impl<K: KeyT, V> BinarySearchTree<K, V> {
                     if let Some(left) = node.left {
                         (node.left, deleted_node) = visit(*left, key);
                     };
+                    node.update_size();
                     (Some(Box::new(node)), deleted_node)
                 }
                 Ordering::Greater => {
@@ -95,33 +361,62 @@ impl<K: KeyT, V> BinarySearchTree<K, V> {
                     if let Some(right) = node.right {
                         (node.right, deleted_node) = visit(*right, key);
                     };
+                    node.update_size();
                     (Some(Box::new(node)), deleted_node)
                 }
             }
         }
 
-        fn find_successor<K: Ord + Copy + Clone, V>(
-            mut node: Node<K, V>,
-        ) -> (Link<K, V>, Node<K, V>) {
-            match node.left {
-                None => (None, node),
-                Some(left) => {
-                    let successor;
-                    (node.left, successor) = find_successor(*left);
-                    (Some(Box::new(node)), successor)
-                }
-            }
+        let root = self.root.take()?;
+        let deleted_node;
+        (self.root, deleted_node) = visit(*root, key);
+        deleted_node
+    }
+}
+
+/// Removes the leftmost node in the subtree rooted at node, returning the new
+/// subtree and the removed node.
+fn delete_min<K, V>(mut node: Node<K, V>) -> (Link<K, V>, Node<K, V>) {
+    match node.left {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let min;
+            (node.left, min) = delete_min(*left);
+            node.update_size();
+            (Some(Box::new(node)), min)
         }
+    }
+}
 
-        match self.root.take() {
-            None => None,
-            Some(root) => {
-                let deleted_node;
-                (self.root, deleted_node) = visit(*root, key);
-                self.size -= 1;
-                deleted_node
-            }
+/// Removes the rightmost node in the subtree rooted at node, returning the new
+/// subtree and the removed node.
+fn delete_max<K, V>(mut node: Node<K, V>) -> (Link<K, V>, Node<K, V>) {
+    match node.right {
+        None => (node.left.take(), node),
+        Some(right) => {
+            let max;
+            (node.right, max) = delete_max(*right);
+            node.update_size();
+            (Some(Box::new(node)), max)
+        }
+    }
+}
+
+impl<K: KeyT, V> VacantInsert<K, V> for BinarySearchTree<K, V> {
+    /// Since the key is not in the tree, the new node will be a leaf and every
+    /// node on the way down gains one descendant, so we can update the sizes
+    /// as we go and insert in a single pass.
+    fn insert_vacant(&mut self, key: K, val: V) -> Result<&mut V, String> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            node.size += 1;
+            link = if key < node.key {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
         }
+        Ok(&mut link.insert(Box::new(Node::new(key, val))).val)
     }
 }
 
@@ -134,15 +429,23 @@ impl<K: KeyT, V> SymbolTable<K, V> for BinarySearchTree<K, V> {
     /// on average (if the tree is roughly balanced) and O(n) in the worst case
     /// (if the tree is very imbalanced).
     fn put(&mut self, key: K, val: V) -> Result<(), String> {
-        self.insert(Node {
-            key: key,
-            val: val,
-            left: None,
-            right: None,
-        });
+        self.insert(Node::new(key, val));
         Ok(())
     }
 
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self) -> usize {
+        size(&self.root)
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for BinarySearchTree<K, V>
+where
+    K: Borrow<Q>,
+{
     /// Get a reference to the value for the associated key (None if the key
     /// does not exist).
     ///
@@ -152,7 +455,7 @@ impl<K: KeyT, V> SymbolTable<K, V> for BinarySearchTree<K, V> {
     /// not exist so we return None). Get is O(log n) on average (if the tree is
     /// roughly balanced) and O(n) in the worst case (if the tree is very
     /// imbalanced).
-    fn get(&self, key: K) -> Option<&V> {
+    fn get(&self, key: &Q) -> Option<&V> {
         Some(&self.search(key)?.val)
     }
 
@@ -174,73 +477,342 @@ impl<K: KeyT, V> SymbolTable<K, V> for BinarySearchTree<K, V> {
     ///
     /// Delete is O(log n) on average (if the tree is roughly balanced) and O(n)
     /// in the worst case (if the tree is very imbalanced).
-    fn pop(&mut self, key: K) -> Option<V> {
+    fn pop(&mut self, key: &Q) -> Option<V> {
         Some(self.delete(key)?.val)
     }
+}
 
-    fn is_empty(&self) -> bool {
-        self.size == 0
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for BinarySearchTree<K, V> {
+    /// Get the smallest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The smallest key is the leftmost node in the tree.
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
     }
 
-    fn size(&self) -> usize {
-        self.size
+    /// Get the largest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The largest key is the rightmost node in the tree.
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
     }
-}
 
-pub struct IntoIter<'a, K: KeyT, V> {
-    pub stack: StackLinkedList<&'a Node<K, V>>,
-}
+    /// Get the largest key that is <= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// If the given key is less than the key at the current node, the floor
+    /// must be in the left subtree. If it is greater, the floor is either in
+    /// the right subtree, or if no key in the right subtree is <= to the given
+    /// key, it is the current node.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, key),
+                Ordering::Greater => visit(&node.right, key).or(Some(&node.key)),
+            }
+        }
 
-impl<'a, K: KeyT, V> IntoIter<'a, K, V> {
-    pub fn new(tree: &'a BinarySearchTree<K, V>) -> Self {
-        let mut iter = IntoIter {
-            stack: StackLinkedList::new(),
-        };
-        iter.push_left_nodes(&tree.root);
-        iter
+        visit(&self.root, key)
     }
 
-    pub fn push_left_nodes(&mut self, mut link: &'a Link<K, V>) {
-        while let Some(node) = link {
-            self.stack.push(node.deref()).unwrap();
-            link = &node.left;
+    /// Get the smallest key that is >= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as floor, but with left and right swapped.
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Greater => visit(&node.right, key),
+                Ordering::Less => visit(&node.left, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the number of keys in the tree that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// If the given key is less than the key at the current node, we recurse
+    /// left. If it is greater, then the current node and it's entire left
+    /// subtree are less than the given key, so we count them and recurse
+    /// right. Since we store subtree sizes in each node, this is O(height).
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<K: Borrow<Q>, Q: ?Sized + Ord, V>(link: &Link<K, V>, key: &Q) -> usize {
+            match link {
+                None => 0,
+                Some(node) => match key.cmp(node.key.borrow()) {
+                    Ordering::Equal => size(&node.left),
+                    Ordering::Less => visit(&node.left, key),
+                    Ordering::Greater => 1 + size(&node.left) + visit(&node.right, key),
+                },
+            }
         }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the key with the given rank, that is the key such that exactly
+    /// `rank` keys in the tree are less than it (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// If the left subtree has more than `rank` nodes, the key is in the left
+    /// subtree. If it has exactly `rank` nodes, it's the current node.
+    /// Otherwise, we look for the key with rank `rank - size(left) - 1` in the
+    /// right subtree. Select is O(height).
+    fn select(&self, rank: usize) -> Option<&K> {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, rank: usize) -> Option<&K> {
+            let node = link.as_ref()?;
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, rank),
+                Ordering::Greater => visit(&node.right, rank - left_size - 1),
+            }
+        }
+
+        visit(&self.root, rank)
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// tree is empty).
+    ///
+    /// ### Implementation
+    /// We go left until we reach a node with a null left link, and replace
+    /// that node with it's right subtree.
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let root = self.root.take()?;
+        let min;
+        (self.root, min) = delete_min(*root);
+        Some((min.key, min.val))
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// tree is empty).
+    ///
+    /// ### Implementation
+    /// Same as pop_min, but with left and right swapped.
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        let root = self.root.take()?;
+        let max;
+        (self.root, max) = delete_max(*root);
+        Some((max.key, max.val))
+    }
+}
+
+impl<K: KeyT + Display, V> BinarySearchTree<K, V> {
+    /// Render the tree in the Graphviz DOT format (e.g. to view with
+    /// `dot -Tsvg`). See RedBlackBST::to_dot.
+    ///
+    /// ### Implementation
+    /// Graphviz places the children of a node in the order their edges are
+    /// added, so a lone right child would be drawn as if it were a left child.
+    /// To keep the shape of the tree, we add an invisible node in place of a
+    /// missing child whenever the other child exists.
+    pub fn to_dot(&self) -> String {
+        fn visit<K: KeyT + Display, V>(link: &Link<K, V>, id: &mut usize, output: &mut String) {
+            let Some(node) = link else {
+                return;
+            };
+            let node_id = *id;
+            output.push_str(&format!(
+                "    n{} [label={}];\n",
+                node_id,
+                dot_label(&node.key)
+            ));
+            if node.left.is_none() && node.right.is_none() {
+                return;
+            }
+            for child in [&node.left, &node.right] {
+                *id += 1;
+                if child.is_some() {
+                    output.push_str(&format!("    n{} -> n{};\n", node_id, *id));
+                    visit(child, id, output);
+                } else {
+                    output.push_str(&format!("    n{} [style=invis];\n", *id));
+                    output.push_str(&format!("    n{} -> n{} [style=invis];\n", node_id, *id));
+                }
+            }
+        }
+
+        let mut output = "digraph {\n    node [shape=circle];\n".to_string();
+        visit(&self.root, &mut 0, &mut output);
+        output.push_str("}\n");
+        output
     }
 }
 
-impl<'a, K: KeyT, V> Iterator for IntoIter<'a, K, V> {
-    type Item = &'a K;
+/// An iterator over the entries of a BinarySearchTree in sorted order.
+///
+/// The iterator keeps two stacks, one for iterating forwards and one for
+/// iterating backwards. The front stack holds the nodes whose left subtrees
+/// we've already visited (initially the left spine of the tree), so the next
+/// entry is always at the top of the stack. After popping a node, we push the
+/// left spine of it's right subtree. The back stack is the mirror image. We
+/// keep track of the number of remaining entries so that the two ends stop
+/// when they meet.
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.stack.pop() {
-            None => None,
-            Some(node) => {
-                self.push_left_nodes(&node.right);
-                Some(&node.key)
-            }
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
         }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
+        }
+
+        Some((&node.key, &node.val))
     }
 }
 
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
 impl<'a, K: KeyT, V> IntoIterator for &'a BinarySearchTree<K, V> {
-    type Item = &'a K;
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
 
-    type IntoIter = IntoIter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut BinarySearchTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter::new(self)
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for BinarySearchTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume the tree, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// We move the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n).
+    fn into_iter(self) -> Self::IntoIter {
+        fn visit<K: KeyT, V>(link: Link<K, V>, list: &mut LinkedList<(K, V)>) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = *node;
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.root, &mut list);
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for BinarySearchTree<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de>
+    for BinarySearchTree<K, V>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::random::shuffle_arr;
+
     use super::*;
 
     #[test]
     fn test_binary_search_tree() {
         // test 1
         let mut st = BinarySearchTree::new();
-        assert_eq!(st.is_empty(), true);
+        assert!(st.is_empty());
         assert_eq!(st.size(), 0);
 
         st.put("apple", 10).unwrap();
@@ -248,24 +820,24 @@ mod tests {
         st.put("cat", -3).unwrap();
         st.put("dog", 0).unwrap();
         assert_eq!(st.size(), 4);
-        assert_eq!(st.is_empty(), false);
+        assert!(!st.is_empty());
 
-        assert_eq!(st.get("apple"), Some(&10));
-        assert_eq!(st.get("banana"), Some(&5));
-        assert_eq!(st.get("cat"), Some(&-3));
-        assert_eq!(st.get("dog"), Some(&0));
-        assert_eq!(st.get("elephant"), None);
+        assert_eq!(st.get(&"apple"), Some(&10));
+        assert_eq!(st.get(&"banana"), Some(&5));
+        assert_eq!(st.get(&"cat"), Some(&-3));
+        assert_eq!(st.get(&"dog"), Some(&0));
+        assert_eq!(st.get(&"elephant"), None);
 
-        assert_eq!(st.pop("cat"), Some(-3));
-        assert_eq!(st.get("cat"), None);
-        assert_eq!(st.pop("cat"), None);
-        assert_eq!(st.get("dog"), Some(&0));
+        assert_eq!(st.pop(&"cat"), Some(-3));
+        assert_eq!(st.get(&"cat"), None);
+        assert_eq!(st.pop(&"cat"), None);
+        assert_eq!(st.get(&"dog"), Some(&0));
 
         st.put("apple", 20).unwrap();
-        assert_eq!(st.get("apple"), Some(&20));
+        assert_eq!(st.get(&"apple"), Some(&20));
         assert_eq!(st.size(), 3);
 
-        let mut iter = (&st).into_iter();
+        let mut iter = st.keys();
         assert_eq!(iter.next(), Some(&"apple"));
         assert_eq!(iter.next(), Some(&"banana"));
         assert_eq!(iter.next(), Some(&"dog"));
@@ -289,16 +861,16 @@ mod tests {
         st.put(9, -9).unwrap();
 
         for i in 0..=12 {
-            assert_eq!(st.get(i), Some(&-i));
+            assert_eq!(st.get(&i), Some(&-i));
         }
 
-        assert!(st.pop(12).is_some());
-        assert!(st.pop(5).is_some());
-        assert!(st.pop(8).is_some());
-        assert!(st.pop(0).is_some());
-        assert!(st.pop(3).is_some());
+        assert!(st.pop(&12).is_some());
+        assert!(st.pop(&5).is_some());
+        assert!(st.pop(&8).is_some());
+        assert!(st.pop(&0).is_some());
+        assert!(st.pop(&3).is_some());
 
-        let mut iter = (&st).into_iter();
+        let mut iter = st.keys();
         assert_eq!(iter.next(), Some(&1));
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&4));
@@ -309,4 +881,311 @@ mod tests {
         assert_eq!(iter.next(), Some(&11));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_range() {
+        let mut st = BinarySearchTree::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let keys = |range: Iter<i32, i32>| range.map(|(k, _)| *k).collect::<Vec<i32>>();
+        assert_eq!(keys(st.range(30..70)), [30, 40, 50, 60]);
+        assert_eq!(keys(st.range(30..=70)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(25..75)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(..30)), [10, 20]);
+        assert_eq!(keys(st.range(85..)), [90]);
+        assert_eq!(keys(st.range(..)), [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        assert_eq!(keys(st.range(91..)), [0; 0]);
+        assert_eq!(keys(st.range(50..50)), [0; 0]);
+
+        let mut range = st.range(40..=50);
+        assert_eq!(range.next(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = BinarySearchTree::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let mut iter = st.iter();
+        assert_eq!(iter.next(), Some((&10, &-10)));
+        assert_eq!(iter.next_back(), Some((&90, &-90)));
+        assert_eq!(iter.next(), Some((&20, &-20)));
+        assert_eq!(iter.next_back(), Some((&80, &-80)));
+        assert_eq!(iter.count(), 5);
+
+        let keys: Vec<i32> = st.keys().copied().collect();
+        assert_eq!(keys, [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        let keys: Vec<i32> = st.keys().rev().copied().collect();
+        assert_eq!(keys, [90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        let vals: Vec<i32> = st.values().copied().collect();
+        assert_eq!(vals, [-10, -20, -30, -40, -50, -60, -70, -80, -90]);
+
+        let mut range = st.range(30..=70).rev();
+        assert_eq!(range.next(), Some((&70, &-70)));
+        assert_eq!(range.next(), Some((&60, &-60)));
+        assert_eq!(range.next_back(), Some((&30, &-30)));
+        assert_eq!(range.next_back(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+
+        for (key, val) in st.iter_mut() {
+            *val = 2 * key;
+        }
+        for (key, val) in st.iter_mut().rev().take(2) {
+            *val += key;
+        }
+        for (_, val) in &mut st {
+            *val += 1;
+        }
+        let entries: Vec<(i32, i32)> = (&st).into_iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(
+            entries,
+            [
+                (10, 21),
+                (20, 41),
+                (30, 61),
+                (40, 81),
+                (50, 101),
+                (60, 121),
+                (70, 141),
+                (80, 241),
+                (90, 271)
+            ]
+        );
+
+        let mut into_iter = st.into_iter();
+        assert_eq!(into_iter.next(), Some((10, 21)));
+        assert_eq!(into_iter.next_back(), Some((90, 271)));
+        assert_eq!(
+            into_iter.rev().map(|(k, _)| k).collect::<Vec<i32>>(),
+            [80, 70, 60, 50, 40, 30, 20]
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut st = BinarySearchTree::new();
+        let text = "it was the best of times it was the worst of times";
+        for word in text.split(' ') {
+            *st.entry(word).or_insert(0).unwrap() += 1;
+        }
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get(&"times"), Some(&2));
+        assert_eq!(st.get(&"best"), Some(&1));
+        assert_eq!(st.rank(&"times"), 4);
+
+        st.entry("the").and_modify(|count| *count *= 10);
+        assert_eq!(st.get(&"the"), Some(&20));
+        st.entry("age").and_modify(|count| *count *= 10);
+        assert_eq!(st.get(&"age"), None);
+
+        assert_eq!(st.entry("of").or_insert_with(|| panic!()), Ok(&mut 2));
+        match st.entry("age") {
+            Entry::Occupied(_) => panic!("expected vacant entry"),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &"age");
+                assert_eq!(entry.insert(100), Ok(&mut 100));
+            }
+        }
+        match st.entry("age") {
+            Entry::Occupied(mut entry) => assert_eq!(entry.insert(0), 100),
+            Entry::Vacant(_) => panic!("expected occupied entry"),
+        }
+        assert_eq!(st.get(&"age"), Some(&0));
+        assert_eq!(st.size(), 8);
+        assert_eq!(st.select(0), Some(&"age"));
+    }
+
+    #[test]
+    fn test_entry_walks() {
+        // a key that counts every comparison, to pin down how many times
+        // entry walks down the tree
+        #[derive(Debug)]
+        struct Counted(i32, Rc<Cell<usize>>);
+        impl PartialEq for Counted {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+        impl Eq for Counted {}
+        impl PartialOrd for Counted {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Counted {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.1.set(self.1.get() + 1);
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let count = Rc::new(Cell::new(0));
+        let mut keys: Vec<i32> = (0..200).map(|i| 2 * i).collect();
+        shuffle_arr(&mut keys);
+        let mut st = BinarySearchTree::new();
+        for &key in keys.iter() {
+            st.put(Counted(key, count.clone()), key).unwrap();
+        }
+        let mut compares = |f: &mut dyn FnMut(&mut BinarySearchTree<Counted, i32>)| {
+            count.set(0);
+            f(&mut st);
+            count.get()
+        };
+
+        // an occupied entry is a single walk, the same as a get
+        for &key in keys.iter() {
+            let get = compares(&mut |st| assert!(st.get(&Counted(key, count.clone())).is_some()));
+            let entry = compares(&mut |st| {
+                *st.entry(Counted(key, count.clone())).or_insert(0).unwrap() += 1;
+            });
+            assert_eq!(entry, get);
+        }
+
+        // a vacant entry walks once to search and once more to insert
+        for &key in keys.iter() {
+            let get =
+                compares(&mut |st| assert!(st.get(&Counted(key + 1, count.clone())).is_none()));
+            let entry = compares(&mut |st| {
+                *st.entry(Counted(key + 1, count.clone()))
+                    .or_insert(0)
+                    .unwrap() += 1;
+            });
+            assert_eq!(entry, 2 * get);
+        }
+        assert_eq!(st.size(), 2 * keys.len());
+        for &key in keys.iter() {
+            assert_eq!(st.get(&Counted(key, count.clone())), Some(&(key + 1)));
+            assert_eq!(st.get(&Counted(key + 1, count.clone())), Some(&1));
+        }
+    }
+
+    #[test]
+    fn test_subtree_sizes() {
+        let mut keys: Vec<i32> = (0..200).collect();
+        shuffle_arr(&mut keys);
+        let mut st = BinarySearchTree::new();
+        for &key in keys.iter() {
+            st.put(key, key).unwrap();
+            st.check().unwrap();
+        }
+        // updating an existing key does not change any sizes
+        for &key in keys.iter().take(20) {
+            st.put(key, -key).unwrap();
+        }
+        st.check().unwrap();
+        assert_eq!(st.size(), 200);
+
+        // pop the root (which has two children, so it's replaced by it's
+        // successor), then everything else in random order
+        let root = st.root.as_ref().unwrap().key;
+        assert!(st.pop(&root).is_some());
+        st.check().unwrap();
+        shuffle_arr(&mut keys);
+        for (i, &key) in keys.iter().enumerate() {
+            match i % 4 {
+                0 => drop(st.pop_min()),
+                1 => drop(st.pop_max()),
+                _ => drop(st.pop(&key)),
+            }
+            st.check().unwrap();
+        }
+        while st.pop_max().is_some() {
+            st.check().unwrap();
+        }
+        assert!(st.is_empty());
+    }
+
+    #[test]
+    fn test_percentiles() {
+        // latencies in ms, streamed in with an id for each request
+        let mut st = BinarySearchTree::new();
+        let latencies = [12, 7, 250, 31, 9, 14, 8, 95, 10, 11];
+        for (id, &latency) in latencies.iter().enumerate() {
+            st.put((latency, id), ()).unwrap();
+        }
+        let latency = |key: Option<&(i32, usize)>| key.map(|&(latency, _)| latency);
+        assert_eq!(latency(st.median()), Some(11));
+        assert_eq!(latency(st.percentile(50.0)), Some(11));
+        assert_eq!(latency(st.percentile(90.0)), Some(95));
+        assert_eq!(latency(st.percentile(99.0)), Some(250));
+        assert_eq!(latency(st.kth_smallest(3)), Some(9));
+
+        st.put((5, 10), ()).unwrap();
+        st.pop(&(250, 2));
+        assert_eq!(latency(st.median()), Some(10));
+        assert_eq!(latency(st.percentile(99.0)), Some(95));
+    }
+
+    #[test]
+    fn test_check_and_stats() {
+        let mut st = BinarySearchTree::new();
+        assert_eq!(st.height(), 0);
+        assert_eq!(st.average_depth(), None);
+        assert!(st.depth_counts().is_empty());
+
+        //       4
+        //     /   \
+        //    2     6
+        //   / \     \
+        //  1   3     7
+        for key in [4, 2, 6, 1, 3, 7] {
+            st.put(key, ()).unwrap();
+        }
+        st.check().unwrap();
+        assert_eq!(st.height(), 3);
+        assert_eq!(st.depth_counts()[..], [1, 2, 3]);
+        assert_eq!(st.average_depth(), Some(8.0 / 6.0));
+
+        // sorted inserts degenerate into a linked list
+        let mut sorted = BinarySearchTree::new();
+        for key in 0..100 {
+            sorted.put(key, ()).unwrap();
+        }
+        assert_eq!(sorted.height(), 100);
+        assert_eq!(sorted.average_depth(), Some(49.5));
+        assert!(sorted.depth_counts().iter().all(|&count| count == 1));
+
+        // corrupt the tree
+        st.root.as_mut().unwrap().size += 1;
+        assert_eq!(st.check(), Err("inconsistent subtree size".to_string()));
+        st.root.as_mut().unwrap().size -= 1;
+        st.root.as_mut().unwrap().left.as_mut().unwrap().key = 5;
+        assert_eq!(st.check(), Err("keys out of order".to_string()));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        //   2
+        //  / \
+        // 1   4
+        //    /
+        //   3
+        let mut st = BinarySearchTree::new();
+        for key in [2, 1, 4, 3] {
+            st.put(key, ()).unwrap();
+        }
+        let expected = [
+            "digraph {",
+            "    node [shape=circle];",
+            "    n0 [label=\"2\"];",
+            "    n0 -> n1;",
+            "    n1 [label=\"1\"];",
+            "    n0 -> n2;",
+            "    n2 [label=\"4\"];",
+            "    n2 -> n3;",
+            "    n3 [label=\"3\"];",
+            "    n4 [style=invis];",
+            "    n2 -> n4 [style=invis];",
+            "}",
+        ];
+        assert_eq!(st.to_dot().lines().collect::<Vec<_>>(), expected);
+    }
 }
@@ -0,0 +1,1083 @@
+use std::{borrow::Borrow, cmp::Ordering, ops::RangeBounds};
+
+use crate::{
+    list::LinkedList,
+    search::binary_search_by,
+    stack::{Stack, StackLinkedList},
+};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+/// The result of splitting a full node: the median entry (which moves up into
+/// the parent) and the new right sibling.
+type Split<K, V> = (K, V, Node<K, V>);
+
+/// A level of nodes when building a tree bottom up, and the entries between
+/// them that separate their subtrees.
+type Level<K, V> = (Vec<Node<K, V>>, Vec<(K, V)>);
+
+pub struct Node<K: KeyT, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    /// Either empty (for a leaf), or has exactly one more entry than keys,
+    /// where the keys in children[i] fall between keys[i - 1] and keys[i].
+    children: Vec<Node<K, V>>,
+    size: usize,
+}
+
+impl<K: KeyT, V> Node<K, V> {
+    fn new() -> Self {
+        Node {
+            keys: Vec::new(),
+            vals: Vec::new(),
+            children: Vec::new(),
+            size: 0,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Recompute the number of entries in the subtree rooted at this node from
+    /// the sizes of it's children.
+    fn update_size(&mut self) {
+        self.size = self.keys.len() + self.children.iter().map(|c| c.size).sum::<usize>();
+    }
+
+    /// Binary search the keys of this node, returning Ok(index) if the key
+    /// exists and Err(index of the child to search next) otherwise.
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        binary_search_by(&self.keys, |k| k.borrow().cmp(key))
+    }
+
+    /// Split the node in half, keeping the left half and returning the median
+    /// entry and the right half.
+    fn split(&mut self) -> Split<K, V> {
+        let mid = self.keys.len() / 2;
+        let mut right = Node {
+            keys: self.keys.split_off(mid + 1),
+            vals: self.vals.split_off(mid + 1),
+            children: if self.is_leaf() {
+                Vec::new()
+            } else {
+                self.children.split_off(mid + 1)
+            },
+            size: 0,
+        };
+        let key = self.keys.pop().unwrap();
+        let val = self.vals.pop().unwrap();
+        right.update_size();
+        self.update_size();
+        (key, val, right)
+    }
+}
+
+/// A B-tree that stores key-value pairs.
+///
+/// A B-tree of order m is a search tree where each node holds up to m - 1
+/// sorted keys, and each internal node with k keys has k + 1 children, such
+/// that the keys in the i-th child fall between the (i-1)-th and i-th keys of
+/// the node. In addition:
+///
+///   a) All leaves are at the same depth (the tree is perfectly balanced).
+///   b) Every node except the root holds at least ceil(m/2) - 1 keys.
+///
+/// To insert, we add the key to a leaf, and if the leaf overflows (has m
+/// keys), we split it in two and move the median key up into the parent,
+/// which may in turn overflow and split, and so on up to the root. To delete,
+/// we remove the key from a leaf (swapping an internal key with it's
+/// predecessor first), and if the leaf underflows, we borrow a key from a
+/// sibling, or else merge it with a sibling, which may in turn cause the
+/// parent to underflow, and so on up to the root.
+///
+/// Since each node holds many keys in contiguous memory, a B-tree has height
+/// ~log_m(n) and does far fewer pointer hops (cache misses) per search than a
+/// binary tree, where every key is it's own heap allocated node. Within a
+/// node, we use binary search. All operations are O(log n).
+///
+/// As with the other trees, each node also stores the number of entries in
+/// it's subtree for rank and select.
+///
+/// See: https://en.wikipedia.org/wiki/B-tree and
+/// https://algs4.cs.princeton.edu/62btree/
+pub struct BTree<K: KeyT, V, const ORDER: usize> {
+    root: Link<K, V>,
+}
+
+impl<K: KeyT, V, const ORDER: usize> Default for BTree<K, V, ORDER> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V, const ORDER: usize> BTree<K, V, ORDER> {
+    /// Minimum number of keys in a non-root node, ceil(ORDER/2) - 1.
+    const MIN_KEYS: usize = (ORDER - 1) / 2;
+
+    pub fn new() -> Self {
+        assert!(ORDER >= 3, "ORDER must be at least 3.");
+        BTree { root: None }
+    }
+
+    /// Build a tree from entries sorted by key, returning an error if the keys
+    /// are out of order. Like put, a duplicate key updates the value.
+    ///
+    /// ### Implementation
+    /// Rather than putting the entries one at a time (which leaves the nodes
+    /// about half full), we build the tree bottom up. We pack the entries into
+    /// leaves from left to right, filling each leaf with ORDER - 1 keys and
+    /// keeping the next entry as the separator between it and the following
+    /// leaf. The separators are then the entries of the level above, which we
+    /// pack the same way (with the leaves as their children), and so on until
+    /// a single node is left, which is the root.
+    ///
+    /// Only the last node on each level may come up short. If it has fewer
+    /// than MIN_KEYS keys, we give it some of the keys of it's left sibling
+    /// (which is full, so it can spare them). Each entry is moved a constant
+    /// number of times, so this is O(n).
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(entries: I) -> Result<Self, String> {
+        let mut st = Self::new();
+        let mut sorted: Vec<(K, V)> = Vec::new();
+        for (key, val) in entries {
+            match sorted.last().map(|(last, _)| key.cmp(last)) {
+                Some(Ordering::Less) => return Err("keys not sorted".to_string()),
+                Some(Ordering::Equal) => sorted.last_mut().unwrap().1 = val,
+                _ => sorted.push((key, val)),
+            }
+        }
+        if sorted.is_empty() {
+            return Ok(st);
+        }
+
+        let (mut nodes, mut separators) = Self::pack_level(sorted, Vec::new());
+        while nodes.len() > 1 {
+            (nodes, separators) = Self::pack_level(separators, nodes);
+        }
+        st.root = nodes.pop().map(Box::new);
+        Ok(st)
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    ///
+    /// ### Implementation
+    /// We collect the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n) time and space.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        fn visit<'a, K: KeyT, V>(
+            node: &'a mut Node<K, V>,
+            list: &mut LinkedList<(&'a K, &'a mut V)>,
+        ) {
+            let Node {
+                keys,
+                vals,
+                children,
+                ..
+            } = node;
+            let mut children = children.iter_mut();
+            for (key, val) in keys.iter().zip(vals.iter_mut()) {
+                if let Some(child) = children.next() {
+                    visit(child, list);
+                }
+                list.push_back((key, val));
+            }
+            if let Some(child) = children.next() {
+                visit(child, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        if let Some(root) = self.root.as_mut() {
+            visit(root, &mut list);
+        }
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// We walk down from the root towards the start of the range, pushing each
+    /// node onto the front stack of the iterator along with the index of it's
+    /// first key in the range (see Iter), and likewise towards the end of the
+    /// range for the back stack. This way, we only visit O(height + number of
+    /// entries in the range) nodes.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let i = node.keys.partition_point(|k| !is_after_start(k, start));
+            iter.front.push((node, i)).unwrap();
+            link = node.children.get(i);
+        }
+
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let j = node.keys.partition_point(|k| is_before_end(k, end));
+            iter.back.push((node, j)).unwrap();
+            link = node.children.get(j);
+        }
+
+        iter
+    }
+
+    /// Split the node if it has overflowed.
+    fn split_if_full(node: &mut Node<K, V>) -> Option<Split<K, V>> {
+        if node.keys.len() >= ORDER {
+            Some(node.split())
+        } else {
+            None
+        }
+    }
+
+    /// Replace the root with a new root, whose children are the old root and
+    /// the right half that was split off of it. This is the only way the
+    /// height of the tree grows.
+    fn grow_root(&mut self, (key, val, right): Split<K, V>) {
+        let mut root = Node::new();
+        root.keys.push(key);
+        root.vals.push(val);
+        root.children.push(*self.root.take().unwrap());
+        root.children.push(right);
+        root.update_size();
+        self.root = Some(Box::new(root));
+    }
+
+    /// Shrink the root after a delete: if the root has run out of keys, it's
+    /// only child (if any) becomes the new root. This is the only way the
+    /// height of the tree shrinks.
+    fn shrink_root(&mut self) {
+        if self.root.as_ref().is_some_and(|root| root.keys.is_empty()) {
+            self.root = self.root.take().unwrap().children.pop().map(Box::new);
+        }
+    }
+
+    /// Insert the entry into the subtree rooted at node, returning whether a
+    /// new entry was added (as opposed to an existing one being updated), and
+    /// the split if the node overflowed.
+    fn insert(node: &mut Node<K, V>, key: K, val: V) -> (bool, Option<Split<K, V>>) {
+        let i = match node.search(&key) {
+            Ok(i) => {
+                node.vals[i] = val;
+                return (false, None);
+            }
+            Err(i) => i,
+        };
+
+        if node.is_leaf() {
+            node.keys.insert(i, key);
+            node.vals.insert(i, val);
+        } else {
+            let (added, split) = Self::insert(&mut node.children[i], key, val);
+            if !added {
+                return (false, None);
+            }
+            if let Some((key, val, right)) = split {
+                node.keys.insert(i, key);
+                node.vals.insert(i, val);
+                node.children.insert(i + 1, right);
+            }
+        }
+        node.size += 1;
+        (true, Self::split_if_full(node))
+    }
+
+    /// Pack the sorted entries into a level of nodes, as full as possible
+    /// from left to right (see from_sorted), where children are the nodes of
+    /// the level below (empty for the leaves). Returns the nodes, and the
+    /// entries left over between them as separators.
+    fn pack_level(entries: Vec<(K, V)>, children: Vec<Node<K, V>>) -> Level<K, V> {
+        // each node but the last takes ORDER - 1 keys plus a separator
+        let n = entries.len();
+        let mut counts = vec![ORDER - 1; (n + 1).div_ceil(ORDER)];
+        let m = counts.len();
+        counts[m - 1] = n - (m - 1) * ORDER;
+        if m > 1 && counts[m - 1] < Self::MIN_KEYS {
+            counts[m - 2] -= Self::MIN_KEYS - counts[m - 1];
+            counts[m - 1] = Self::MIN_KEYS;
+        }
+
+        let mut entries = entries.into_iter();
+        let mut children = children.into_iter();
+        let mut nodes = Vec::with_capacity(m);
+        let mut separators = Vec::with_capacity(m - 1);
+        for count in counts {
+            let mut node = Node::new();
+            for (key, val) in entries.by_ref().take(count) {
+                node.keys.push(key);
+                node.vals.push(val);
+            }
+            node.children.extend(children.by_ref().take(count + 1));
+            node.update_size();
+            nodes.push(node);
+            separators.extend(entries.next());
+        }
+        (nodes, separators)
+    }
+
+    /// Restore the minimum number of keys in node.children[i] (if it has
+    /// underflowed), by either:
+    ///
+    ///   1) Borrowing from the left sibling: the separating key in the parent
+    ///      moves down into the child, and the largest key of the sibling
+    ///      moves up to replace it (along with the sibling's last child).
+    ///   2) Borrowing from the right sibling: mirror image of 1).
+    ///   3) If both siblings are at the minimum, merging the child with a
+    ///      sibling and the separating key into a single node. The parent
+    ///      loses a key, so it may underflow in turn.
+    fn fix_child(node: &mut Node<K, V>, i: usize) {
+        if node.children[i].keys.len() >= Self::MIN_KEYS {
+            return;
+        }
+
+        if i > 0 && node.children[i - 1].keys.len() > Self::MIN_KEYS {
+            let (left, right) = node.children.split_at_mut(i);
+            let (sibling, child) = (&mut left[i - 1], &mut right[0]);
+            let key = std::mem::replace(&mut node.keys[i - 1], sibling.keys.pop().unwrap());
+            let val = std::mem::replace(&mut node.vals[i - 1], sibling.vals.pop().unwrap());
+            child.keys.insert(0, key);
+            child.vals.insert(0, val);
+            if let Some(grandchild) = sibling.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+            sibling.update_size();
+            child.update_size();
+        } else if i + 1 < node.children.len() && node.children[i + 1].keys.len() > Self::MIN_KEYS {
+            let (left, right) = node.children.split_at_mut(i + 1);
+            let (child, sibling) = (&mut left[i], &mut right[0]);
+            let key = std::mem::replace(&mut node.keys[i], sibling.keys.remove(0));
+            let val = std::mem::replace(&mut node.vals[i], sibling.vals.remove(0));
+            child.keys.push(key);
+            child.vals.push(val);
+            if !sibling.is_leaf() {
+                child.children.push(sibling.children.remove(0));
+            }
+            sibling.update_size();
+            child.update_size();
+        } else {
+            // merge children j and j + 1
+            let j = if i > 0 { i - 1 } else { i };
+            let right = node.children.remove(j + 1);
+            let key = node.keys.remove(j);
+            let val = node.vals.remove(j);
+            let left = &mut node.children[j];
+            left.keys.push(key);
+            left.vals.push(val);
+            let Node {
+                keys,
+                vals,
+                children,
+                size,
+            } = right;
+            left.keys.extend(keys);
+            left.vals.extend(vals);
+            left.children.extend(children);
+            left.size += 1 + size;
+        }
+    }
+
+    /// Delete the entry with the smallest key in the subtree rooted at node.
+    fn delete_min(node: &mut Node<K, V>) -> (K, V) {
+        let deleted = if node.is_leaf() {
+            (node.keys.remove(0), node.vals.remove(0))
+        } else {
+            let deleted = Self::delete_min(&mut node.children[0]);
+            Self::fix_child(node, 0);
+            deleted
+        };
+        node.size -= 1;
+        deleted
+    }
+
+    /// Delete the entry with the largest key in the subtree rooted at node.
+    fn delete_max(node: &mut Node<K, V>) -> (K, V) {
+        let deleted = if node.is_leaf() {
+            (node.keys.pop().unwrap(), node.vals.pop().unwrap())
+        } else {
+            let i = node.children.len() - 1;
+            let deleted = Self::delete_max(&mut node.children[i]);
+            Self::fix_child(node, i);
+            deleted
+        };
+        node.size -= 1;
+        deleted
+    }
+
+    /// Delete the entry with the given key in the subtree rooted at node (None
+    /// if the key does not exist).
+    fn delete<Q: ?Sized + Ord>(node: &mut Node<K, V>, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+    {
+        let deleted = match node.search(key) {
+            Ok(i) if node.is_leaf() => (node.keys.remove(i), node.vals.remove(i)),
+            Ok(i) => {
+                // replace the entry with it's predecessor, the largest entry
+                // in the child to the left of it
+                let (key, val) = Self::delete_max(&mut node.children[i]);
+                let deleted = (
+                    std::mem::replace(&mut node.keys[i], key),
+                    std::mem::replace(&mut node.vals[i], val),
+                );
+                Self::fix_child(node, i);
+                deleted
+            }
+            Err(_) if node.is_leaf() => return None,
+            Err(i) => {
+                let deleted = Self::delete(&mut node.children[i], key)?;
+                Self::fix_child(node, i);
+                deleted
+            }
+        };
+        node.size -= 1;
+        Some(deleted)
+    }
+}
+
+impl<K: KeyT, V, const ORDER: usize> SymbolTable<K, V> for BTree<K, V, ORDER> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// Search down to a leaf and insert the entry there, then on the way back
+    /// up, split any node that has overflowed (see insert). If the root
+    /// splits, the tree grows by one level. Put is O(log n).
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        let root = self.root.get_or_insert_with(|| Box::new(Node::new()));
+        if let (_, Some(split)) = Self::insert(root, key, val) {
+            self.grow_root(split);
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.size)
+    }
+}
+
+impl<K: KeyT, V, const ORDER: usize, Q: ?Sized + Ord> Lookup<Q, V> for BTree<K, V, ORDER>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    ///
+    /// ### Implementation
+    /// Binary search the keys of each node on the way down from the root. Get
+    /// is O(log n).
+    fn get(&self, key: &Q) -> Option<&V> {
+        let mut node = self.root.as_deref()?;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&node.vals[i]),
+                Err(i) => node = node.children.get(i)?,
+            }
+        }
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// Delete the entry (see delete), then on the way back up, fix any node
+    /// that has underflowed (see fix_child). If the root runs out of keys, the
+    /// tree shrinks by one level. Pop is O(log n).
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let (_, val) = Self::delete(self.root.as_mut()?, key)?;
+        self.shrink_root();
+        Some(val)
+    }
+}
+
+impl<K: KeyT, V, const ORDER: usize> OrderedSymbolTable<K, V> for BTree<K, V, ORDER> {
+    /// Get the smallest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The smallest key is the first key of the leftmost leaf.
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        node.keys.first()
+    }
+
+    /// Get the largest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The largest key is the last key of the rightmost leaf.
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        node.keys.last()
+    }
+
+    /// Get the largest key that is <= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// If the key is not in the node, the floor is either in the child between
+    /// the keys just below and just above the given key, or if no key in that
+    /// child is <= to the given key, it is the key just below.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            node: &'a Node<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            match node.search(key) {
+                Ok(i) => Some(&node.keys[i]),
+                Err(i) => node
+                    .children
+                    .get(i)
+                    .and_then(|child| visit(child, key))
+                    .or_else(|| Some(&node.keys[i.checked_sub(1)?])),
+            }
+        }
+
+        visit(self.root.as_ref()?, key)
+    }
+
+    /// Get the smallest key that is >= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as floor, but with the key just above instead of just below.
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            node: &'a Node<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            match node.search(key) {
+                Ok(i) => Some(&node.keys[i]),
+                Err(i) => node
+                    .children
+                    .get(i)
+                    .and_then(|child| visit(child, key))
+                    .or_else(|| node.keys.get(i)),
+            }
+        }
+
+        visit(self.root.as_ref()?, key)
+    }
+
+    /// Get the number of keys in the tree that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// On the way down, for each node we add the number of keys in the node
+    /// that are less than the given key, along with the sizes of the children
+    /// to the left of the path. Rank is O(log n).
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let (i, found) = match node.search(key) {
+                Ok(i) => (i, true),
+                Err(i) => (i, false),
+            };
+            rank += i + node.children[..i.min(node.children.len())]
+                .iter()
+                .map(|child| child.size)
+                .sum::<usize>();
+            if found {
+                // the keys in the child to the left of the key are also less
+                return rank + node.children.get(i).map_or(0, |child| child.size);
+            }
+            link = node.children.get(i);
+        }
+        rank
+    }
+
+    /// Get the key with the given rank, that is the key such that exactly
+    /// `rank` keys in the tree are less than it (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// On the way down, we skip over the children (and keys) to the left of
+    /// the key we want using the subtree sizes. Select is O(log n).
+    fn select(&self, mut rank: usize) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        if rank >= node.size {
+            return None;
+        }
+        loop {
+            if node.is_leaf() {
+                return node.keys.get(rank);
+            }
+            let mut i = 0;
+            while rank > node.children[i].size {
+                rank -= node.children[i].size + 1;
+                i += 1;
+            }
+            if rank == node.children[i].size {
+                return node.keys.get(i);
+            }
+            node = &node.children[i];
+        }
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// tree is empty).
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let deleted = Self::delete_min(self.root.as_mut()?);
+        self.shrink_root();
+        Some(deleted)
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// tree is empty).
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        let deleted = Self::delete_max(self.root.as_mut()?);
+        self.shrink_root();
+        Some(deleted)
+    }
+}
+
+/// An iterator over the entries of a BTree in sorted order.
+///
+/// Like the iterators of the binary trees, we keep a stack of nodes for each
+/// end, but we also keep track of our position within each node. The front
+/// stack holds (node, i) pairs where keys[i] is the next key to yield from
+/// that node once the children to the left of it have been visited. The back
+/// stack is the mirror image, holding (node, j) pairs where keys[j - 1] is
+/// the next key to yield. We keep track of the number of remaining entries so
+/// that the two ends stop when they meet.
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<(&'a Node<K, V>, usize)>,
+    back: StackLinkedList<(&'a Node<K, V>, usize)>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let (node, i) = self.front.pop()?;
+            if i < node.keys.len() {
+                self.front.push((node, i + 1)).unwrap();
+                let mut link = node.children.get(i + 1);
+                while let Some(child) = link {
+                    self.front.push((child, 0)).unwrap();
+                    link = child.children.first();
+                }
+                self.remaining -= 1;
+                return Some((&node.keys[i], &node.vals[i]));
+            }
+        }
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let (node, j) = self.back.pop()?;
+            if j > 0 {
+                self.back.push((node, j - 1)).unwrap();
+                let mut link = node.children.get(j - 1);
+                while let Some(child) = link {
+                    self.back.push((child, child.keys.len())).unwrap();
+                    link = child.children.last();
+                }
+                self.remaining -= 1;
+                return Some((&node.keys[j - 1], &node.vals[j - 1]));
+            }
+        }
+    }
+}
+
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
+impl<'a, K: KeyT, V, const ORDER: usize> IntoIterator for &'a BTree<K, V, ORDER> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V, const ORDER: usize> IntoIterator for &'a mut BTree<K, V, ORDER> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V, const ORDER: usize> IntoIterator for BTree<K, V, ORDER> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume the tree, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// We move the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n).
+    fn into_iter(self) -> Self::IntoIter {
+        fn visit<K: KeyT, V>(node: Node<K, V>, list: &mut LinkedList<(K, V)>) {
+            let mut children = node.children.into_iter();
+            for (key, val) in node.keys.into_iter().zip(node.vals) {
+                if let Some(child) = children.next() {
+                    visit(child, list);
+                }
+                list.push_back((key, val));
+            }
+            if let Some(child) = children.next() {
+                visit(child, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        if let Some(root) = self.root {
+            visit(*root, &mut list);
+        }
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize, const ORDER: usize> Serialize for BTree<K, V, ORDER> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>, const ORDER: usize> Deserialize<'de>
+    for BTree<K, V, ORDER>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::random::shuffle_arr;
+
+    use super::*;
+
+    /// Checks that the tree is a valid B-tree, that is:
+    ///
+    ///   1) keys are in symmetric order
+    ///   2) every node has at most ORDER - 1 keys, and every node except the
+    ///      root has at least ceil(ORDER/2) - 1 keys
+    ///   3) internal nodes have one more child than keys
+    ///   4) all leaves are at the same depth
+    ///   5) the subtree sizes stored in each node are consistent
+    fn check_invariants<K: KeyT, V, const ORDER: usize>(st: &BTree<K, V, ORDER>) {
+        /// Returns (height, entry count) of the subtree.
+        fn visit<K: KeyT, V, const ORDER: usize>(
+            node: &Node<K, V>,
+            lo: Option<&K>,
+            hi: Option<&K>,
+            is_root: bool,
+        ) -> (usize, usize) {
+            assert!(node.keys.len() < ORDER, "too many keys");
+            assert!(
+                is_root || node.keys.len() >= BTree::<K, V, ORDER>::MIN_KEYS,
+                "too few keys"
+            );
+            assert!(!node.keys.is_empty(), "empty node");
+            assert_eq!(node.keys.len(), node.vals.len());
+            assert!(
+                node.keys.windows(2).all(|w| w[0] < w[1]),
+                "keys out of order"
+            );
+            assert!(lo.is_none_or(|lo| &node.keys[0] > lo), "keys out of order");
+            assert!(
+                hi.is_none_or(|hi| node.keys.last().unwrap() < hi),
+                "keys out of order"
+            );
+
+            if node.is_leaf() {
+                assert_eq!(node.size, node.keys.len(), "inconsistent subtree size");
+                return (1, node.size);
+            }
+
+            assert_eq!(
+                node.children.len(),
+                node.keys.len() + 1,
+                "wrong child count"
+            );
+            let mut height = None;
+            let mut count = node.keys.len();
+            for (i, child) in node.children.iter().enumerate() {
+                let child_lo = if i == 0 { lo } else { Some(&node.keys[i - 1]) };
+                let child_hi = node.keys.get(i).or(hi);
+                let (child_height, child_count) =
+                    visit::<K, V, ORDER>(child, child_lo, child_hi, false);
+                assert!(
+                    height.is_none_or(|h| h == child_height),
+                    "leaves at different depths"
+                );
+                height = Some(child_height);
+                count += child_count;
+            }
+            assert_eq!(node.size, count, "inconsistent subtree size");
+            (height.unwrap() + 1, count)
+        }
+
+        match &st.root {
+            None => assert_eq!(st.size(), 0),
+            Some(root) => {
+                let (_, count) = visit::<K, V, ORDER>(root, None, None, true);
+                assert_eq!(count, st.size());
+            }
+        }
+    }
+
+    fn test_random_put_pop<const ORDER: usize>() {
+        let mut rng = rand::thread_rng();
+        let mut st = BTree::<i32, i32, ORDER>::new();
+        let mut keys: Vec<i32> = (0..500).collect();
+        shuffle_arr(&mut keys);
+        for &key in keys.iter() {
+            st.put(key, -key).unwrap();
+            check_invariants(&st);
+        }
+
+        for _ in 0..2000 {
+            let key = rng.gen_range(-10..510);
+            match rng.gen_range(0..4) {
+                0 => {
+                    st.put(key, key).unwrap();
+                }
+                1 => {
+                    st.pop_min();
+                }
+                2 => {
+                    st.pop_max();
+                }
+                _ => {
+                    let expected = st.get(&key).copied();
+                    assert_eq!(st.pop(&key), expected);
+                    assert_eq!(st.get(&key), None);
+                }
+            }
+            check_invariants(&st);
+        }
+
+        while st.pop_min().is_some() {
+            check_invariants(&st);
+        }
+        assert!(st.is_empty());
+    }
+
+    #[test]
+    fn test_btree() {
+        let mut st = BTree::<char, usize, 3>::new();
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            st.put(key, i).unwrap();
+            check_invariants(&st);
+        }
+        assert_eq!(st.size(), 10);
+        assert_eq!(st.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(st.get(&'E'), Some(&12));
+        assert_eq!(st.get(&'B'), None);
+
+        assert_eq!(st.pop(&'B'), None);
+        assert_eq!(st.pop(&'S'), Some(0));
+        check_invariants(&st);
+        assert_eq!(st.pop(&'E'), Some(12));
+        check_invariants(&st);
+        assert_eq!(st.keys().collect::<String>(), "ACHLMPRX");
+    }
+
+    #[test]
+    fn test_random_put_pop_order_3() {
+        test_random_put_pop::<3>();
+    }
+
+    #[test]
+    fn test_random_put_pop_order_4() {
+        test_random_put_pop::<4>();
+    }
+
+    #[test]
+    fn test_random_put_pop_order_5() {
+        test_random_put_pop::<5>();
+    }
+
+    #[test]
+    fn test_random_put_pop_order_32() {
+        test_random_put_pop::<32>();
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let st = BTree::<i32, i32, 4>::from_sorted((0..1000).map(|i| (i, -i))).unwrap();
+        check_invariants(&st);
+        assert_eq!(st.size(), 1000);
+        assert_eq!(st.get(&500), Some(&-500));
+        assert!(st.iter().map(|(&k, _)| k).eq(0..1000));
+
+        let st = BTree::<i32, i32, 5>::from_sorted([(1, 1), (2, 2), (2, 3), (5, 5)]).unwrap();
+        check_invariants(&st);
+        assert_eq!(st.size(), 3);
+        assert_eq!(st.get(&2), Some(&3));
+
+        let st = BTree::<i32, i32, 5>::from_sorted([]).unwrap();
+        assert!(st.is_empty());
+
+        assert!(BTree::<i32, i32, 5>::from_sorted([(1, 1), (3, 3), (2, 2)]).is_err());
+    }
+
+    /// The number of keys in each node, level by level from the root down.
+    fn key_counts<K: KeyT, V, const ORDER: usize>(st: &BTree<K, V, ORDER>) -> Vec<Vec<usize>> {
+        let mut levels = Vec::new();
+        let mut level: Vec<&Node<K, V>> = st.root.iter().map(|root| root.as_ref()).collect();
+        while !level.is_empty() {
+            levels.push(level.iter().map(|node| node.keys.len()).collect());
+            level = level.iter().flat_map(|node| node.children.iter()).collect();
+        }
+        levels
+    }
+
+    fn test_from_sorted_fill<const ORDER: usize>() {
+        for n in (0..300).chain([1000, 4321]) {
+            let st = BTree::<i32, i32, ORDER>::from_sorted((0..n).map(|i| (i, -i))).unwrap();
+            check_invariants(&st);
+            assert!(st.iter().map(|(&k, _)| k).eq(0..n));
+
+            // every node is full, except maybe the last two on each level
+            // (the last one, topped up to MIN_KEYS from it's left sibling)
+            let levels = key_counts(&st);
+            for level in levels.iter() {
+                let full = level.len().saturating_sub(2);
+                assert!(level[..full].iter().all(|&count| count == ORDER - 1));
+            }
+            let leaves = levels.last().map_or(0, |level| level.len());
+            let expected = if n == 0 {
+                0
+            } else {
+                (n as usize + 1).div_ceil(ORDER)
+            };
+            assert_eq!(leaves, expected);
+        }
+    }
+
+    #[test]
+    fn test_from_sorted_fill_order_3() {
+        test_from_sorted_fill::<3>();
+    }
+
+    #[test]
+    fn test_from_sorted_fill_order_4() {
+        test_from_sorted_fill::<4>();
+    }
+
+    #[test]
+    fn test_from_sorted_fill_order_32() {
+        test_from_sorted_fill::<32>();
+    }
+
+    #[test]
+    fn test_range() {
+        let st = BTree::<i32, i32, 4>::from_sorted((0..100).map(|i| (i, -i))).unwrap();
+        let keys: Vec<i32> = st.range(10..20).map(|(&k, _)| k).collect();
+        assert_eq!(keys, (10..20).collect::<Vec<_>>());
+        let keys: Vec<i32> = st.range(95..).rev().map(|(&k, _)| k).collect();
+        assert_eq!(keys, [99, 98, 97, 96, 95]);
+        assert_eq!(st.range(50..50).count(), 0);
+
+        // meet in the middle
+        let mut iter = st.range(40..=45);
+        assert_eq!(iter.next(), Some((&40, &-40)));
+        assert_eq!(iter.next_back(), Some((&45, &-45)));
+        assert_eq!(iter.next_back(), Some((&44, &-44)));
+        assert_eq!(iter.next(), Some((&41, &-41)));
+        assert_eq!(iter.next(), Some((&42, &-42)));
+        assert_eq!(iter.next(), Some((&43, &-43)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = BTree::<i32, i32, 3>::new();
+        for i in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            st.put(i, i * 10).unwrap();
+        }
+        for (_, val) in st.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(
+            st.values().copied().collect::<Vec<_>>(),
+            [11, 21, 31, 41, 51, 61, 71, 81, 91]
+        );
+        assert_eq!(
+            st.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::marker::PhantomData;
+
+/// Symbol tables that can insert a key which is known not to be in the table,
+/// returning a mutable reference to the newly inserted value. Used by
+/// VacantEntry, so the key is guaranteed to be vacant.
+pub trait VacantInsert<K, V> {
+    fn insert_vacant(&mut self, key: K, val: V) -> Result<&mut V, String>;
+}
+
+/// A view into a single entry of a symbol table, which is either occupied or
+/// vacant (constructed with the `entry` method of a symbol table).
+///
+/// For example, counting words becomes:
+///
+/// ```ignore
+/// *st.entry(word).or_insert(0)? += 1;
+/// ```
+///
+/// Inserting into a vacant entry can fail (for example, if a fixed capacity
+/// table is full), so like put, the inserting methods return a Result.
+pub enum Entry<'a, K, V, ST> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V, ST>),
+}
+
+impl<'a, K, V, ST: VacantInsert<K, V>> Entry<'a, K, V, ST> {
+    /// Get a reference to the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Get a mutable reference to the value, inserting `default` first if the
+    /// entry is vacant.
+    pub fn or_insert(self, default: V) -> Result<&'a mut V, String> {
+        self.or_insert_with(|| default)
+    }
+
+    /// Get a mutable reference to the value, inserting the result of `f`
+    /// first if the entry is vacant (`f` is only called if the entry is
+    /// vacant).
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> Result<&'a mut V, String> {
+        match self {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Modify the value in place if the entry is occupied (does nothing if the
+    /// entry is vacant).
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A view into an entry that exists in the symbol table.
+pub struct OccupiedEntry<'a, K, V> {
+    key: &'a K,
+    val: &'a mut V,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub(super) fn new(key: &'a K, val: &'a mut V) -> Self {
+        OccupiedEntry { key, val }
+    }
+
+    pub fn key(&self) -> &K {
+        self.key
+    }
+
+    pub fn get(&self) -> &V {
+        self.val
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.val
+    }
+
+    /// Convert the entry into a mutable reference to it's value, which lives
+    /// as long as the borrow of the symbol table.
+    pub fn into_mut(self) -> &'a mut V {
+        self.val
+    }
+
+    /// Replace the value of the entry, returning the old value.
+    pub fn insert(&mut self, val: V) -> V {
+        std::mem::replace(self.val, val)
+    }
+}
+
+/// A view into an entry that does not exist in the symbol table.
+pub struct VacantEntry<'a, K, V, ST> {
+    st: &'a mut ST,
+    key: K,
+    _val: PhantomData<V>,
+}
+
+impl<'a, K, V, ST: VacantInsert<K, V>> VacantEntry<'a, K, V, ST> {
+    pub(super) fn new(st: &'a mut ST, key: K) -> Self {
+        VacantEntry {
+            st,
+            key,
+            _val: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take back ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Insert the value into the symbol table, returning a mutable reference
+    /// to it.
+    pub fn insert(self, val: V) -> Result<&'a mut V, String> {
+        self.st.insert_vacant(self.key, val)
+    }
+}
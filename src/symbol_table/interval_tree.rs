@@ -0,0 +1,379 @@
+use crate::list::LinkedList;
+
+use super::{
+    iter::ListIter,
+    red_black_bst::{self, Summary},
+    RedBlackBST,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// A closed interval [lo, hi].
+///
+/// Intervals are ordered by lo, and then by hi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    lo: T,
+    hi: T,
+}
+
+impl<T: Ord> Interval<T> {
+    /// Create the interval [lo, hi] (returns an Err if lo > hi).
+    pub fn new(lo: T, hi: T) -> Result<Self, String> {
+        if lo > hi {
+            return Err("lo > hi".to_string());
+        }
+        Ok(Interval { lo, hi })
+    }
+
+    pub fn lo(&self) -> &T {
+        &self.lo
+    }
+
+    pub fn hi(&self) -> &T {
+        &self.hi
+    }
+
+    /// Whether the two intervals share at least one point.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    /// Whether the point is in the interval.
+    pub fn contains(&self, point: &T) -> bool {
+        &self.lo <= point && point <= &self.hi
+    }
+}
+
+/// The max hi endpoint of the intervals in a subtree.
+struct MaxHi<T>(T);
+
+impl<T: Ord + Clone> Summary<Interval<T>> for MaxHi<T> {
+    fn summarize(interval: &Interval<T>, left: Option<&Self>, right: Option<&Self>) -> Self {
+        let mut max = &interval.hi;
+        for child in [left, right].into_iter().flatten() {
+            max = max.max(&child.0);
+        }
+        MaxHi(max.clone())
+    }
+}
+
+type Link<T, V> = red_black_bst::Link<Interval<T>, V, MaxHi<T>>;
+
+/// An interval tree, that stores (closed) intervals with associated values
+/// and supports finding the intervals that overlap a given interval or point.
+///
+/// The intervals are stored in a left leaning red black BST ordered by their
+/// (lo, hi) endpoints (see RedBlackBST), where each node is augmented with the
+/// max hi endpoint in it's subtree. Put and pop are O(log n), finding any
+/// overlapping interval is O(log n), and finding all k overlapping intervals
+/// is O(k log n).
+///
+/// ### Implementation
+/// The max endpoint of a node only depends on it's own interval and the max
+/// endpoints of it's children, so it's a Summary of the subtree, which the
+/// RedBlackBST balancing code recomputes from the bottom up whenever the
+/// children of a node change (like the subtree sizes).
+///
+/// When searching, the max endpoint lets us skip subtrees: if the max hi in
+/// a subtree is < the lo of the query, then nothing in it can overlap the
+/// query. Likewise, if the lo of a node is > the hi of the query, then
+/// nothing in it's right subtree can overlap the query.
+///
+/// See: https://algs4.cs.princeton.edu/93intersection/
+pub struct IntervalTree<T: Ord, V> {
+    tree: RedBlackBST<Interval<T>, V, MaxHi<T>>,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        IntervalTree {
+            tree: RedBlackBST::empty(),
+        }
+    }
+
+    /// Add (or update) the interval and it's value.
+    pub fn put(&mut self, interval: Interval<T>, val: V) {
+        self.tree.insert_entry(interval, val);
+    }
+
+    /// Get a reference to the value for the interval (None if the interval
+    /// does not exist).
+    pub fn get(&self, interval: &Interval<T>) -> Option<&V> {
+        let node = RedBlackBST::<_, V, MaxHi<T>>::search(self.tree.root(), interval)?;
+        Some(&node.val)
+    }
+
+    /// Remove the interval and return it's value (None if the interval does
+    /// not exist). See RedBlackBST::pop.
+    pub fn pop(&mut self, interval: &Interval<T>) -> Option<V> {
+        self.tree.delete_entry(interval)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.root().is_none()
+    }
+
+    pub fn size(&self) -> usize {
+        red_black_bst::size(self.tree.root())
+    }
+
+    /// Iterate over the intervals (ordered by lo, then hi) and their values.
+    pub fn iter(&self) -> ListIter<(&Interval<T>, &V)> {
+        fn visit<'a, T: Ord, V>(
+            link: &'a Link<T, V>,
+            list: &mut LinkedList<(&'a Interval<T>, &'a V)>,
+        ) {
+            if let Some(node) = link {
+                visit(&node.left, list);
+                list.push_back((&node.key, &node.val));
+                visit(&node.right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.tree.root(), &mut list);
+        ListIter::new(list)
+    }
+
+    /// Get any interval that overlaps the query (None if no interval
+    /// overlaps it).
+    ///
+    /// ### Implementation
+    /// If the current interval overlaps the query we're done. Otherwise, if
+    /// the max endpoint of the left subtree is >= the lo of the query, we go
+    /// left, and if not, we go right. Going left is safe, since if no interval
+    /// in the left subtree overlaps, then the one ending at the max endpoint
+    /// must start after the query ends, so every interval in the right
+    /// subtree does too. O(log n).
+    pub fn any_overlap(&self, query: &Interval<T>) -> Option<(&Interval<T>, &V)> {
+        let mut link = self.tree.root();
+        while let Some(node) = link {
+            if node.key.overlaps(query) {
+                return Some((&node.key, &node.val));
+            }
+            link = match &node.left {
+                Some(left) if left.summary.0 >= query.lo => &node.left,
+                _ => &node.right,
+            };
+        }
+        None
+    }
+
+    /// Iterate over all the intervals that overlap the query (ordered by lo,
+    /// then hi), and their values.
+    ///
+    /// ### Implementation
+    /// An in-order traversal, where we skip the subtrees that can't contain
+    /// an overlapping interval (see IntervalTree). O(k log n) for k matching
+    /// intervals.
+    pub fn all_overlaps(&self, query: &Interval<T>) -> ListIter<(&Interval<T>, &V)> {
+        fn visit<'a, T: Ord, V>(
+            link: &'a Link<T, V>,
+            query: &Interval<T>,
+            list: &mut LinkedList<(&'a Interval<T>, &'a V)>,
+        ) {
+            let Some(node) = link else {
+                return;
+            };
+            if node.summary.0 < query.lo {
+                return;
+            }
+            visit(&node.left, query, list);
+            if node.key.overlaps(query) {
+                list.push_back((&node.key, &node.val));
+            }
+            if node.key.lo <= query.hi {
+                visit(&node.right, query, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.tree.root(), query, &mut list);
+        ListIter::new(list)
+    }
+
+    /// Get any interval that contains the point (None if no interval
+    /// contains it). See any_overlap.
+    pub fn any_containing(&self, point: &T) -> Option<(&Interval<T>, &V)> {
+        self.any_overlap(&Interval::new(point.clone(), point.clone()).unwrap())
+    }
+
+    /// Iterate over all the intervals that contain the point (ordered by lo,
+    /// then hi), and their values. See all_overlaps.
+    pub fn all_containing(&self, point: &T) -> ListIter<(&Interval<T>, &V)> {
+        self.all_overlaps(&Interval::new(point.clone(), point.clone()).unwrap())
+    }
+}
+
+/// Serialized as a (lo, hi) pair, and validated on load (see Interval::new).
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Interval<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.lo, &self.hi).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Deserialize<'de>> Deserialize<'de> for Interval<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (lo, hi) = <(T, T)>::deserialize(deserializer)?;
+        Interval::new(lo, hi).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serialized as a sequence of (interval, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<T: Ord + Clone + Serialize, V: Serialize> Serialize for IntervalTree<T, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Clone + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de>
+    for IntervalTree<T, V>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |st, (interval, val)| {
+            st.put(interval, val);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    /// Checks the red black tree invariants (see RedBlackBST::check), and that
+    /// the max endpoints are consistent.
+    fn check_invariants<T: Ord + Clone + std::fmt::Debug, V>(st: &IntervalTree<T, V>) {
+        fn visit<T: Ord + Clone + std::fmt::Debug, V>(link: &Link<T, V>) -> Option<T> {
+            let node = link.as_ref()?;
+            let max = [
+                visit(&node.left),
+                visit(&node.right),
+                Some(node.key.hi.clone()),
+            ]
+            .into_iter()
+            .flatten()
+            .max();
+            assert_eq!(Some(&node.summary.0), max.as_ref(), "inconsistent max");
+            max
+        }
+
+        st.tree.check().unwrap();
+        visit(st.tree.root());
+    }
+
+    fn interval(lo: i32, hi: i32) -> Interval<i32> {
+        Interval::new(lo, hi).unwrap()
+    }
+
+    #[test]
+    fn test_interval() {
+        assert!(Interval::new(3, 2).is_err());
+        let a = interval(1, 5);
+        assert_eq!((a.lo(), a.hi()), (&1, &5));
+        assert!(a.overlaps(&interval(5, 8)));
+        assert!(a.overlaps(&interval(2, 3)));
+        assert!(a.overlaps(&interval(-1, 10)));
+        assert!(!a.overlaps(&interval(6, 8)));
+        assert!(!a.overlaps(&interval(-3, 0)));
+        assert!(a.contains(&1) && a.contains(&5));
+        assert!(!a.contains(&0) && !a.contains(&6));
+    }
+
+    #[test]
+    fn test_bookings() {
+        let mut st = IntervalTree::new();
+        let bookings = [(9, 10), (10, 12), (13, 15), (8, 9), (16, 18), (11, 14)];
+        for (i, &(lo, hi)) in bookings.iter().enumerate() {
+            st.put(interval(lo, hi), i);
+            check_invariants(&st);
+        }
+        assert_eq!(st.size(), 6);
+        assert_eq!(st.get(&interval(13, 15)), Some(&2));
+        assert_eq!(st.get(&interval(13, 14)), None);
+
+        let overlaps = |query: Interval<i32>| {
+            st.all_overlaps(&query)
+                .map(|(interval, _)| (interval.lo, interval.hi))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(overlaps(interval(12, 13)), [(10, 12), (11, 14), (13, 15)]);
+        assert_eq!(overlaps(interval(15, 15)), [(13, 15)]);
+        assert_eq!(overlaps(interval(19, 20)), []);
+        assert_eq!(overlaps(interval(0, 100)).len(), 6);
+        assert!(st.any_overlap(&interval(19, 20)).is_none());
+        assert!(st.any_overlap(&interval(17, 20)).is_some());
+
+        assert_eq!(st.any_containing(&17).map(|(_, &val)| val), Some(4));
+        assert_eq!(
+            st.all_containing(&9)
+                .map(|(_, &val)| val)
+                .collect::<Vec<_>>(),
+            [3, 0]
+        );
+        assert!(st.any_containing(&15).is_some());
+        assert!(st.any_containing(&100).is_none());
+
+        assert_eq!(st.pop(&interval(16, 18)), Some(4));
+        assert_eq!(st.pop(&interval(16, 18)), None);
+        check_invariants(&st);
+        assert!(st.any_containing(&17).is_none());
+        assert_eq!(
+            st.iter().map(|(_, &val)| val).collect::<Vec<_>>(),
+            [3, 0, 1, 5, 2]
+        );
+    }
+
+    #[test]
+    fn test_random_queries() {
+        let mut rng = rand::thread_rng();
+        let mut st = IntervalTree::new();
+        let mut intervals = Vec::new();
+        for _ in 0..300 {
+            let lo = rng.gen_range(0..1000);
+            let interval = interval(lo, lo + rng.gen_range(0..50));
+            st.put(interval, ());
+            intervals.push(interval);
+            check_invariants(&st);
+        }
+        intervals.sort();
+        intervals.dedup();
+        assert_eq!(st.size(), intervals.len());
+
+        for i in 0..200 {
+            if i % 2 == 0 {
+                let index = rng.gen_range(0..intervals.len());
+                assert!(st.pop(&intervals.remove(index)).is_some());
+                check_invariants(&st);
+            }
+
+            let lo = rng.gen_range(-50..1050);
+            let query = interval(lo, lo + rng.gen_range(0..20));
+            let expected: Vec<_> = intervals.iter().filter(|i| i.overlaps(&query)).collect();
+            let actual: Vec<_> = st.all_overlaps(&query).map(|(i, _)| i).collect();
+            assert_eq!(actual, expected);
+            match st.any_overlap(&query) {
+                Some((interval, _)) => assert!(interval.overlaps(&query)),
+                None => assert!(expected.is_empty()),
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::list::LinkedList;
+
+/// An iterator over the keys of a symbol table, built on top of an iterator
+/// over it's entries.
+pub struct Keys<I> {
+    iter: I,
+}
+
+impl<I> Keys<I> {
+    pub(super) fn new(iter: I) -> Self {
+        Keys { iter }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I: Iterator<Item = (&'a K, &'a V)>> Iterator for Keys<I> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.0)
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I: DoubleEndedIterator<Item = (&'a K, &'a V)>> DoubleEndedIterator
+    for Keys<I>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next_back()?.0)
+    }
+}
+
+/// An iterator over the values of a symbol table, built on top of an
+/// iterator over it's entries.
+pub struct Values<I> {
+    iter: I,
+}
+
+impl<I> Values<I> {
+    pub(super) fn new(iter: I) -> Self {
+        Values { iter }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I: Iterator<Item = (&'a K, &'a V)>> Iterator for Values<I> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.1)
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I: DoubleEndedIterator<Item = (&'a K, &'a V)>> DoubleEndedIterator
+    for Values<I>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next_back()?.1)
+    }
+}
+
+/// An iterator that yields the entries of a tree in sorted order, after they
+/// have been collected into a doubly linked list.
+///
+/// Iterating a tree lazily from both ends with mutable references (or by
+/// value) would require both ends to have access to the same nodes. Instead,
+/// we do a single in-order traversal up front and collect the entries into a
+/// list, which we can then pop from either end. This is used to implement
+/// iter_mut and into_iter for the tree symbol tables.
+pub struct ListIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> ListIter<T> {
+    pub(super) fn new(list: LinkedList<T>) -> Self {
+        ListIter { list }
+    }
+}
+
+impl<T> Iterator for ListIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+}
+
+impl<T> DoubleEndedIterator for ListIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
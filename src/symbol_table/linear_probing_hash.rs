@@ -0,0 +1,440 @@
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::list::Vector;
+
+use super::{
+    iter::{Keys, Values},
+    KeyT, Lookup, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// Initial (and minimum) number of slots.
+const INIT_CAPACITY: usize = 4;
+
+/// A hash table that stores key-value pairs, resolving collisions with
+/// linear probing.
+///
+/// The entries are stored in parallel arrays of m slots. A hash function maps
+/// each key to a slot, and if that slot is already taken by a different key
+/// (a collision), we probe the next slot, and the next, and so on (wrapping
+/// around), until we find the key or an empty slot. An empty slot means the
+/// key is not in the table.
+///
+/// Probing stays short as long as the table is not too full, so we keep the
+/// load factor n/m between 1/8 and 1/2 by doubling the number of slots when
+/// the table gets too full (and halving it when it gets too sparse). Under
+/// uniform hashing, put, get and pop are then O(1) on average.
+///
+/// The hash function is pluggable via the BuildHasher `S` (see with_hasher),
+/// and defaults to the one used by the std HashMap.
+///
+/// See: https://algs4.cs.princeton.edu/34hash/
+pub struct LinearProbingHashST<K: KeyT, V, S = RandomState> {
+    keys: Vector<Option<K>>,
+    vals: Vector<Option<V>>,
+    size: usize,
+    hash_builder: S,
+}
+
+impl<K: KeyT + Hash, V> LinearProbingHashST<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher + Default> Default for LinearProbingHashST<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher> LinearProbingHashST<K, V, S> {
+    /// Create an empty table which uses the given hash builder to hash keys.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        LinearProbingHashST {
+            keys: empty_slots(INIT_CAPACITY),
+            vals: empty_slots(INIT_CAPACITY),
+            size: 0,
+            hash_builder,
+        }
+    }
+
+    /// The number of slots in the table.
+    pub fn capacity(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Iterate over the entries (in no particular order).
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            keys: self.keys.iter(),
+            vals: self.vals.iter(),
+        }
+    }
+
+    /// Iterate over the entries (in no particular order), with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            keys: self.keys.iter(),
+            vals: self.vals.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys (in no particular order).
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values (in no particular order).
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Index of the first slot to probe for the given key.
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> usize {
+        (self.hash_builder.hash_one(key) % self.keys.len() as u64) as usize
+    }
+
+    /// Index of the slot that holds the given key, or the empty slot that ends
+    /// it's probe sequence if the key does not exist.
+    fn probe<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut i = self.hash(key);
+        while let Some(k) = &self.keys[i] {
+            if k.borrow() == key {
+                break;
+            }
+            i = (i + 1) % self.keys.len();
+        }
+        i
+    }
+
+    /// Add (or update) the key-value pair, without resizing. There must be at
+    /// least one empty slot, else probing would never end.
+    fn insert(&mut self, key: K, val: V) {
+        let i = self.probe(&key);
+        if self.keys[i].is_none() {
+            self.size += 1;
+        }
+        self.keys[i] = Some(key);
+        self.vals[i] = Some(val);
+    }
+
+    /// Rehash all the entries into a new table with the given number of
+    /// slots.
+    fn resize(&mut self, num_slots: usize) {
+        let mut old_keys = std::mem::replace(&mut self.keys, empty_slots(num_slots));
+        let mut old_vals = std::mem::replace(&mut self.vals, empty_slots(num_slots));
+        self.size = 0;
+        for (key, val) in old_keys.iter_mut().zip(old_vals.iter_mut()) {
+            if let (Some(key), Some(val)) = (key.take(), val.take()) {
+                self.insert(key, val);
+            }
+        }
+    }
+}
+
+fn empty_slots<T>(num_slots: usize) -> Vector<Option<T>> {
+    let mut slots = Vector::new();
+    for _ in 0..num_slots {
+        slots.push(None);
+    }
+    slots
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher> SymbolTable<K, V> for LinearProbingHashST<K, V, S> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// If the table is half full, we first double the number of slots. Then we
+    /// probe from the hash of the key until we find the key (update the value)
+    /// or an empty slot (insert the entry there).
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        if self.size >= self.keys.len() / 2 {
+            self.resize(2 * self.keys.len());
+        }
+        self.insert(key, val);
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher, Q: ?Sized + Hash + Eq> Lookup<Q, V>
+    for LinearProbingHashST<K, V, S>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    ///
+    /// ### Implementation
+    /// Probe from the hash of the key until we find the key or an empty slot.
+    fn get(&self, key: &Q) -> Option<&V> {
+        self.vals[self.probe(key)].as_ref()
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// We can't simply empty the slot of the key, since that would cut off the
+    /// probe sequence of any keys further along in the same cluster (the run
+    /// of non-empty slots), making them unreachable. Instead of leaving a
+    /// "tombstone" marker in the slot, we reinsert all the keys in the rest of
+    /// the cluster, which moves them back into a reachable position. If the
+    /// table drops to 1/8 full, we halve the number of slots.
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let mut i = self.probe(key);
+        self.keys[i].take()?;
+        let val = self.vals[i].take();
+        self.size -= 1;
+
+        i = (i + 1) % self.keys.len();
+        while let Some(key) = self.keys[i].take() {
+            let val = self.vals[i].take().unwrap();
+            self.size -= 1;
+            self.insert(key, val);
+            i = (i + 1) % self.keys.len();
+        }
+
+        if self.keys.len() > INIT_CAPACITY && self.size <= self.keys.len() / 8 {
+            self.resize(self.keys.len() / 2);
+        }
+        val
+    }
+}
+
+/// An iterator over the entries of a LinearProbingHashST, in the order of
+/// their slots.
+pub struct Iter<'a, K, V> {
+    keys: std::slice::Iter<'a, Option<K>>,
+    vals: std::slice::Iter<'a, Option<V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(key), Some(val)) = (self.keys.next()?, self.vals.next()?) {
+                return Some((key, val));
+            }
+        }
+    }
+}
+
+/// An iterator over the entries of a LinearProbingHashST, in the order of
+/// their slots, with mutable references to the values.
+pub struct IterMut<'a, K, V> {
+    keys: std::slice::Iter<'a, Option<K>>,
+    vals: std::slice::IterMut<'a, Option<V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(key), Some(val)) = (self.keys.next()?, self.vals.next()?) {
+                return Some((key, val));
+            }
+        }
+    }
+}
+
+/// An owning iterator over the entries of a LinearProbingHashST.
+pub struct IntoIter<K, V> {
+    keys: Vector<Option<K>>,
+    vals: Vector<Option<V>>,
+    i: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i < self.keys.len() {
+            self.i += 1;
+            if let (Some(key), Some(val)) =
+                (self.keys[self.i - 1].take(), self.vals[self.i - 1].take())
+            {
+                return Some((key, val));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, K: KeyT + Hash, V, S: BuildHasher> IntoIterator for &'a LinearProbingHashST<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT + Hash, V, S: BuildHasher> IntoIterator for &'a mut LinearProbingHashST<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT + Hash, V, S> IntoIterator for LinearProbingHashST<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            keys: self.keys,
+            vals: self.vals,
+            i: 0,
+        }
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in no particular order,
+/// and rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Hash + Serialize, V: Serialize, S: BuildHasher> Serialize
+    for LinearProbingHashST<K, V, S>
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Hash + Deserialize<'de>, V: Deserialize<'de>, S: BuildHasher + Default>
+    Deserialize<'de> for LinearProbingHashST<K, V, S>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasherDefault;
+
+    use super::*;
+
+    /// A hash function that sends every key to the same slot, so every key
+    /// ends up in a single cluster.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl std::hash::Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    #[test]
+    fn test_linear_probing_hash_st() {
+        let mut st = LinearProbingHashST::new();
+        assert!(st.is_empty());
+        assert_eq!(st.get("a"), None);
+        assert_eq!(st.pop("a"), None);
+
+        for (i, word) in "it was the best of times it was the worst of times"
+            .split(' ')
+            .enumerate()
+        {
+            st.put(word.to_string(), i).unwrap();
+        }
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get("it"), Some(&6));
+        assert_eq!(st.get("times"), Some(&11));
+        assert_eq!(st.get("age"), None);
+        assert_eq!(st.pop("best"), Some(3));
+        assert_eq!(st.pop("best"), None);
+        assert_eq!(st.size(), 6);
+
+        let mut keys: Vec<&str> = st.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["it", "of", "the", "times", "was", "worst"]);
+
+        for (_, val) in st.iter_mut() {
+            *val *= 10;
+        }
+        assert_eq!(st.get("worst"), Some(&90));
+
+        let mut entries: Vec<(String, usize)> = st.into_iter().collect();
+        entries.sort();
+        assert_eq!(entries[0], ("it".to_string(), 60));
+        assert_eq!(entries.len(), 6);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut st = LinearProbingHashST::new();
+        assert_eq!(st.capacity(), INIT_CAPACITY);
+        for i in 0..1000 {
+            st.put(i, -i).unwrap();
+            assert!(2 * st.size() <= st.capacity());
+        }
+        for i in 0..1000 {
+            assert_eq!(st.get(&i), Some(&-i));
+        }
+        for i in 0..1000 {
+            assert_eq!(st.pop(&i), Some(-i));
+            assert_eq!(st.get(&i), None);
+            assert!(st.capacity() == INIT_CAPACITY || 8 * st.size() > st.capacity());
+        }
+        assert!(st.is_empty());
+        assert_eq!(st.capacity(), INIT_CAPACITY);
+    }
+
+    #[test]
+    fn test_pop_from_cluster() {
+        // every key collides, so popping from the middle of the cluster must
+        // keep the keys after it reachable
+        let mut st: LinearProbingHashST<i32, i32, BuildHasherDefault<ConstantHasher>> =
+            LinearProbingHashST::default();
+        for i in 0..100 {
+            st.put(i, i * i).unwrap();
+        }
+        st.put(50, 0).unwrap();
+        assert_eq!(st.size(), 100);
+        assert_eq!(st.get(&50), Some(&0));
+        for i in (0..100).step_by(3) {
+            assert_eq!(st.pop(&i), Some(i * i));
+        }
+        for i in 0..100 {
+            let expected = match i {
+                50 => Some(0),
+                _ if i % 3 == 0 => None,
+                _ => Some(i * i),
+            };
+            assert_eq!(st.get(&i).copied(), expected);
+        }
+        assert_eq!(st.iter().count(), st.size());
+    }
+}
@@ -1,34 +1,73 @@
+mod avl_tree;
 mod binary_search_tree;
+mod btree;
+mod entry;
+mod interval_tree;
+mod iter;
+mod linear_probing_hash;
 mod ordered_arr;
+mod ordered_vector;
+mod persistent_red_black_bst;
 mod red_black_bst;
+mod separate_chaining_hash;
+mod set;
+mod skip_list;
+mod splay_tree;
+mod treap;
+mod trie_st;
+mod tst;
 
+use std::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+};
+
+pub use avl_tree::AVLTree;
 pub use binary_search_tree::BinarySearchTree;
+pub use btree::BTree;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use interval_tree::{Interval, IntervalTree};
+pub use linear_probing_hash::LinearProbingHashST;
 pub use ordered_arr::OrderedArrST;
+pub use ordered_vector::OrderedVectorST;
+pub use persistent_red_black_bst::PersistentRedBlackBST;
 pub use red_black_bst::RedBlackBST;
+pub use separate_chaining_hash::SeparateChainingHashST;
+pub use set::{HashSet, Merge, OrderedSet};
+pub use skip_list::SkipList;
+pub use splay_tree::SplayTree;
+pub use treap::Treap;
+pub use trie_st::TrieST;
+pub use tst::TST;
 
-/// Keys must be Clone, Copy, and Ord (i.e. defines ==, =>, <=, >, <).
-/// Technically only == is needed for a regular non-ordered Symbol Table,
-/// however for simplicity/consistency with OrderedSymbolTable, we'll leave this
-/// as is.
+/// Keys must be Ord (i.e. defines ==, =>, <=, >, <). The hash tables (and
+/// HashSet) additionally require their keys to be Hash.
 ///
-/// The Clone + Copy are so we can pass around Key by reference, which is a lot
-/// more convenient than passing around by reference. This is probably fine
-/// since the key types that are likely to be used will be integers or strings,
-/// which implement Clone + Copy.
+/// Keys are moved into the table on put, and all other operations take the key
+/// by reference. Like the std maps, lookups accept any borrowed form `&Q` of
+/// the key type (where `K: Borrow<Q>`), so for example a table with `String`
+/// keys can be queried with a `&str`. The `Ord` (and `Hash`) implementations
+/// for `Q` must match those for `K`.
+pub trait KeyT: Ord {}
+impl<T: ?Sized + Ord> KeyT for T {}
 
-pub trait KeyT: Ord + Clone + Copy {}
-impl<T: Ord + Clone + Copy> KeyT for T {}
+/// Lookups by a borrowed form `&Q` of the key type. This is separate from
+/// SymbolTable so each table can put it's own bound on `Q` (the ordered
+/// tables compare keys, so need `Q: Ord`, while the hash tables hash them, so
+/// need `Q: Hash + Eq`).
+pub trait Lookup<Q: ?Sized, V> {
+    fn get(&self, key: &Q) -> Option<&V>;
+    fn pop(&mut self, key: &Q) -> Option<V>;
+}
 
 /// Symbol tables stores key-value pairs.
 ///
 /// See: https://algs4.cs.princeton.edu/31elementary/
-pub trait SymbolTable<K: KeyT, V>
+pub trait SymbolTable<K: KeyT, V>: Lookup<K, V>
 where
-    for<'a> &'a Self: IntoIterator<Item = &'a K>,
+    for<'a> &'a Self: IntoIterator<Item = (&'a K, &'a V)>,
 {
     fn put(&mut self, key: K, val: V) -> Result<(), String>;
-    fn get(&self, key: K) -> Option<&V>;
-    fn pop(&mut self, key: K) -> Option<V>;
     fn is_empty(&self) -> bool;
     fn size(&self) -> usize;
 }
@@ -39,14 +78,453 @@ where
 /// See: https://algs4.cs.princeton.edu/31elementary/
 pub trait OrderedSymbolTable<K: KeyT, V>: SymbolTable<K, V>
 where
-    for<'a> &'a Self: IntoIterator<Item = &'a K>,
+    for<'a> &'a Self: IntoIterator<Item = (&'a K, &'a V)>,
 {
-    fn min(&self) -> Option<K>;
-    fn max(&self) -> Option<K>;
-    fn floor(&self, key: K) -> Option<K>;
-    fn ceil(&self, key: K) -> Option<K>;
-    fn rank(&self, key: K) -> usize;
-    fn select(&self, rank: usize) -> Option<K>;
-    fn pop_min(&self) -> Option<(K, V)>;
-    fn pop_max(&self) -> Option<(K, V)>;
+    fn min(&self) -> Option<&K>;
+    fn max(&self) -> Option<&K>;
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>;
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>;
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>;
+    fn select(&self, rank: usize) -> Option<&K>;
+    fn pop_min(&mut self) -> Option<(K, V)>;
+    fn pop_max(&mut self) -> Option<(K, V)>;
+
+    /// Get the number of keys that fall in the given range (e.g. `lo..hi`,
+    /// `lo..=hi`, `..hi`).
+    ///
+    /// ### Implementation
+    /// The number of keys in the range is the difference between the rank of
+    /// the end of the range and the rank of the start of the range, adjusting
+    /// for whether the endpoints are included and exist in the table.
+    fn range_count<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let contains = |key: &Q| self.floor(key).is_some_and(|k| k.borrow() == key) as usize;
+
+        let lo = match range.start_bound() {
+            Bound::Included(lo) => self.rank(lo),
+            Bound::Excluded(lo) => self.rank(lo) + contains(lo),
+            Bound::Unbounded => 0,
+        };
+        let hi = match range.end_bound() {
+            Bound::Included(hi) => self.rank(hi) + contains(hi),
+            Bound::Excluded(hi) => self.rank(hi),
+            Bound::Unbounded => self.size(),
+        };
+
+        hi.saturating_sub(lo)
+    }
+
+    /// Get the k-th smallest key, counting from 1 (None if k is 0 or k > size).
+    ///
+    /// ### Implementation
+    /// The k-th smallest key is the key with rank k - 1 (see select).
+    fn kth_smallest(&self, k: usize) -> Option<&K> {
+        self.select(k.checked_sub(1)?)
+    }
+
+    /// Get the median key (None if the table is empty). For an even number of
+    /// keys, this is the lower of the two middle keys.
+    ///
+    /// ### Implementation
+    /// The median is the key with rank (size - 1) / 2 (see select).
+    fn median(&self) -> Option<&K> {
+        self.select(self.size().checked_sub(1)? / 2)
+    }
+
+    /// Get the p-th percentile key, that is the smallest key such that at
+    /// least p percent of the keys are <= to it (None if the table is empty or
+    /// p is not in [0, 100]). The 0th percentile is the min, the 50th is the
+    /// median and the 100th is the max.
+    ///
+    /// ### Implementation
+    /// Using the nearest rank method, the p-th percentile is the k-th smallest
+    /// key for k = ceil(p * size / 100) (or 1 if that's 0) (see select).
+    fn percentile(&self, p: f64) -> Option<&K> {
+        if !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        // p / 100 is inexact (e.g. 0.07 * 100 > 7), so divide last
+        let k = (p * self.size() as f64 / 100.0).ceil() as usize;
+        self.kth_smallest(k.max(1))
+    }
+}
+
+/// Whether the key is at or after the start of a range (i.e. the key is not
+/// cut off by the range's lower bound).
+fn is_after_start<K: Borrow<Q>, Q: ?Sized + Ord>(key: &K, start: Bound<&Q>) -> bool {
+    match start {
+        Bound::Included(lo) => key.borrow() >= lo,
+        Bound::Excluded(lo) => key.borrow() > lo,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Whether the key is at or before the end of a range (i.e. the key is not
+/// cut off by the range's upper bound).
+fn is_before_end<K: Borrow<Q>, Q: ?Sized + Ord>(key: &K, end: Bound<&Q>) -> bool {
+    match end {
+        Bound::Included(hi) => key.borrow() <= hi,
+        Bound::Excluded(hi) => key.borrow() < hi,
+        Bound::Unbounded => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, ops::Bound};
+
+    use rand::Rng;
+
+    use crate::random::shuffle_arr;
+
+    use super::*;
+
+    fn test_ordered_symbol_table<ST>(st: &mut ST)
+    where
+        ST: OrderedSymbolTable<i32, i32>,
+        for<'a> &'a ST: IntoIterator<Item = (&'a i32, &'a i32)>,
+    {
+        assert!(st.is_empty());
+        assert_eq!(st.min(), None);
+        assert_eq!(st.max(), None);
+        assert_eq!(st.median(), None);
+        assert_eq!(st.percentile(50.0), None);
+        assert_eq!(st.floor(&0), None);
+        assert_eq!(st.ceil(&0), None);
+        assert_eq!(st.rank(&0), 0);
+        assert_eq!(st.select(0), None);
+        assert_eq!(st.pop_min(), None);
+        assert_eq!(st.pop_max(), None);
+
+        // even keys 0, 2, ..., 98 inserted in random order
+        let mut keys: Vec<i32> = (0..50).map(|x| 2 * x).collect();
+        shuffle_arr(&mut keys);
+        for &key in keys.iter() {
+            st.put(key, -key).unwrap();
+        }
+        assert_eq!(st.size(), 50);
+
+        assert_eq!(st.min(), Some(&0));
+        assert_eq!(st.max(), Some(&98));
+
+        assert_eq!(st.floor(&-1), None);
+        assert_eq!(st.floor(&0), Some(&0));
+        assert_eq!(st.floor(&51), Some(&50));
+        assert_eq!(st.floor(&1000), Some(&98));
+
+        assert_eq!(st.ceil(&-1), Some(&0));
+        assert_eq!(st.ceil(&51), Some(&52));
+        assert_eq!(st.ceil(&98), Some(&98));
+        assert_eq!(st.ceil(&99), None);
+
+        for i in 0..50 {
+            assert_eq!(st.rank(&(2 * i)), i as usize);
+            assert_eq!(st.rank(&(2 * i + 1)), i as usize + 1);
+            assert_eq!(st.select(i as usize), Some(&(2 * i)));
+        }
+        assert_eq!(st.rank(&-5), 0);
+        assert_eq!(st.select(50), None);
+
+        assert_eq!(st.kth_smallest(0), None);
+        assert_eq!(st.kth_smallest(1), Some(&0));
+        assert_eq!(st.kth_smallest(50), Some(&98));
+        assert_eq!(st.kth_smallest(51), None);
+        assert_eq!(st.median(), Some(&48));
+        assert_eq!(st.percentile(0.0), Some(&0));
+        assert_eq!(st.percentile(50.0), Some(&48));
+        assert_eq!(st.percentile(90.0), Some(&88));
+        assert_eq!(st.percentile(99.0), Some(&98));
+        assert_eq!(st.percentile(100.0), Some(&98));
+        assert_eq!(st.percentile(100.5), None);
+        assert_eq!(st.percentile(f64::NAN), None);
+
+        assert_eq!(st.range_count(10..20), 5);
+        assert_eq!(st.range_count(10..=20), 6);
+        assert_eq!(st.range_count(11..=19), 4);
+        assert_eq!(st.range_count(..10), 5);
+        assert_eq!(st.range_count(90..), 5);
+        assert_eq!(st.range_count(..), 50);
+        assert_eq!(st.range_count(-100..1000), 50);
+        assert_eq!(
+            st.range_count((Bound::Included(20), Bound::Excluded(10))),
+            0
+        );
+        assert_eq!(st.range_count(10..10), 0);
+        assert_eq!(st.range_count(10..=10), 1);
+        assert_eq!(
+            st.range_count((Bound::Excluded(10), Bound::Included(20))),
+            5
+        );
+
+        assert_eq!(st.pop_min(), Some((0, 0)));
+        assert_eq!(st.pop_max(), Some((98, -98)));
+        assert_eq!(st.pop_min(), Some((2, -2)));
+        assert_eq!(st.size(), 47);
+        assert_eq!(st.min(), Some(&4));
+        assert_eq!(st.max(), Some(&96));
+        assert_eq!(st.rank(&50), 23);
+        assert_eq!(st.select(23), Some(&50));
+        assert_eq!(st.median(), Some(&50));
+
+        // remove the rest in random order, checking rank/select as we go
+        let mut remaining: Vec<i32> = st.into_iter().map(|(&k, _)| k).collect();
+        shuffle_arr(&mut remaining);
+        while let Some(key) = remaining.pop() {
+            assert_eq!(st.pop(&key), Some(-key));
+            let mut sorted = remaining.clone();
+            sorted.sort();
+            for (i, &k) in sorted.iter().enumerate() {
+                assert_eq!(st.rank(&k), i);
+                assert_eq!(st.select(i), Some(&k));
+            }
+            assert_eq!(st.size(), sorted.len());
+            assert_eq!(st.min(), sorted.first());
+            assert_eq!(st.max(), sorted.last());
+        }
+        assert!(st.is_empty());
+    }
+
+    /// Owned String keys, queried with &str.
+    fn test_borrowed_keys<ST>(st: &mut ST)
+    where
+        ST: OrderedSymbolTable<String, usize> + Lookup<str, usize>,
+        for<'a> &'a ST: IntoIterator<Item = (&'a String, &'a usize)>,
+    {
+        let words = ["pear", "apple", "fig", "banana", "cherry"];
+        for (i, word) in words.iter().enumerate() {
+            st.put(word.to_string(), i).unwrap();
+        }
+        assert_eq!(st.get("apple"), Some(&1));
+        assert_eq!(st.get("grape"), None);
+        assert_eq!(st.min().map(String::as_str), Some("apple"));
+        assert_eq!(st.max().map(String::as_str), Some("pear"));
+        assert_eq!(st.floor("c").map(String::as_str), Some("banana"));
+        assert_eq!(st.ceil("c").map(String::as_str), Some("cherry"));
+        assert_eq!(st.rank("cherry"), 2);
+        assert_eq!(
+            st.range_count::<str, _>((Bound::Included("b"), Bound::Excluded("g"))),
+            3
+        );
+        assert_eq!(st.pop("fig"), Some(2));
+        assert_eq!(st.pop("fig"), None);
+        assert_eq!(st.size(), 4);
+    }
+
+    /// A random mix of puts, gets and pops (by key, min and max), with every
+    /// result checked against a BTreeMap model. The check is called after
+    /// each operation, for the table's own structural invariants.
+    pub(super) fn test_random_ops<ST>(st: &mut ST, mut check: impl FnMut(&ST))
+    where
+        ST: OrderedSymbolTable<i32, i32>,
+        for<'a> &'a ST: IntoIterator<Item = (&'a i32, &'a i32)>,
+    {
+        let mut rng = rand::thread_rng();
+        let mut model = BTreeMap::new();
+        for _ in 0..3000 {
+            let key = rng.gen_range(0..200);
+            match rng.gen_range(0..10) {
+                0..=3 => {
+                    let val = rng.gen();
+                    st.put(key, val).unwrap();
+                    model.insert(key, val);
+                }
+                4 => assert_eq!(st.get(&key), model.get(&key)),
+                5..=6 => assert_eq!(st.pop(&key), model.remove(&key)),
+                7 => assert_eq!(st.pop_min(), model.pop_first()),
+                8 => assert_eq!(st.pop_max(), model.pop_last()),
+                _ => {
+                    let rank = model.range(..key).count();
+                    assert_eq!(st.rank(&key), rank);
+                    assert_eq!(st.select(rank), model.range(key..).next().map(|(k, _)| k));
+                    assert_eq!(
+                        st.floor(&key),
+                        model.range(..=key).next_back().map(|(k, _)| k)
+                    );
+                }
+            }
+            check(st);
+            assert_eq!(st.size(), model.len());
+            assert_eq!(st.min(), model.keys().next());
+            assert_eq!(st.max(), model.keys().next_back());
+        }
+        assert!(st.into_iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn test_binary_search_tree() {
+        test_ordered_symbol_table(&mut BinarySearchTree::new());
+        test_borrowed_keys(&mut BinarySearchTree::new());
+    }
+
+    #[test]
+    fn test_avl_tree() {
+        test_ordered_symbol_table(&mut AVLTree::new());
+        test_borrowed_keys(&mut AVLTree::new());
+    }
+
+    #[test]
+    fn test_btree() {
+        test_ordered_symbol_table(&mut BTree::<i32, i32, 3>::new());
+        test_ordered_symbol_table(&mut BTree::<i32, i32, 4>::new());
+        test_ordered_symbol_table(&mut BTree::<i32, i32, 16>::new());
+        test_borrowed_keys(&mut BTree::<String, usize, 3>::new());
+    }
+
+    #[test]
+    fn test_red_black_bst() {
+        test_ordered_symbol_table(&mut RedBlackBST::new());
+        test_borrowed_keys(&mut RedBlackBST::new());
+    }
+
+    #[test]
+    fn test_persistent_red_black_bst() {
+        test_ordered_symbol_table(&mut PersistentRedBlackBST::new());
+        test_borrowed_keys(&mut PersistentRedBlackBST::new());
+    }
+
+    #[test]
+    fn test_treap() {
+        test_ordered_symbol_table(&mut Treap::new());
+        test_borrowed_keys(&mut Treap::new());
+    }
+
+    #[test]
+    fn test_skip_list() {
+        test_ordered_symbol_table(&mut SkipList::new());
+        test_borrowed_keys(&mut SkipList::new());
+    }
+
+    #[test]
+    fn test_splay_tree() {
+        test_ordered_symbol_table(&mut SplayTree::new());
+        test_borrowed_keys(&mut SplayTree::new());
+    }
+
+    #[test]
+    fn test_ordered_arr_st() {
+        test_ordered_symbol_table(&mut OrderedArrST::<i32, i32, 64>::new());
+        test_borrowed_keys(&mut OrderedArrST::<String, usize, 8>::new());
+    }
+
+    #[test]
+    fn test_ordered_vector_st() {
+        test_ordered_symbol_table(&mut OrderedVectorST::new());
+        test_borrowed_keys(&mut OrderedVectorST::new());
+    }
+
+    /// Percentiles whose rank p * size / 100 is a whole number, which must not
+    /// be rounded up by floating point error.
+    #[test]
+    fn test_percentile_exact_ranks() {
+        let mut st = RedBlackBST::new();
+        for key in 1..=100 {
+            st.put(key, ()).unwrap();
+        }
+        for p in [7, 14, 28, 55, 56] {
+            assert_eq!(st.percentile(p as f64), Some(&p));
+        }
+
+        let mut st = RedBlackBST::new();
+        for key in 1..=50 {
+            st.put(key, ()).unwrap();
+        }
+        for (p, expected) in [(7, 4), (14, 7), (28, 14), (55, 28), (56, 28), (58, 29)] {
+            assert_eq!(st.percentile(p as f64), Some(&expected));
+        }
+    }
+
+    /// Round trips the table through JSON, checking that the entries are
+    /// serialized in sorted order (for ordered tables) and rebuilt on load.
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip<ST>(mut st: ST, is_ordered: bool)
+    where
+        ST: SymbolTable<i32, String> + serde::Serialize + serde::de::DeserializeOwned,
+        for<'a> &'a ST: IntoIterator<Item = (&'a i32, &'a String)>,
+    {
+        assert_eq!(serde_json::to_string(&st).unwrap(), "[]");
+
+        let mut keys: Vec<i32> = (0..50).collect();
+        shuffle_arr(&mut keys);
+        for &key in keys.iter() {
+            st.put(key, key.to_string()).unwrap();
+        }
+
+        let json = serde_json::to_string(&st).unwrap();
+        let mut entries: Vec<(i32, String)> = serde_json::from_str(&json).unwrap();
+        if is_ordered {
+            assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+        entries.sort();
+        assert_eq!(
+            entries,
+            (0..50).map(|k| (k, k.to_string())).collect::<Vec<_>>()
+        );
+
+        let loaded: ST = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.size(), 50);
+        for key in 0..50 {
+            assert_eq!(loaded.get(&key), Some(&key.to_string()));
+        }
+
+        // bincode needs the length of the sequence up front
+        let bytes = bincode::serialize(&st).unwrap();
+        let loaded: ST = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.size(), 50);
+        assert_eq!(loaded.get(&49), Some(&"49".to_string()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        test_serde_round_trip(AVLTree::new(), true);
+        test_serde_round_trip(BinarySearchTree::new(), true);
+        test_serde_round_trip(BTree::<_, _, 4>::new(), true);
+        test_serde_round_trip(OrderedArrST::<_, _, 64>::new(), true);
+        test_serde_round_trip(OrderedVectorST::new(), true);
+        test_serde_round_trip(PersistentRedBlackBST::new(), true);
+        test_serde_round_trip(RedBlackBST::new(), true);
+        test_serde_round_trip(SkipList::new(), true);
+        test_serde_round_trip(SplayTree::new(), true);
+        test_serde_round_trip(Treap::new(), true);
+        test_serde_round_trip(LinearProbingHashST::new(), false);
+        test_serde_round_trip(SeparateChainingHashST::new(), false);
+
+        // a fixed capacity table fails to load too many entries
+        let json = "[[1, 1], [2, 2], [3, 3]]";
+        assert!(serde_json::from_str::<OrderedArrST<i32, i32, 2>>(json).is_err());
+        // duplicate keys are put in order, so the last value wins
+        let st: RedBlackBST<i32, i32> = serde_json::from_str("[[1, 1], [1, 2]]").unwrap();
+        assert_eq!(st.get(&1), Some(&2));
+
+        let mut st = TST::new();
+        st.put("she", 0);
+        st.put("sells", 1);
+        let json = serde_json::to_string(&st).unwrap();
+        assert_eq!(json, r#"[["sells",1],["she",0]]"#);
+        let trie: TrieST<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(trie.keys().collect::<Vec<_>>(), ["sells", "she"]);
+        assert_eq!(trie.get("she"), Some(&0));
+
+        let set: OrderedSet<i32> = [3, 1, 2].into_iter().collect();
+        assert_eq!(serde_json::to_string(&set).unwrap(), "[1,2,3]");
+        let set: HashSet<i32> = serde_json::from_str("[3, 1, 2, 1]").unwrap();
+        assert_eq!(set.size(), 3);
+
+        let mut st = IntervalTree::new();
+        st.put(Interval::new(5, 8).unwrap(), "b");
+        st.put(Interval::new(1, 3).unwrap(), "a");
+        let json = serde_json::to_string(&st).unwrap();
+        assert_eq!(json, r#"[[[1,3],"a"],[[5,8],"b"]]"#);
+        let st: IntervalTree<i32, String> = serde_json::from_str(&json).unwrap();
+        assert!(st.any_containing(&6).is_some());
+        assert!(serde_json::from_str::<Interval<i32>>("[3, 1]").is_err());
+    }
 }
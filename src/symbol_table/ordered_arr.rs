@@ -1,9 +1,24 @@
+use std::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+};
+
 use crate::{
-    search::{binary_search, binary_search_insert_index},
+    search::binary_search_by,
     utils::{insert_and_shift, pop_and_shift},
 };
 
-use super::{KeyT, SymbolTable};
+use super::{
+    entry::{Entry, OccupiedEntry, VacantEntry, VacantInsert},
+    iter::{Keys, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
 
 /// A symbol table where keys and values are stored in fixed length arrays,
 /// sorted in the ordering of the keys.
@@ -23,6 +38,12 @@ pub struct OrderedArrST<K: KeyT, V, const CAPACITY: usize> {
     size: usize,
 }
 
+impl<K: KeyT, V, const CAPACITY: usize> Default for OrderedArrST<K, V, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K: KeyT, V, const CAPACITY: usize> OrderedArrST<K, V, CAPACITY> {
     const K_INIT: Option<K> = None;
     const V_INIT: Option<V> = None;
@@ -34,6 +55,99 @@ impl<K: KeyT, V, const CAPACITY: usize> OrderedArrST<K, V, CAPACITY> {
             size: 0,
         }
     }
+
+    /// Binary search for the key, returning Ok(index) if the key exists and
+    /// Err(index_of_insertion) otherwise (see binary_search_by).
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        binary_search_by(&self.keys[..self.size], |k| {
+            k.as_ref().unwrap().borrow().cmp(key)
+        })
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V, CAPACITY> {
+        Iter {
+            st: self,
+            i: 0,
+            end: self.size,
+        }
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            keys: self.keys[..self.size].iter(),
+            vals: self.vals[..self.size].iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V, CAPACITY>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V, CAPACITY>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// The entries in the range are contiguous in the array, so we only need
+    /// to find the index of the first entry in the range (the number of keys
+    /// that come before the range) and the number of entries in the range,
+    /// both of which are O(log n) via binary search.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V, CAPACITY>
+    where
+        K: Borrow<Q>,
+    {
+        let start = range.start_bound();
+        let i = self.size - self.range_count((start, Bound::Unbounded));
+        let end = i + self.range_count((start, range.end_bound()));
+        Iter { st: self, i, end }
+    }
+
+    /// Get the entry for the given key, for in-place updates (see Entry).
+    ///
+    /// ### Implementation
+    /// We binary search for the key. If it exists, the entry holds references
+    /// to the key and value at the index we found. Otherwise, inserting into
+    /// the vacant entry is the same as inserting with put (O(n) due to the
+    /// shifting).
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        match self.search(&key) {
+            Ok(i) => Entry::Occupied(OccupiedEntry::new(
+                self.keys[i].as_ref().unwrap(),
+                self.vals[i].as_mut().unwrap(),
+            )),
+            Err(_) => Entry::Vacant(VacantEntry::new(self, key)),
+        }
+    }
+
+    /// Insert a new entry at index i (which must be the index of insertion
+    /// for the key), shifting the entries after it to the right by one.
+    fn insert_at(&mut self, i: usize, key: K, val: V) -> Result<&mut V, String> {
+        if self.size >= CAPACITY {
+            return Err("capacity full".to_string());
+        }
+        self.keys[self.size] = insert_and_shift(&mut self.keys[..self.size], Some(key), i);
+        self.vals[self.size] = insert_and_shift(&mut self.vals[..self.size], Some(val), i);
+        self.size += 1;
+        Ok(self.vals[i].as_mut().unwrap())
+    }
+}
+
+impl<K: KeyT, V, const CAPACITY: usize> VacantInsert<K, V> for OrderedArrST<K, V, CAPACITY> {
+    fn insert_vacant(&mut self, key: K, val: V) -> Result<&mut V, String> {
+        let i = self.rank(&key);
+        self.insert_at(i, key, val)
+    }
 }
 
 impl<K: KeyT, V, const CAPACITY: usize> SymbolTable<K, V> for OrderedArrST<K, V, CAPACITY> {
@@ -50,32 +164,39 @@ impl<K: KeyT, V, const CAPACITY: usize> SymbolTable<K, V> for OrderedArrST<K, V,
     ///      to shift all the position to the right by one to make space for the
     ///      insertion, which is an O(n) operation.
     fn put(&mut self, key: K, val: V) -> Result<(), String> {
-        let key = Some(key);
-        let val = Some(val);
-        let i = binary_search_insert_index(&self.keys[..self.size], &key);
-        if self.keys[i] == key {
+        match self.search(&key) {
             // update the entry
-            self.vals[i] = val;
-        } else {
+            Ok(i) => self.vals[i] = Some(val),
             // insert a new entry
-            if self.size >= CAPACITY {
-                return Err("capacity full".to_string());
+            Err(i) => {
+                self.insert_at(i, key, val)?;
             }
-            self.keys[self.size] = insert_and_shift(&mut self.keys[..self.size], key, i);
-            self.vals[self.size] = insert_and_shift(&mut self.vals[..self.size], val, i);
-            self.size += 1;
         }
         Ok(())
     }
 
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: KeyT, V, const CAPACITY: usize, Q: ?Sized + Ord> Lookup<Q, V>
+    for OrderedArrST<K, V, CAPACITY>
+where
+    K: Borrow<Q>,
+{
     /// Get a reference to the value for the associated key (None if the key
     /// does not exist).
     ///
     /// ### Implementation
     /// We perform binary search on the array. If there is a hit, we return it,
     /// else the key does not exist and we return None.
-    fn get(&self, key: K) -> Option<&V> {
-        let i = binary_search(&self.keys[..self.size], &Some(key))?;
+    fn get(&self, key: &Q) -> Option<&V> {
+        let i = self.search(key).ok()?;
         self.vals[i].as_ref()
     }
 
@@ -90,46 +211,263 @@ impl<K: KeyT, V, const CAPACITY: usize> SymbolTable<K, V> for OrderedArrST<K, V,
     ///      to delete the entry from the array and shift the values to the left
     ///      by 1 to fill the vacant position. While finding the index is an
     ///      O(log n) operation, shifting the values is an O(n) operation.
-    fn pop(&mut self, key: K) -> Option<V> {
-        let i = binary_search(&self.keys[..self.size], &Some(key))?;
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let i = self.search(key).ok()?;
         self.size -= 1;
         pop_and_shift(&mut self.keys[..self.size + 1], None, i);
         pop_and_shift(&mut self.vals[..self.size + 1], None, i)
     }
+}
 
-    fn is_empty(&self) -> bool {
-        self.size == 0
+impl<K: KeyT, V, const CAPACITY: usize> OrderedSymbolTable<K, V> for OrderedArrST<K, V, CAPACITY> {
+    /// Get the smallest key (None if the table is empty).
+    ///
+    /// ### Implementation
+    /// The smallest key is the first entry in the array.
+    fn min(&self) -> Option<&K> {
+        self.select(0)
     }
 
-    fn size(&self) -> usize {
-        self.size
+    /// Get the largest key (None if the table is empty).
+    ///
+    /// ### Implementation
+    /// The largest key is the last entry in the array.
+    fn max(&self) -> Option<&K> {
+        self.select(self.size.checked_sub(1)?)
+    }
+
+    /// Get the largest key that is <= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// If the key exists, it is it's own floor. Else, the floor is the entry
+    /// right before the position at which the key would be inserted.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        match self.search(key) {
+            Ok(i) => self.select(i),
+            Err(i) => self.select(i.checked_sub(1)?),
+        }
+    }
+
+    /// Get the smallest key that is >= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// The ceil is the entry at the position at which the key would be
+    /// inserted (which is the position of the key itself if it exists).
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.select(self.rank(key))
+    }
+
+    /// Get the number of keys in the table that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// Since the keys are sorted and unique, the rank of a key is simply the
+    /// index at which it would be inserted, which binary search finds in
+    /// O(log n) time.
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key).unwrap_or_else(|i| i)
+    }
+
+    /// Get the key with the given rank (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// The key with rank i is at index i in the array.
+    fn select(&self, rank: usize) -> Option<&K> {
+        if rank >= self.size {
+            return None;
+        }
+        self.keys[rank].as_ref()
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// table is empty).
+    ///
+    /// ### Implementation
+    /// We remove the first entry and shift the rest of the array to the left
+    /// by 1, which is an O(n) operation.
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        if self.size == 0 {
+            return None;
+        }
+        let key = pop_and_shift(&mut self.keys[..self.size], None, 0)?;
+        let val = pop_and_shift(&mut self.vals[..self.size], None, 0)?;
+        self.size -= 1;
+        Some((key, val))
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// table is empty).
+    ///
+    /// ### Implementation
+    /// We remove the last entry in the array, which is an O(1) operation.
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        if self.size == 0 {
+            return None;
+        }
+        self.size -= 1;
+        let key = self.keys[self.size].take()?;
+        let val = self.vals[self.size].take()?;
+        Some((key, val))
     }
 }
 
-pub struct IntoIter<'a, K: KeyT, V, const CAPACITY: usize> {
+/// An iterator over the entries of an OrderedArrST in sorted order.
+///
+/// Since the entries are contiguous in the array, we simply keep track of the
+/// index of the next entry from the front (i) and one past the next entry from
+/// the back (end).
+pub struct Iter<'a, K: KeyT, V, const CAPACITY: usize> {
     st: &'a OrderedArrST<K, V, CAPACITY>,
     i: usize,
+    end: usize,
+}
+
+impl<'a, K: KeyT, V, const CAPACITY: usize> Iterator for Iter<'a, K, V, CAPACITY> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i >= self.end {
+            return None;
+        }
+        self.i += 1;
+        let key = self.st.keys[self.i - 1].as_ref()?;
+        let val = self.st.vals[self.i - 1].as_ref()?;
+        Some((key, val))
+    }
+}
+
+impl<K: KeyT, V, const CAPACITY: usize> DoubleEndedIterator for Iter<'_, K, V, CAPACITY> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.i >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        let key = self.st.keys[self.end].as_ref()?;
+        let val = self.st.vals[self.end].as_ref()?;
+        Some((key, val))
+    }
+}
+
+/// An iterator over the entries of an OrderedArrST in sorted order, with
+/// mutable references to the values.
+pub struct IterMut<'a, K: KeyT, V> {
+    keys: std::slice::Iter<'a, Option<K>>,
+    vals: std::slice::IterMut<'a, Option<V>>,
+}
+
+impl<'a, K: KeyT, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?.as_ref()?;
+        let val = self.vals.next()?.as_mut()?;
+        Some((key, val))
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next_back()?.as_ref()?;
+        let val = self.vals.next_back()?.as_mut()?;
+        Some((key, val))
+    }
+}
+
+/// An owning iterator over the entries of an OrderedArrST in sorted order.
+pub struct IntoIter<K: KeyT, V, const CAPACITY: usize> {
+    st: OrderedArrST<K, V, CAPACITY>,
+    i: usize,
+    end: usize,
 }
 
-impl<'a, K: KeyT, V, const CAPACITY: usize> Iterator for IntoIter<'a, K, V, CAPACITY> {
-    type Item = &'a K;
+impl<K: KeyT, V, const CAPACITY: usize> Iterator for IntoIter<K, V, CAPACITY> {
+    type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.i >= self.st.size() {
-            None
-        } else {
-            self.i += 1;
-            self.st.keys[self.i - 1].as_ref()
+        if self.i >= self.end {
+            return None;
         }
+        self.i += 1;
+        let key = self.st.keys[self.i - 1].take()?;
+        let val = self.st.vals[self.i - 1].take()?;
+        Some((key, val))
+    }
+}
+
+impl<K: KeyT, V, const CAPACITY: usize> DoubleEndedIterator for IntoIter<K, V, CAPACITY> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.i >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        let key = self.st.keys[self.end].take()?;
+        let val = self.st.vals[self.end].take()?;
+        Some((key, val))
     }
 }
 
 impl<'a, K: KeyT, V, const CAPACITY: usize> IntoIterator for &'a OrderedArrST<K, V, CAPACITY> {
-    type Item = &'a K;
-    type IntoIter = IntoIter<'a, K, V, CAPACITY>;
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, CAPACITY>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V, const CAPACITY: usize> IntoIterator for &'a mut OrderedArrST<K, V, CAPACITY> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V, const CAPACITY: usize> IntoIterator for OrderedArrST<K, V, CAPACITY> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, CAPACITY>;
 
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter { st: self, i: 0 }
+        let end = self.size;
+        IntoIter {
+            st: self,
+            i: 0,
+            end,
+        }
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize, const CAPACITY: usize> Serialize
+    for OrderedArrST<K, V, CAPACITY>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>, const CAPACITY: usize> Deserialize<'de>
+    for OrderedArrST<K, V, CAPACITY>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
     }
 }
 
@@ -140,7 +478,7 @@ mod tests {
     #[test]
     fn test_ordered_arr_st() {
         let mut st: OrderedArrST<&str, i32, 5> = OrderedArrST::new();
-        assert_eq!(st.is_empty(), true);
+        assert!(st.is_empty());
         assert_eq!(st.size(), 0);
 
         st.put("apple", 10).unwrap();
@@ -148,21 +486,21 @@ mod tests {
         st.put("cat", -3).unwrap();
         st.put("dog", 0).unwrap();
         assert_eq!(st.size(), 4);
-        assert_eq!(st.is_empty(), false);
+        assert!(!st.is_empty());
 
-        assert_eq!(st.get("apple"), Some(&10));
-        assert_eq!(st.get("banana"), Some(&5));
-        assert_eq!(st.get("cat"), Some(&-3));
-        assert_eq!(st.get("dog"), Some(&0));
-        assert_eq!(st.get("elephant"), None);
+        assert_eq!(st.get(&"apple"), Some(&10));
+        assert_eq!(st.get(&"banana"), Some(&5));
+        assert_eq!(st.get(&"cat"), Some(&-3));
+        assert_eq!(st.get(&"dog"), Some(&0));
+        assert_eq!(st.get(&"elephant"), None);
 
-        assert_eq!(st.pop("cat"), Some(-3));
-        assert_eq!(st.get("cat"), None);
-        assert_eq!(st.pop("cat"), None);
-        assert_eq!(st.get("dog"), Some(&0));
+        assert_eq!(st.pop(&"cat"), Some(-3));
+        assert_eq!(st.get(&"cat"), None);
+        assert_eq!(st.pop(&"cat"), None);
+        assert_eq!(st.get(&"dog"), Some(&0));
 
         st.put("apple", 20).unwrap();
-        assert_eq!(st.get("apple"), Some(&20));
+        assert_eq!(st.get(&"apple"), Some(&20));
         assert_eq!(st.size(), 3);
 
         assert!(st.put("a", 1).is_ok());
@@ -170,7 +508,7 @@ mod tests {
         assert!(st.put("a", 1).is_ok());
         assert!(st.put("c", 1).is_err());
 
-        let mut iter = (&st).into_iter();
+        let mut iter = st.keys();
         assert_eq!(iter.next(), Some(&"a"));
         assert_eq!(iter.next(), Some(&"apple"));
         assert_eq!(iter.next(), Some(&"b"));
@@ -178,4 +516,114 @@ mod tests {
         assert_eq!(iter.next(), Some(&"dog"));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_range() {
+        let mut st = OrderedArrST::<i32, i32, 32>::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let keys = |range: Iter<i32, i32, 32>| range.map(|(k, _)| *k).collect::<Vec<i32>>();
+        assert_eq!(keys(st.range(30..70)), [30, 40, 50, 60]);
+        assert_eq!(keys(st.range(30..=70)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(25..75)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(..30)), [10, 20]);
+        assert_eq!(keys(st.range(85..)), [90]);
+        assert_eq!(keys(st.range(..)), [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        assert_eq!(keys(st.range(91..)), [0; 0]);
+        assert_eq!(keys(st.range(50..50)), [0; 0]);
+
+        let mut range = st.range(40..=50);
+        assert_eq!(range.next(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = OrderedArrST::<i32, i32, 32>::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let mut iter = st.iter();
+        assert_eq!(iter.next(), Some((&10, &-10)));
+        assert_eq!(iter.next_back(), Some((&90, &-90)));
+        assert_eq!(iter.next(), Some((&20, &-20)));
+        assert_eq!(iter.next_back(), Some((&80, &-80)));
+        assert_eq!(iter.count(), 5);
+
+        let keys: Vec<i32> = st.keys().copied().collect();
+        assert_eq!(keys, [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        let keys: Vec<i32> = st.keys().rev().copied().collect();
+        assert_eq!(keys, [90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        let vals: Vec<i32> = st.values().copied().collect();
+        assert_eq!(vals, [-10, -20, -30, -40, -50, -60, -70, -80, -90]);
+
+        let mut range = st.range(30..=70).rev();
+        assert_eq!(range.next(), Some((&70, &-70)));
+        assert_eq!(range.next(), Some((&60, &-60)));
+        assert_eq!(range.next_back(), Some((&30, &-30)));
+        assert_eq!(range.next_back(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+
+        for (key, val) in st.iter_mut() {
+            *val = 2 * key;
+        }
+        for (key, val) in st.iter_mut().rev().take(2) {
+            *val += key;
+        }
+        for (_, val) in &mut st {
+            *val += 1;
+        }
+        let entries: Vec<(i32, i32)> = (&st).into_iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(
+            entries,
+            [
+                (10, 21),
+                (20, 41),
+                (30, 61),
+                (40, 81),
+                (50, 101),
+                (60, 121),
+                (70, 141),
+                (80, 241),
+                (90, 271)
+            ]
+        );
+
+        let mut into_iter = st.into_iter();
+        assert_eq!(into_iter.next(), Some((10, 21)));
+        assert_eq!(into_iter.next_back(), Some((90, 271)));
+        assert_eq!(
+            into_iter.rev().map(|(k, _)| k).collect::<Vec<i32>>(),
+            [80, 70, 60, 50, 40, 30, 20]
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut st = OrderedArrST::<&str, i32, 7>::new();
+        let text = "it was the best of times it was the worst of times";
+        for word in text.split(' ') {
+            *st.entry(word).or_insert(0).unwrap() += 1;
+        }
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get(&"times"), Some(&2));
+        assert_eq!(st.get(&"best"), Some(&1));
+
+        st.entry("the").and_modify(|count| *count *= 10);
+        assert_eq!(st.get(&"the"), Some(&20));
+
+        // updating a full table is fine, but inserting is not
+        assert_eq!(st.entry("was").or_insert(0), Ok(&mut 2));
+        assert_eq!(
+            st.entry("age").or_insert(0),
+            Err("capacity full".to_string())
+        );
+        assert_eq!(st.get(&"age"), None);
+    }
 }
@@ -0,0 +1,467 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{
+    list::{LinkedList, Vector},
+    search::binary_search_by,
+    sorting::heap_sort,
+};
+
+use super::{
+    entry::{Entry, OccupiedEntry, VacantEntry, VacantInsert},
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// A symbol table where the entries are stored in a growable array, sorted in
+/// the ordering of the keys.
+///
+/// Same as OrderedArrST (get is O(log n) via binary search, put and pop are
+/// O(n) due to shifting), except that the entries are stored in a Vector
+/// which grows as needed, so there is no capacity to pick up front and put
+/// never fails.
+///
+/// A table can also be built from unsorted entries in O(n log n) (see
+/// from_unsorted), which beats the O(n^2) of putting the entries one at a
+/// time.
+pub struct OrderedVectorST<K: KeyT, V> {
+    entries: Vector<(K, V)>,
+}
+
+impl<K: KeyT, V> Default for OrderedVectorST<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V> OrderedVectorST<K, V> {
+    pub fn new() -> Self {
+        OrderedVectorST {
+            entries: Vector::new(),
+        }
+    }
+
+    /// Build a table from the given (unsorted) entries. If a key appears more
+    /// than once, the last value for it wins (like calling put for each
+    /// entry).
+    ///
+    /// ### Implementation
+    /// We tag each entry with it's position in the input, sort the entries
+    /// by (key, position) with heap_sort in O(n log n), and then keep only
+    /// the last entry in each run of equal keys, which is a single O(n) pass.
+    /// Tagging the entries means we don't need a stable sort (or V: Ord) to
+    /// know which value came last.
+    pub fn from_unsorted<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        let mut tagged = Vector::new();
+        for (i, (key, val)) in entries.into_iter().enumerate() {
+            tagged.push(Tagged { key, i, val });
+        }
+        heap_sort(&mut tagged);
+
+        let mut st = Self::new();
+        let mut reversed = Vector::new();
+        while let Some(entry) = tagged.pop() {
+            // we go from the back, so the first entry we see for each key is
+            // the last one from the input
+            if reversed
+                .last()
+                .is_none_or(|last: &Tagged<K, V>| last.key != entry.key)
+            {
+                reversed.push(entry);
+            }
+        }
+        while let Some(Tagged { key, val, .. }) = reversed.pop() {
+            st.entries.push((key, val));
+        }
+        st
+    }
+
+    /// Binary search for the key, returning Ok(index) if the key exists and
+    /// Err(index_of_insertion) otherwise (see binary_search_by).
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        binary_search_by(&self.entries, |(k, _)| k.borrow().cmp(key))
+    }
+
+    /// The number of entries the table can hold before it needs to grow.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            entries: self.entries.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order. See OrderedArrST::range.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let start = range.start_bound();
+        let i = self.size() - self.range_count((start, Bound::Unbounded));
+        let end = i + self.range_count((start, range.end_bound()));
+        Iter {
+            entries: self.entries[i..end].iter(),
+        }
+    }
+
+    /// Get the entry for the given key, for in-place updates (see Entry and
+    /// OrderedArrST::entry).
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        match self.search(&key) {
+            Ok(i) => {
+                let (key, val) = &mut self.entries[i];
+                Entry::Occupied(OccupiedEntry::new(key, val))
+            }
+            Err(_) => Entry::Vacant(VacantEntry::new(self, key)),
+        }
+    }
+}
+
+/// An entry tagged with it's position in the input of from_unsorted, ordered
+/// by key and then by position.
+struct Tagged<K, V> {
+    key: K,
+    i: usize,
+    val: V,
+}
+
+impl<K: Ord, V> Ord for Tagged<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key).then(self.i.cmp(&other.i))
+    }
+}
+
+impl<K: Ord, V> PartialOrd for Tagged<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> PartialEq for Tagged<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, V> Eq for Tagged<K, V> {}
+
+impl<K: KeyT, V> VacantInsert<K, V> for OrderedVectorST<K, V> {
+    fn insert_vacant(&mut self, key: K, val: V) -> Result<&mut V, String> {
+        let i = self.rank(&key);
+        self.entries.insert(i, (key, val));
+        Ok(&mut self.entries[i].1)
+    }
+}
+
+impl<K: KeyT, V> SymbolTable<K, V> for OrderedVectorST<K, V> {
+    /// Add (or update) the key-value pair. See OrderedArrST::put, except the
+    /// underlying Vector grows (doubling it's capacity) when it's full, so
+    /// this never fails.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        match self.search(&key) {
+            Ok(i) => self.entries[i].1 = val,
+            Err(i) => self.entries.insert(i, (key, val)),
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn size(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for OrderedVectorST<K, V>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist). See OrderedArrST::get.
+    fn get(&self, key: &Q) -> Option<&V> {
+        let i = self.search(key).ok()?;
+        Some(&self.entries[i].1)
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist). See OrderedArrST::pop.
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let i = self.search(key).ok()?;
+        Some(self.entries.remove(i).1)
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for OrderedVectorST<K, V> {
+    fn min(&self) -> Option<&K> {
+        self.select(0)
+    }
+
+    fn max(&self) -> Option<&K> {
+        self.select(self.size().checked_sub(1)?)
+    }
+
+    /// See OrderedArrST::floor.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        match self.search(key) {
+            Ok(i) => self.select(i),
+            Err(i) => self.select(i.checked_sub(1)?),
+        }
+    }
+
+    /// See OrderedArrST::ceil.
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.select(self.rank(key))
+    }
+
+    /// See OrderedArrST::rank.
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.search(key).unwrap_or_else(|i| i)
+    }
+
+    fn select(&self, rank: usize) -> Option<&K> {
+        Some(&self.entries.get(rank)?.0)
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// table is empty). O(n) due to the shifting.
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.entries.remove(0))
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// table is empty). O(1).
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        self.entries.pop()
+    }
+}
+
+impl<K: KeyT, V> FromIterator<(K, V)> for OrderedVectorST<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_unsorted(iter)
+    }
+}
+
+/// An iterator over the entries of an OrderedVectorST in sorted order.
+pub struct Iter<'a, K, V> {
+    entries: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.entries.next()?;
+        Some((key, val))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.entries.next_back()?;
+        Some((key, val))
+    }
+}
+
+/// An iterator over the entries of an OrderedVectorST in sorted order, with
+/// mutable references to the values.
+pub struct IterMut<'a, K, V> {
+    entries: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.entries.next()?;
+        Some((key, val))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, val) = self.entries.next_back()?;
+        Some((key, val))
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a OrderedVectorST<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut OrderedVectorST<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for OrderedVectorST<K, V> {
+    type Item = (K, V);
+    type IntoIter = ListIter<(K, V)>;
+
+    /// ### Implementation
+    /// Vector can only move entries out from the back, so we pop all the
+    /// entries into a linked list (see ListIter), which is O(n).
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut list = LinkedList::new();
+        while let Some(entry) = self.entries.pop() {
+            list.push_front(entry);
+        }
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for OrderedVectorST<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de>
+    for OrderedVectorST<K, V>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_grows() {
+        let mut st = OrderedVectorST::new();
+        for key in (0..1000).rev() {
+            st.put(key, -key).unwrap();
+        }
+        assert_eq!(st.size(), 1000);
+        assert!(st.capacity() >= 1000);
+        assert_eq!(st.get(&500), Some(&-500));
+        assert_eq!(
+            st.keys().copied().collect::<Vec<_>>(),
+            (0..1000).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            st.range(10..13).map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+            [(10, -10), (11, -11), (12, -12)]
+        );
+
+        let text = "it was the best of times it was the worst of times";
+        let mut counts = OrderedVectorST::new();
+        for word in text.split(' ') {
+            *counts.entry(word).or_insert(0).unwrap() += 1;
+        }
+        assert_eq!(counts.size(), 7);
+        assert_eq!(counts.get(&"times"), Some(&2));
+        assert_eq!(counts.get(&"worst"), Some(&1));
+    }
+
+    #[test]
+    fn test_from_unsorted() {
+        let st = OrderedVectorST::from_unsorted([("c", 1), ("a", 2), ("b", 3), ("a", 4), ("c", 5)]);
+        assert_eq!(
+            st.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+            [("a", 4), ("b", 3), ("c", 5)]
+        );
+        assert!(OrderedVectorST::<i32, i32>::from_unsorted([]).is_empty());
+
+        // matches putting the entries one at a time
+        let mut rng = rand::thread_rng();
+        let entries: Vec<(i32, i32)> = (0..500).map(|i| (rng.gen_range(0..200), i)).collect();
+        let mut expected = OrderedVectorST::new();
+        for &(key, val) in &entries {
+            expected.put(key, val).unwrap();
+        }
+        let st: OrderedVectorST<i32, i32> = entries.into_iter().collect();
+        assert!(st.iter().eq(expected.iter()));
+        assert!(st.into_iter().rev().eq(expected.into_iter().rev()));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st: OrderedVectorST<i32, i32> =
+            [50, 20, 80, 10, 30].into_iter().map(|k| (k, -k)).collect();
+
+        let mut iter = st.iter();
+        assert_eq!(iter.next(), Some((&10, &-10)));
+        assert_eq!(iter.next_back(), Some((&80, &-80)));
+        assert_eq!(iter.count(), 3);
+
+        for (key, val) in &mut st {
+            *val = 2 * key;
+        }
+        assert_eq!(
+            st.values().rev().copied().collect::<Vec<_>>(),
+            [160, 100, 60, 40, 20]
+        );
+
+        let mut into_iter = st.into_iter();
+        assert_eq!(into_iter.next(), Some((10, 20)));
+        assert_eq!(into_iter.next_back(), Some((80, 160)));
+        assert_eq!(into_iter.count(), 3);
+    }
+}
@@ -0,0 +1,477 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Deref, RangeBounds},
+};
+
+use crate::stack::{Stack, StackLinkedList};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, Values},
+    red_black_bst::{self, size, RedBlackBST, Shared},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Tree<K, V> = RedBlackBST<K, V, (), Shared>;
+type Link<K, V> = red_black_bst::Link<K, V, (), Shared>;
+type Node<K, V> = red_black_bst::Node<K, V, (), Shared>;
+
+/// A persistent left leaning red black BST, where updates produce a new
+/// version of the tree while leaving the old versions intact.
+///
+/// The nodes are reference counted (Rc) and shared between versions. Each
+/// update only copies the O(log n) nodes on the search path that it changes
+/// (called path copying), and the new nodes point to the same unchanged
+/// subtrees as the old ones. So insert and remove are O(log n) time and space,
+/// and cloning a version is O(1).
+///
+/// Each version is just a RedBlackBST with Shared (Rc) links, so the
+/// balancing code is RedBlackBST's (see it's docs for details). It only ever
+/// modifies a node through Pointer::make_mut, which for a Shared node is
+/// Rc::make_mut. That clones the node if it is shared with another version,
+/// and otherwise lets us modify it in place, so we get path copying for free,
+/// and a version that isn't shared (e.g. one updated with put or pop) is
+/// updated in place without any copying at all.
+///
+/// Since nodes may need to be copied, the keys and values must be Clone.
+///
+/// See: https://en.wikipedia.org/wiki/Persistent_data_structure
+pub struct PersistentRedBlackBST<K: KeyT + Clone, V: Clone> {
+    tree: Tree<K, V>,
+}
+
+impl<K: KeyT + Clone, V: Clone> Clone for PersistentRedBlackBST<K, V> {
+    /// Get a copy of this version of the tree in O(1) time (the copy shares
+    /// all it's nodes with this version).
+    fn clone(&self) -> Self {
+        PersistentRedBlackBST {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone> Default for PersistentRedBlackBST<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone> PersistentRedBlackBST<K, V> {
+    pub fn new() -> Self {
+        PersistentRedBlackBST {
+            tree: Tree::empty(),
+        }
+    }
+
+    /// Get a new version of the tree with the key-value pair added (or
+    /// updated), leaving this version unchanged.
+    pub fn insert(&self, key: K, val: V) -> Self {
+        let mut tree = self.tree.clone();
+        tree.insert_entry(key, val);
+        PersistentRedBlackBST { tree }
+    }
+
+    /// Get a new version of the tree with the key removed, leaving this
+    /// version unchanged.
+    pub fn remove<Q: ?Sized + Ord>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+    {
+        let mut tree = self.tree.clone();
+        tree.delete_entry(key);
+        PersistentRedBlackBST { tree }
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order (see RedBlackBST::range).
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = self.tree.root();
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = self.tree.root();
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone> SymbolTable<K, V> for PersistentRedBlackBST<K, V> {
+    /// Add (or update) the key-value pair, replacing this version of the tree.
+    /// Nodes that are not shared with other versions are updated in place.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        self.tree.insert_entry(key, val);
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tree.root().is_none()
+    }
+
+    fn size(&self) -> usize {
+        size(self.tree.root())
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone, Q: ?Sized + Ord> Lookup<Q, V> for PersistentRedBlackBST<K, V>
+where
+    K: Borrow<Q>,
+{
+    fn get(&self, key: &Q) -> Option<&V> {
+        Some(&Tree::search(self.tree.root(), key)?.val)
+    }
+
+    /// Remove the entry that matches the key, replacing this version of the
+    /// tree, and return (a copy of) it's value (return None if the key does
+    /// not exist).
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        self.tree.delete_entry(key)
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone> OrderedSymbolTable<K, V> for PersistentRedBlackBST<K, V> {
+    fn min(&self) -> Option<&K> {
+        let mut node = self.tree.root().as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    fn max(&self) -> Option<&K> {
+        let mut node = self.tree.root().as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Clone + Borrow<Q>, Q: ?Sized + Ord, V: Clone>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, key),
+                Ordering::Greater => visit(&node.right, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(self.tree.root(), key)
+    }
+
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Clone + Borrow<Q>, Q: ?Sized + Ord, V: Clone>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Greater => visit(&node.right, key),
+                Ordering::Less => visit(&node.left, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(self.tree.root(), key)
+    }
+
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<K: KeyT + Clone + Borrow<Q>, Q: ?Sized + Ord, V: Clone>(
+            link: &Link<K, V>,
+            key: &Q,
+        ) -> usize {
+            match link {
+                None => 0,
+                Some(node) => match key.cmp(node.key.borrow()) {
+                    Ordering::Equal => size(&node.left),
+                    Ordering::Less => visit(&node.left, key),
+                    Ordering::Greater => 1 + size(&node.left) + visit(&node.right, key),
+                },
+            }
+        }
+
+        visit(self.tree.root(), key)
+    }
+
+    fn select(&self, rank: usize) -> Option<&K> {
+        fn visit<K: KeyT + Clone, V: Clone>(link: &Link<K, V>, rank: usize) -> Option<&K> {
+            let node = link.as_ref()?;
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, rank),
+                Ordering::Greater => visit(&node.right, rank - left_size - 1),
+            }
+        }
+
+        visit(self.tree.root(), rank)
+    }
+
+    /// Remove the entry with the smallest key, replacing this version of the
+    /// tree, and return (a copy of) it (None if the tree is empty).
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        self.tree.delete_min_entry()
+    }
+
+    /// Remove the entry with the largest key, replacing this version of the
+    /// tree, and return (a copy of) it (None if the tree is empty).
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        self.tree.delete_max_entry()
+    }
+}
+
+/// An iterator over the entries of a PersistentRedBlackBST in sorted order
+/// (see RedBlackBST's Iter for details).
+pub struct Iter<'a, K: KeyT + Clone, V: Clone> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT + Clone, V: Clone> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<K: KeyT + Clone, V: Clone> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<'a, K: KeyT + Clone, V: Clone> IntoIterator for &'a PersistentRedBlackBST<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Clone + Serialize, V: Clone + Serialize> Serialize for PersistentRedBlackBST<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Clone + Deserialize<'de>, V: Clone + Deserialize<'de>> Deserialize<'de>
+    for PersistentRedBlackBST<K, V>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, rc::Rc};
+
+    use crate::{random::shuffle_arr, symbol_table::tests::test_random_ops};
+
+    use super::*;
+
+    /// Checks that the tree is a valid left leaning red black BST (see
+    /// RedBlackBST::check).
+    fn check_invariants<K: KeyT + Clone, V: Clone>(st: &PersistentRedBlackBST<K, V>) {
+        st.tree.check().unwrap();
+    }
+
+    /// The addresses of all the nodes in the tree.
+    fn nodes<K: KeyT + Clone, V: Clone>(
+        st: &PersistentRedBlackBST<K, V>,
+    ) -> HashSet<*const Node<K, V>> {
+        fn visit<K: KeyT + Clone, V: Clone>(
+            link: &Link<K, V>,
+            nodes: &mut HashSet<*const Node<K, V>>,
+        ) {
+            if let Some(node) = link {
+                nodes.insert(Rc::as_ptr(node));
+                visit(&node.left, nodes);
+                visit(&node.right, nodes);
+            }
+        }
+
+        let mut nodes = HashSet::new();
+        visit(st.tree.root(), &mut nodes);
+        nodes
+    }
+
+    #[test]
+    fn test_versions() {
+        let mut versions = vec![PersistentRedBlackBST::new()];
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            let next = versions.last().unwrap().insert(key, i);
+            check_invariants(&next);
+            versions.push(next);
+        }
+
+        // every old version is unchanged
+        for (i, version) in versions.iter().enumerate() {
+            let mut expected: Vec<char> = "SEARCHEXAMPLE".chars().take(i).collect();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(version.keys().copied().collect::<Vec<_>>(), expected);
+            check_invariants(version);
+        }
+        let latest = versions.last().unwrap();
+        assert_eq!(latest.get(&'E'), Some(&12));
+        assert_eq!(versions[2].get(&'E'), Some(&1));
+
+        let removed = latest.remove(&'E').remove(&'S').remove(&'B');
+        check_invariants(&removed);
+        assert_eq!(removed.keys().collect::<String>(), "ACHLMPRX");
+        assert_eq!(latest.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(latest.get(&'E'), Some(&12));
+    }
+
+    #[test]
+    fn test_structural_sharing() {
+        let mut keys: Vec<i32> = (0..1000).collect();
+        shuffle_arr(&mut keys);
+        let mut st = PersistentRedBlackBST::new();
+        for &key in keys.iter() {
+            st.put(key, key).unwrap();
+        }
+
+        // a new version only copies the nodes along the search path (plus
+        // a few of their children for color flips)
+        let old_nodes = nodes(&st);
+        for new in [
+            st.insert(500, 0),
+            st.insert(2000, 0),
+            st.remove(&500),
+            st.remove(&0),
+        ] {
+            check_invariants(&new);
+            let copied = nodes(&new).difference(&old_nodes).count();
+            assert!(copied <= 3 * 2 * 10, "copied {} nodes", copied);
+        }
+        check_invariants(&st);
+        assert_eq!(nodes(&st), old_nodes);
+        assert_eq!(st.get(&500), Some(&500));
+
+        // a version that isn't shared is updated in place
+        let mut st = PersistentRedBlackBST::new();
+        for i in 0..100 {
+            st.put(i, i).unwrap();
+        }
+        let old_nodes = nodes(&st);
+        st.put(50, -50).unwrap();
+        assert_eq!(nodes(&st), old_nodes);
+        assert_eq!(st.get(&50), Some(&-50));
+    }
+
+    #[test]
+    fn test_against_btree_map() {
+        // snapshot every so often, and check the later operations on the
+        // live version leave them unaffected
+        let mut snapshots = Vec::new();
+        let mut ops = 0;
+        let mut st = PersistentRedBlackBST::new();
+        test_random_ops(&mut st, |st| {
+            check_invariants(st);
+            ops += 1;
+            if ops % 100 == 0 {
+                let entries: Vec<_> = st.iter().map(|(&k, &v)| (k, v)).collect();
+                snapshots.push((st.clone(), entries));
+            }
+        });
+        for (snapshot, entries) in snapshots.iter() {
+            check_invariants(snapshot);
+            assert!(snapshot
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .eq(entries.iter().copied()));
+        }
+    }
+}
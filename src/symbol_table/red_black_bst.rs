@@ -1,17 +1,167 @@
-use std::{cmp::Ordering, fmt::Display, ops::Deref};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Deref, DerefMut, RangeBounds},
+    rc::Rc,
+};
 
-use crate::stack::{Stack, StackLinkedList};
+use crate::{
+    list::{LinkedList, Vector},
+    stack::{Stack, StackLinkedList},
+    utils::dot_label,
+};
 
-use super::{KeyT, SymbolTable};
+use super::{
+    entry::{Entry, OccupiedEntry, VacantEntry, VacantInsert},
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
 
-type Link<K, V> = Option<Box<Node<K, V>>>;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-pub struct Node<K: KeyT, V> {
-    key: K,
-    val: V,
-    left: Link<K, V>,
-    right: Link<K, V>,
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+pub(super) type Link<K, V, S = (), P = Boxed> = Option<<P as Pointer<K, V, S>>::Ptr>;
+
+/// The new subtree after a delete, and the deleted node.
+type Deleted<K, V, S, P> = (Link<K, V, S, P>, <P as Pointer<K, V, S>>::Ptr);
+
+/// An augmentation of the nodes of a RedBlackBST, with a summary of the keys
+/// in their subtree (e.g. the max endpoint of the intervals in IntervalTree).
+///
+/// Like the subtree size, the summary of a node must only depend on it's own
+/// key and the summaries of it's children, so that it can be recomputed from
+/// the bottom up whenever the children of a node change (in balance, and for
+/// the nodes that move in a rotation).
+pub trait Summary<K> {
+    /// The summary of a subtree, given the key at it's root and the summaries
+    /// of it's children (None for a null link).
+    fn summarize(key: &K, left: Option<&Self>, right: Option<&Self>) -> Self;
+}
+
+/// No augmentation.
+impl<K> Summary<K> for () {
+    fn summarize(_: &K, _: Option<&()>, _: Option<&()>) {}
+}
+
+/// The kind of pointer that links the nodes of a RedBlackBST, so that the
+/// balancing code can be shared between trees that own their nodes (Boxed)
+/// and trees whose nodes are shared between versions (Shared, see
+/// PersistentRedBlackBST).
+///
+/// The balancing code only ever modifies a node through make_mut, which for a
+/// shared node first makes a copy (so the other versions are unaffected).
+pub trait Pointer<K: KeyT, V, S>: Sized {
+    type Ptr: Deref<Target = Node<K, V, S, Self>>;
+
+    fn new(node: Node<K, V, S, Self>) -> Self::Ptr;
+
+    /// Get a mutable reference to the node, copying it first if it's shared.
+    fn make_mut(ptr: &mut Self::Ptr) -> &mut Node<K, V, S, Self>;
+
+    /// Move the node out of the pointer, copying it if it's shared.
+    fn into_inner(ptr: Self::Ptr) -> Node<K, V, S, Self>;
+}
+
+/// Nodes owned by their parent (the default).
+pub struct Boxed;
+
+impl<K: KeyT, V, S> Pointer<K, V, S> for Boxed {
+    type Ptr = Box<Node<K, V, S, Self>>;
+
+    fn new(node: Node<K, V, S, Self>) -> Self::Ptr {
+        Box::new(node)
+    }
+
+    fn make_mut(ptr: &mut Self::Ptr) -> &mut Node<K, V, S, Self> {
+        ptr
+    }
+
+    fn into_inner(ptr: Self::Ptr) -> Node<K, V, S, Self> {
+        *ptr
+    }
+}
+
+/// Reference counted nodes, which may be shared between versions of a tree.
+/// Since a shared node may need to be copied, the keys and values must be
+/// Clone.
+pub struct Shared;
+
+impl<K: KeyT + Clone, V: Clone, S: Clone> Pointer<K, V, S> for Shared {
+    type Ptr = Rc<Node<K, V, S, Self>>;
+
+    fn new(node: Node<K, V, S, Self>) -> Self::Ptr {
+        Rc::new(node)
+    }
+
+    fn make_mut(ptr: &mut Self::Ptr) -> &mut Node<K, V, S, Self> {
+        Rc::make_mut(ptr)
+    }
+
+    fn into_inner(ptr: Self::Ptr) -> Node<K, V, S, Self> {
+        Rc::unwrap_or_clone(ptr)
+    }
+}
+
+pub struct Node<K: KeyT, V, S = (), P: Pointer<K, V, S> = Boxed> {
+    pub(super) key: K,
+    pub(super) val: V,
+    pub(super) left: Link<K, V, S, P>,
+    pub(super) right: Link<K, V, S, P>,
     is_red: bool,
+    size: usize,
+    pub(super) summary: S,
+}
+
+impl<K: KeyT + Clone, V: Clone, S: Clone> Clone for Node<K, V, S, Shared> {
+    /// Copy the node, sharing it's children with the original.
+    fn clone(&self) -> Self {
+        Node {
+            key: self.key.clone(),
+            val: self.val.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            is_red: self.is_red,
+            size: self.size,
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+impl<K: KeyT, V, S: Summary<K>, P: Pointer<K, V, S>> Node<K, V, S, P> {
+    fn new(key: K, val: V) -> Self {
+        Node {
+            summary: S::summarize(&key, None, None),
+            key,
+            val,
+            left: None,
+            right: None,
+            is_red: true, // new node must be a red link
+            size: 1,
+        }
+    }
+
+    /// Recompute the size and summary of the subtree rooted at this node from
+    /// those of it's children.
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+        self.summary = S::summarize(
+            &self.key,
+            self.left.as_ref().map(|left| &left.summary),
+            self.right.as_ref().map(|right| &right.summary),
+        );
+    }
+}
+
+/// Number of nodes in the subtree (0 for a null link).
+pub(super) fn size<K: KeyT, V, S, P: Pointer<K, V, S>>(
+    link: &Option<impl Deref<Target = Node<K, V, S, P>>>,
+) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
 }
 
 /// A red black binary search tree.
@@ -19,22 +169,328 @@ pub struct Node<K: KeyT, V> {
 /// A red black BST is an implementation of a 2-3 such that the underlying
 /// data structure is just a BST, with the insert and delete functions
 /// doing the heavy lifting to maintain balance.
-pub struct RedBlackBST<K: KeyT, V> {
-    root: Link<K, V>,
-    size: usize,
+///
+/// Each node also stores the number of nodes in it's subtree, which lets us
+/// implement the ordered operations rank and select in O(log n) time. Within
+/// the crate, the nodes can be further augmented with a Summary of their
+/// subtree, which the balancing code keeps up to date along with the size
+/// (see IntervalTree), and the nodes can be shared between versions of the
+/// tree (see Pointer and PersistentRedBlackBST).
+///
+/// See: https://algs4.cs.princeton.edu/33balanced/
+pub struct RedBlackBST<K: KeyT, V, S = (), P: Pointer<K, V, S> = Boxed> {
+    root: Link<K, V, S, P>,
+}
+
+impl<K: KeyT + Clone, V: Clone, S: Clone> Clone for RedBlackBST<K, V, S, Shared> {
+    /// Copy the tree in O(1) time, sharing all it's nodes with the original.
+    fn clone(&self) -> Self {
+        RedBlackBST {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: KeyT, V> Default for RedBlackBST<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<K: KeyT, V> RedBlackBST<K, V> {
     pub fn new() -> Self {
-        RedBlackBST {
-            root: None,
-            size: 0,
+        RedBlackBST { root: None }
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    ///
+    /// ### Implementation
+    /// We collect the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n) time and space.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        fn visit<'a, K: KeyT, V>(
+            link: &'a mut Link<K, V>,
+            list: &mut LinkedList<(&'a K, &'a mut V)>,
+        ) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = node.deref_mut();
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
         }
+
+        let mut list = LinkedList::new();
+        visit(&mut self.root, &mut list);
+        ListIter::new(list)
     }
 
-    /// Given a node n, with the right link is a red link and the left is a
-    /// black, we want to rotate the structure such that the red link is on the
-    /// left as such:
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// We walk down from the root towards the start of the range, pushing the
+    /// nodes that are in the range onto the front stack of the iterator (see
+    /// Iter), and likewise towards the end of the range for the back stack.
+    /// From there we iterate in-order, stopping once we've yielded
+    /// range_count entries. This way, we only visit O(height + number of
+    /// entries in the range) nodes.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+
+    /// Get the entry for the given key, for in-place updates (see Entry).
+    ///
+    /// ### Implementation
+    /// We search the tree for the key once. If it exists, the entry holds
+    /// references to the key and value of the matching node, so updating it
+    /// requires no further traversal. Otherwise, inserting into the vacant
+    /// entry is a single put down the same path (see VacantInsert). So an
+    /// occupied entry costs one walk down the tree and a vacant one two, each
+    /// O(log n).
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, Self> {
+        // the borrow checker won't let us reuse self after a search_mut that
+        // only returns it's borrow on a hit (even though on a miss nothing is
+        // borrowed), so we search through a raw pointer to the root instead.
+        // This is sound since the two borrows are on disjoint branches.
+        let root: *mut Link<K, V> = &mut self.root;
+        match Self::search_mut(unsafe { &mut *root }, &key) {
+            Some((key, val)) => Entry::Occupied(OccupiedEntry::new(key, val)),
+            None => Entry::Vacant(VacantEntry::new(self, key)),
+        }
+    }
+
+    /// Height of the tree, that is the number of nodes on the longest path
+    /// from the root to a leaf (0 if the tree is empty). Unlike AVLTree, the
+    /// nodes don't store their height, so this is O(n).
+    pub fn height(&self) -> usize {
+        self.depth_counts().len()
+    }
+
+    /// The average depth of the nodes in the tree, where the root has depth 0
+    /// (None if the tree is empty). A search hit for a random key compares
+    /// against average depth + 1 nodes. O(n).
+    pub fn average_depth(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let total: usize = self
+            .depth_counts()
+            .iter()
+            .enumerate()
+            .map(|(depth, count)| depth * count)
+            .sum();
+        Some(total as f64 / self.size() as f64)
+    }
+
+    /// The number of nodes at each depth of the tree, where the root has
+    /// depth 0 (so the length is the height of the tree). O(n).
+    pub fn depth_counts(&self) -> Vector<usize> {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, depth: usize, counts: &mut Vector<usize>) {
+            if let Some(node) = link {
+                if depth == counts.len() {
+                    counts.push(0);
+                }
+                counts[depth] += 1;
+                visit(&node.left, depth + 1, counts);
+                visit(&node.right, depth + 1, counts);
+            }
+        }
+
+        let mut counts = Vector::new();
+        visit(&self.root, 0, &mut counts);
+        counts
+    }
+}
+
+impl<K: KeyT, V, S: Summary<K>, P: Pointer<K, V, S>> RedBlackBST<K, V, S, P> {
+    /// Create an empty tree, whose nodes are augmented with S (see Summary).
+    pub(super) fn empty() -> Self {
+        RedBlackBST { root: None }
+    }
+
+    pub(super) fn root(&self) -> &Link<K, V, S, P> {
+        &self.root
+    }
+
+    /// Add (or update) the key-value pair (see SymbolTable::put).
+    pub(super) fn insert_entry(&mut self, key: K, val: V) {
+        self.insert_node(P::new(Node::new(key, val)));
+    }
+
+    /// Add (or update) the entry of the given node, rebalancing from the root.
+    fn insert_node(&mut self, node: P::Ptr) {
+        let mut root = Self::insert(self.root.take(), node);
+
+        // root is kept black since it is technically not part of a 3-node
+        P::make_mut(&mut root).is_red = false;
+        self.root = Some(root);
+    }
+
+    /// Remove the entry that matches the key, and return it's value (see
+    /// SymbolTable::pop).
+    pub(super) fn delete_entry<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        Self::search(&self.root, key)?;
+
+        let root = self.take_root_for_delete()?;
+        let deleted;
+        (self.root, deleted) = Self::delete(root, key);
+        self.finish_delete();
+        Some(P::into_inner(deleted).val)
+    }
+
+    /// Remove the entry with the smallest key and return it (see
+    /// OrderedSymbolTable::pop_min).
+    pub(super) fn delete_min_entry(&mut self) -> Option<(K, V)> {
+        let root = self.take_root_for_delete()?;
+        let deleted;
+        (self.root, deleted) = Self::delete_min(root);
+        self.finish_delete();
+        let deleted = P::into_inner(deleted);
+        Some((deleted.key, deleted.val))
+    }
+
+    /// Remove the entry with the largest key and return it (see
+    /// OrderedSymbolTable::pop_max).
+    pub(super) fn delete_max_entry(&mut self) -> Option<(K, V)> {
+        let root = self.take_root_for_delete()?;
+        let deleted;
+        (self.root, deleted) = Self::delete_max(root);
+        self.finish_delete();
+        let deleted = P::into_inner(deleted);
+        Some((deleted.key, deleted.val))
+    }
+
+    /// Check that the tree is a valid left leaning red black BST, returning
+    /// an Err describing the first violation found. That is:
+    ///
+    ///   1) keys are in symmetric order
+    ///   2) the subtree sizes stored in each node are consistent
+    ///   3) the tree corresponds to a 2-3 tree, so the root is black, there
+    ///      are no right leaning red links, and there are no two red links in
+    ///      a row
+    ///   4) every path from the root to a null link has the same number of
+    ///      black links (perfect black balance)
+    ///
+    /// This is O(n), so it's meant for tests and debugging.
+    pub fn check(&self) -> Result<(), String> {
+        /// Returns the black height of the subtree.
+        fn visit<K: KeyT, V, S: Summary<K>, P: Pointer<K, V, S>>(
+            node: Option<&Node<K, V, S, P>>,
+            lo: Option<&K>,
+            hi: Option<&K>,
+            parent_is_red: bool,
+        ) -> Result<usize, String> {
+            let Some(node) = node else {
+                return Ok(0);
+            };
+            if lo.is_some_and(|lo| &node.key <= lo) || hi.is_some_and(|hi| &node.key >= hi) {
+                return Err("keys out of order".to_string());
+            }
+            if node.size != 1 + size(&node.left) + size(&node.right) {
+                return Err("inconsistent subtree size".to_string());
+            }
+            if RedBlackBST::<K, V, S, P>::is_red(&node.right) {
+                return Err("right leaning red link".to_string());
+            }
+            if parent_is_red && node.is_red {
+                return Err("two red links in a row".to_string());
+            }
+
+            let left_height = visit(node.left.as_deref(), lo, Some(&node.key), node.is_red)?;
+            let right_height = visit(node.right.as_deref(), Some(&node.key), hi, node.is_red)?;
+            if left_height != right_height {
+                return Err("not black balanced".to_string());
+            }
+            Ok(left_height + if node.is_red { 0 } else { 1 })
+        }
+
+        if Self::is_red(&self.root) {
+            return Err("root is red".to_string());
+        }
+        visit(self.root.as_deref(), None, None, false)?;
+        Ok(())
+    }
+
+    /// Takes the root out of the tree in preparation for a delete. If both
+    /// children of the root are black, we set the root to red so that
+    /// move_red_left/move_red_right have a red link to push down the tree.
+    fn take_root_for_delete(&mut self) -> Link<K, V, S, P> {
+        let mut root = self.root.take()?;
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            P::make_mut(&mut root).is_red = true;
+        }
+        Some(root)
+    }
+
+    /// Restores the root to black (see put) after a delete.
+    fn finish_delete(&mut self) {
+        if let Some(root) = self.root.as_mut() {
+            P::make_mut(root).is_red = false;
+        }
+    }
+
+    fn is_red(link: &Link<K, V, S, P>) -> bool {
+        link.as_ref().is_some_and(|n| n.is_red)
+    }
+
+    /// Given a node n, with the right link is a red link, we want to rotate the
+    /// structure such that the red link is on the left as such:
     ///
     /// ```text
     ///        n
@@ -52,20 +508,24 @@ impl<K: KeyT, V> RedBlackBST<K, V> {
     ///
     /// Notice, we return r so that the link for the node pointing to n can be
     /// updated to r.
-    fn rotate_left(mut n: Node<K, V>) -> Link<K, V> {
-        let mut r = n.right.take().unwrap();
+    fn rotate_left(mut n: P::Ptr) -> P::Ptr {
+        let n_mut = P::make_mut(&mut n);
+        let mut r = n_mut.right.take().unwrap();
+        let r_mut = P::make_mut(&mut r);
 
-        r.is_red = n.is_red;
-        n.is_red = true;
+        r_mut.is_red = n_mut.is_red;
+        n_mut.is_red = true;
 
-        n.right = r.left.take();
-        r.left = Some(Box::new(n));
+        n_mut.right = r_mut.left.take();
+        n_mut.update();
+        r_mut.left = Some(n);
+        r_mut.update();
 
-        Some(r)
+        r
     }
 
-    /// Given a node n, with two consecutive left links that are red, we need to
-    /// rotate the structure to the right:
+    /// Given a node n, with a left link that is red, we rotate the structure
+    /// to the right (the mirror image of rotate_left):
     ///
     /// ```text
     ///          |
@@ -83,30 +543,27 @@ impl<K: KeyT, V> RedBlackBST<K, V> {
     ///          r
     /// ```
     ///
-    /// Then, we run the flip colors function giving us a final result of:
-    ///
-    /// ```text
-    ///          ||
-    ///          m
-    ///        /   \
-    ///       l     n
-    ///            /
-    ///           r
-    /// ```
-    ///
-    /// The incoming link is guaranteed to be black (so when we convert to red
-    /// we aren't converting something that is already red) because rotations
-    /// are run bottom up, so anything above us must be a valid red-black BST
-    /// and a valid red-black BST cannot have two red links in a row.
+    /// During insertion, this is used to fix two consecutive left red links,
+    /// after which m has two red links and we run flip_colors on it. During
+    /// deletion, this is used to lean a red link to the right so that we can
+    /// carry it down the right side of the tree.
     ///
     /// Finally, we return m so that the link for the node pointing to n can be
-    /// update to r.
-    fn rotate_right(mut n: Node<K, V>) -> Link<K, V> {
-        let mut m = n.left.take().unwrap();
-        n.left = m.right.take();
-        n.is_red = true;
-        m.right = Some(Box::new(n));
-        Self::flip_colors(*m)
+    /// update to m.
+    fn rotate_right(mut n: P::Ptr) -> P::Ptr {
+        let n_mut = P::make_mut(&mut n);
+        let mut m = n_mut.left.take().unwrap();
+        let m_mut = P::make_mut(&mut m);
+
+        m_mut.is_red = n_mut.is_red;
+        n_mut.is_red = true;
+
+        n_mut.left = m_mut.right.take();
+        n_mut.update();
+        m_mut.right = Some(n);
+        m_mut.update();
+
+        m
     }
 
     /// Given a node n, where both it's left and right link are red, we simply
@@ -124,60 +581,206 @@ impl<K: KeyT, V> RedBlackBST<K, V> {
     ///    l     r
     /// ```
     ///
-    fn flip_colors(mut n: Node<K, V>) -> Link<K, V> {
-        n.left.as_mut().unwrap().is_red = false;
-        n.right.as_mut().unwrap().is_red = false;
-        n.is_red = true;
-        Some(Box::new(n))
+    /// The colors are toggled rather than set, so the same operation also
+    /// works in reverse. During deletion, a node n with a red incoming link
+    /// and two black links is flipped to combine n and it's children into a
+    /// temporary 4-node.
+    fn flip_colors(n: &mut P::Ptr) {
+        let n = P::make_mut(n);
+        n.is_red = !n.is_red;
+        if let Some(left) = n.left.as_mut() {
+            let left = P::make_mut(left);
+            left.is_red = !left.is_red;
+        }
+        if let Some(right) = n.right.as_mut() {
+            let right = P::make_mut(right);
+            right.is_red = !right.is_red;
+        }
+    }
+
+    /// Restores the red black tree invariants on the way back up the tree
+    /// after an insertion or deletion. The invariants may have been violated
+    /// in 3 possible ways:
+    ///
+    ///   1) there is a right red link (in which case we rotate left)
+    ///   2) there are two left red links in a row (in which case we rotate right)
+    ///   3) there is a left and a right red link (in which case we flip the colors)
+    ///
+    /// The checks are run in order, since fixing one case can produce the next.
+    /// Since the children of n may have changed, we also update it's size (and
+    /// summary).
+    fn balance(mut n: P::Ptr) -> P::Ptr {
+        if Self::is_red(&n.right) && !Self::is_red(&n.left) {
+            n = Self::rotate_left(n);
+        }
+        if Self::is_red(&n.left) && Self::is_red(&n.left.as_ref().unwrap().left) {
+            n = Self::rotate_right(n);
+        }
+        if Self::is_red(&n.left) && Self::is_red(&n.right) {
+            Self::flip_colors(&mut n);
+        }
+        P::make_mut(&mut n).update();
+        n
     }
 
-    fn insert(link: &mut Link<K, V>, node_to_insert: Node<K, V>) -> Link<K, V> {
-        match link.take() {
+    /// Assuming n is red and both n.left and n.left.left are black, make
+    /// n.left or one of it's children red, so that we never delete from a
+    /// 2-node when descending to the left. If n.right.left is red, we borrow a
+    /// key from our right sibling, otherwise we merge with it into a 4-node.
+    fn move_red_left(mut n: P::Ptr) -> P::Ptr {
+        Self::flip_colors(&mut n);
+        if Self::is_red(&n.right.as_ref().unwrap().left) {
+            let n_mut = P::make_mut(&mut n);
+            n_mut.right = Some(Self::rotate_right(n_mut.right.take().unwrap()));
+            n = Self::rotate_left(n);
+            Self::flip_colors(&mut n);
+        }
+        n
+    }
+
+    /// Assuming n is red and both n.right and n.right.left are black, make
+    /// n.right or one of it's children red (the mirror image of move_red_left).
+    fn move_red_right(mut n: P::Ptr) -> P::Ptr {
+        Self::flip_colors(&mut n);
+        if Self::is_red(&n.left.as_ref().unwrap().left) {
+            n = Self::rotate_right(n);
+            Self::flip_colors(&mut n);
+        }
+        n
+    }
+
+    fn insert(link: Link<K, V, S, P>, node_to_insert: P::Ptr) -> P::Ptr {
+        match link {
             // we keep searching down the tree for a null link to place the
             // new node (or until we find a match in which case we just update)
-            None => Some(Box::new(node_to_insert)),
+            None => node_to_insert,
             Some(mut node) => {
                 // check if we need to go left, right, or we've hit a match
-                match node_to_insert.key.cmp(&node.key) {
-                    Ordering::Equal => node.val = node_to_insert.val,
-                    Ordering::Less => node.left = Self::insert(&mut node.left, node_to_insert),
-                    Ordering::Greater => node.right = Self::insert(&mut node.right, node_to_insert),
+                let node_mut = P::make_mut(&mut node);
+                match node_to_insert.key.cmp(&node_mut.key) {
+                    Ordering::Equal => node_mut.val = P::into_inner(node_to_insert).val,
+                    Ordering::Less => {
+                        node_mut.left = Some(Self::insert(node_mut.left.take(), node_to_insert))
+                    }
+                    Ordering::Greater => {
+                        node_mut.right = Some(Self::insert(node_mut.right.take(), node_to_insert))
+                    }
                 }
 
-                // If we've inserted a new node, our red black tree structure
-                // invariants may have been violated in 3 possible ways:
-                //
-                //   1) there is a left and a right red link (in which case we flip the colors)
-                //   2) there are two left red links in a row (in which case we rotate right and flip colors)
-                //   3) there is a right red link (in which case we rotate left)
-                //
-                let some_and_red = |n: Option<&Box<Node<K, V>>>| n.map_or(false, |x| x.is_red);
-
-                let left = (&node).left.as_ref();
-                let right = (&node).right.as_ref();
-                let left_left = left.map_or(None, |n| n.left.as_ref());
-
-                let left_is_red = some_and_red(left);
-                let right_is_red = some_and_red(right);
-                let left_left_is_red = some_and_red(left_left);
-
-                if left_is_red && right_is_red {
-                    Self::flip_colors(*node)
-                } else if left_is_red && left_left_is_red {
-                    Self::rotate_right(*node)
-                } else if right_is_red {
-                    Self::rotate_left(*node)
-                } else {
-                    Some(node)
-                }
+                // if we've inserted a new node, our red black tree structure
+                // invariants may have been violated, so we rebalance
+                Self::balance(node)
+            }
+        }
+    }
+
+    /// Deletes the minimum node in the subtree rooted at n, returning the new
+    /// subtree and the deleted node.
+    ///
+    /// As we descend down the left spine, we make sure that the current node
+    /// is never a 2-node (using move_red_left), so that at the bottom we can
+    /// remove the minimum without breaking perfect black balance.
+    fn delete_min(mut n: P::Ptr) -> Deleted<K, V, S, P> {
+        // in a left leaning red black tree, if there is no left child there is
+        // also no right child
+        if n.left.is_none() {
+            return (None, n);
+        }
+
+        if !Self::is_red(&n.left) && !Self::is_red(&n.left.as_ref().unwrap().left) {
+            n = Self::move_red_left(n);
+        }
+
+        let n_mut = P::make_mut(&mut n);
+        let deleted;
+        (n_mut.left, deleted) = Self::delete_min(n_mut.left.take().unwrap());
+        (Some(Self::balance(n)), deleted)
+    }
+
+    /// Deletes the maximum node in the subtree rooted at n, returning the new
+    /// subtree and the deleted node.
+    ///
+    /// Same as delete_min, but since red links lean left, we first rotate
+    /// them to the right so that we can carry them down the right spine.
+    fn delete_max(mut n: P::Ptr) -> Deleted<K, V, S, P> {
+        if Self::is_red(&n.left) {
+            n = Self::rotate_right(n);
+        }
+
+        if n.right.is_none() {
+            return (None, n);
+        }
+
+        if !Self::is_red(&n.right) && !Self::is_red(&n.right.as_ref().unwrap().left) {
+            n = Self::move_red_right(n);
+        }
+
+        let n_mut = P::make_mut(&mut n);
+        let deleted;
+        (n_mut.right, deleted) = Self::delete_max(n_mut.right.take().unwrap());
+        (Some(Self::balance(n)), deleted)
+    }
+
+    /// Deletes the node with the given key in the subtree rooted at n,
+    /// returning the new subtree and the deleted node. The key must exist in
+    /// the subtree.
+    ///
+    /// Similar to delete_min and delete_max, we move red links down the search
+    /// path so that the node we end up deleting is never a 2-node. If the
+    /// matching node is not at the bottom of the tree, we swap it's contents
+    /// with it's successor (the min of the right subtree) and delete the
+    /// successor instead.
+    fn delete<Q: ?Sized + Ord>(mut n: P::Ptr, key: &Q) -> Deleted<K, V, S, P>
+    where
+        K: Borrow<Q>,
+    {
+        let deleted;
+
+        if key < n.key.borrow() {
+            if !Self::is_red(&n.left) && !Self::is_red(&n.left.as_ref().unwrap().left) {
+                n = Self::move_red_left(n);
+            }
+            let n_mut = P::make_mut(&mut n);
+            (n_mut.left, deleted) = Self::delete(n_mut.left.take().unwrap(), key);
+        } else {
+            if Self::is_red(&n.left) {
+                n = Self::rotate_right(n);
+            }
+
+            if key == n.key.borrow() && n.right.is_none() {
+                return (None, n);
+            }
+
+            if !Self::is_red(&n.right) && !Self::is_red(&n.right.as_ref().unwrap().left) {
+                n = Self::move_red_right(n);
+            }
+
+            let n_mut = P::make_mut(&mut n);
+            if key == n_mut.key.borrow() {
+                let mut successor;
+                (n_mut.right, successor) = Self::delete_min(n_mut.right.take().unwrap());
+                let successor_mut = P::make_mut(&mut successor);
+                std::mem::swap(&mut n_mut.key, &mut successor_mut.key);
+                std::mem::swap(&mut n_mut.val, &mut successor_mut.val);
+                deleted = successor;
+            } else {
+                (n_mut.right, deleted) = Self::delete(n_mut.right.take().unwrap(), key);
             }
         }
+
+        (Some(Self::balance(n)), deleted)
     }
 
-    fn search<'a>(link: &'a Link<K, V>, key: K) -> Option<&'a Node<K, V>> {
+    pub(super) fn search<'a, Q: ?Sized + Ord>(
+        link: &'a Link<K, V, S, P>,
+        key: &Q,
+    ) -> Option<&'a Node<K, V, S, P>>
+    where
+        K: Borrow<Q>,
+    {
         match link {
             None => None,
-            Some(node) => match key.cmp(&node.key) {
+            Some(node) => match key.cmp(node.key.borrow()) {
                 Ordering::Equal => Some(node.deref()),
                 Ordering::Greater => Self::search(&node.right, key),
                 Ordering::Less => Self::search(&node.left, key),
@@ -186,43 +789,250 @@ impl<K: KeyT, V> RedBlackBST<K, V> {
     }
 }
 
+impl<K: KeyT, V> RedBlackBST<K, V> {
+    fn search_mut<'a, Q: ?Sized + Ord>(
+        link: &'a mut Link<K, V>,
+        key: &Q,
+    ) -> Option<(&'a K, &'a mut V)>
+    where
+        K: Borrow<Q>,
+    {
+        let node = link.as_mut()?.deref_mut();
+        match key.cmp(node.key.borrow()) {
+            Ordering::Equal => Some((&node.key, &mut node.val)),
+            Ordering::Greater => Self::search_mut(&mut node.right, key),
+            Ordering::Less => Self::search_mut(&mut node.left, key),
+        }
+    }
+}
+
+impl<K: KeyT, V> VacantInsert<K, V> for RedBlackBST<K, V> {
+    /// Once the key is moved into the tree we can no longer search for it, and
+    /// the rotations may move the new node anywhere along the search path. So
+    /// instead, we box the new node up front and keep a pointer to it's value.
+    /// The rotations only ever move the box, never the node on the heap, and
+    /// since the key is new the insert never overwrites or drops the value, so
+    /// the pointer is still valid once the insert returns.
+    fn insert_vacant(&mut self, key: K, val: V) -> Result<&mut V, String> {
+        let mut node = Box::new(Node::new(key, val));
+        let val: *mut V = &mut node.val;
+        self.insert_node(node);
+        Ok(unsafe { &mut *val })
+    }
+}
+
 impl<K: KeyT, V> SymbolTable<K, V> for RedBlackBST<K, V> {
     fn put(&mut self, key: K, val: V) -> Result<(), String> {
-        let node_to_insert = Node {
-            key: key,
-            val: val,
-            left: None,
-            right: None,
-            is_red: true, // new node must be a red link
-        };
-
-        let mut root = self.root.take();
-        self.root = Self::insert(&mut root, node_to_insert);
+        self.insert_entry(key, val);
+        Ok(())
+    }
 
-        // root is kept black since it is technically not part of a 3-node
-        self.root.as_mut().unwrap().is_red = false;
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
 
-        self.size += 1;
-        Ok(())
+    fn size(&self) -> usize {
+        size(&self.root)
     }
+}
 
-    fn get(&self, key: K) -> Option<&V> {
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for RedBlackBST<K, V>
+where
+    K: Borrow<Q>,
+{
+    fn get(&self, key: &Q) -> Option<&V> {
         let node = Self::search(&self.root, key)?;
         Some(&node.val)
     }
 
-    #[allow(unused_variables)]
-    fn pop(&mut self, key: K) -> Option<V> {
-        self.size -= 1;
-        todo!()
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// We use the left leaning red black tree hard delete. Deleting a node
+    /// from a 3-node or 4-node leaves the tree balanced, but deleting from a
+    /// 2-node would leave an empty link and break perfect black balance. So, on
+    /// the way down the search path we push red links down the tree (by
+    /// borrowing from or merging with siblings) so that the current node is
+    /// never a 2-node, and then on the way back up we rebalance the temporary
+    /// 4-nodes we've created. Delete is O(log n).
+    ///
+    /// See: https://algs4.cs.princeton.edu/33balanced/
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        self.delete_entry(key)
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for RedBlackBST<K, V> {
+    /// Get the smallest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The smallest key is the leftmost node in the tree.
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
     }
 
-    fn is_empty(&self) -> bool {
-        self.size == 0
+    /// Get the largest key (None if the tree is empty).
+    ///
+    /// ### Implementation
+    /// The largest key is the rightmost node in the tree.
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
     }
 
-    fn size(&self) -> usize {
-        self.size
+    /// Get the largest key that is <= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST, since the colors of the links don't matter
+    /// for searching.
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, key),
+                Ordering::Greater => visit(&node.right, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the smallest key that is >= to the given key (None if no such key
+    /// exists).
+    ///
+    /// ### Implementation
+    /// Same as floor, but with left and right swapped.
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<'a, K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(
+            link: &'a Link<K, V>,
+            key: &Q,
+        ) -> Option<&'a K> {
+            let node = link.as_ref()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Greater => visit(&node.right, key),
+                Ordering::Less => visit(&node.left, key).or(Some(&node.key)),
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the number of keys in the tree that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST, using the subtree sizes stored in each node.
+    /// Since the tree is balanced, rank is O(log n).
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        fn visit<K: KeyT + Borrow<Q>, Q: ?Sized + Ord, V>(link: &Link<K, V>, key: &Q) -> usize {
+            match link {
+                None => 0,
+                Some(node) => match key.cmp(node.key.borrow()) {
+                    Ordering::Equal => size(&node.left),
+                    Ordering::Less => visit(&node.left, key),
+                    Ordering::Greater => 1 + size(&node.left) + visit(&node.right, key),
+                },
+            }
+        }
+
+        visit(&self.root, key)
+    }
+
+    /// Get the key with the given rank, that is the key such that exactly
+    /// `rank` keys in the tree are less than it (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// Same as for a regular BST, using the subtree sizes stored in each node.
+    /// Since the tree is balanced, select is O(log n).
+    fn select(&self, rank: usize) -> Option<&K> {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, rank: usize) -> Option<&K> {
+            let node = link.as_ref()?;
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => Some(&node.key),
+                Ordering::Less => visit(&node.left, rank),
+                Ordering::Greater => visit(&node.right, rank - left_size - 1),
+            }
+        }
+
+        visit(&self.root, rank)
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the
+    /// tree is empty). See pop for details on how deletion works.
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        self.delete_min_entry()
+    }
+
+    /// Remove the entry with the largest key and return it (None if the
+    /// tree is empty). See pop for details on how deletion works.
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        self.delete_max_entry()
+    }
+}
+
+impl<K: KeyT + Display, V> RedBlackBST<K, V> {
+    /// Render the tree in the Graphviz DOT format (e.g. to view with
+    /// `dot -Tsvg`), with the red links colored red.
+    ///
+    /// ### Implementation
+    /// We do a pre-order traversal, numbering the nodes in the order we visit
+    /// them, and add an edge from each node to each of it's children. The
+    /// color of a link is stored in the child node, so an edge is red if the
+    /// child is red. Null links are left out (unlike BinarySearchTree::to_dot,
+    /// we don't need placeholders to keep the shape of the tree, since a node
+    /// with a single child always has it on the left).
+    pub fn to_dot(&self) -> String {
+        fn visit<K: KeyT + Display, V>(link: &Link<K, V>, id: &mut usize, output: &mut String) {
+            let Some(node) = link else {
+                return;
+            };
+            let node_id = *id;
+            output.push_str(&format!(
+                "    n{} [label={}];\n",
+                node_id,
+                dot_label(&node.key)
+            ));
+            for child in [&node.left, &node.right] {
+                if let Some(child_node) = child {
+                    *id += 1;
+                    let color = if child_node.is_red { "red" } else { "black" };
+                    output.push_str(&format!(
+                        "    n{} -> n{} [color={}];\n",
+                        node_id, *id, color
+                    ));
+                    visit(child, id, output);
+                }
+            }
+        }
+
+        let mut output = "digraph {\n    node [shape=circle];\n".to_string();
+        visit(&self.root, &mut 0, &mut output);
+        output.push_str("}\n");
+        output
     }
 }
 
@@ -268,59 +1078,144 @@ impl<K: KeyT + Display, V> Display for RedBlackBST<K, V> {
     }
 }
 
-pub struct IntoIter<'a, K: KeyT, V> {
-    pub stack: StackLinkedList<&'a Node<K, V>>,
+/// An iterator over the entries of a RedBlackBST in sorted order.
+///
+/// The iterator keeps two stacks, one for iterating forwards and one for
+/// iterating backwards. The front stack holds the nodes whose left subtrees
+/// we've already visited (initially the left spine of the tree), so the next
+/// entry is always at the top of the stack. After popping a node, we push the
+/// left spine of it's right subtree. The back stack is the mirror image. We
+/// keep track of the number of remaining entries so that the two ends stop
+/// when they meet.
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
 }
 
-impl<'a, K: KeyT, V> IntoIter<'a, K, V> {
-    pub fn new(tree: &'a RedBlackBST<K, V>) -> Self {
-        let mut iter = IntoIter {
-            stack: StackLinkedList::new(),
-        };
-        iter.push_left_nodes(&tree.root);
-        iter
-    }
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
 
-    pub fn push_left_nodes(&mut self, mut link: &'a Link<K, V>) {
-        while let Some(node) = link {
-            self.stack.push(node.deref()).unwrap();
-            link = &node.left;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
+        }
+
+        Some((&node.key, &node.val))
     }
 }
 
-impl<'a, K: KeyT, V> Iterator for IntoIter<'a, K, V> {
-    type Item = &'a K;
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.stack.pop() {
-            None => None,
-            Some(node) => {
-                self.push_left_nodes(&node.right);
-                Some(&node.key)
-            }
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
         }
+
+        Some((&node.key, &node.val))
     }
 }
 
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
 impl<'a, K: KeyT, V> IntoIterator for &'a RedBlackBST<K, V> {
-    type Item = &'a K;
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut RedBlackBST<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
 
-    type IntoIter = IntoIter<'a, K, V>;
+impl<K: KeyT, V> IntoIterator for RedBlackBST<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
 
+    /// Consume the tree, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// We move the entries into a linked list with an in-order traversal
+    /// (see ListIter), which is O(n).
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter::new(self)
+        fn visit<K: KeyT, V>(link: Link<K, V>, list: &mut LinkedList<(K, V)>) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = *node;
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.root, &mut list);
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for RedBlackBST<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for RedBlackBST<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use rand::Rng;
+
+    use crate::random::shuffle_arr;
+
     use super::*;
 
     #[test]
     fn test_example_1() {
         let mut st = RedBlackBST::new();
-        assert_eq!(st.is_empty(), true);
+        assert!(st.is_empty());
         assert_eq!(st.size(), 0);
 
         st.put("S", 1).unwrap();
@@ -335,20 +1230,20 @@ mod tests {
         st.put("L", 10).unwrap();
 
         assert_eq!(st.size(), 10);
-        assert_eq!(st.is_empty(), false);
-
-        assert_eq!(st.get("S"), Some(&1));
-        assert_eq!(st.get("E"), Some(&2));
-        assert_eq!(st.get("A"), Some(&3));
-        assert_eq!(st.get("R"), Some(&4));
-        assert_eq!(st.get("C"), Some(&5));
-        assert_eq!(st.get("H"), Some(&6));
-        assert_eq!(st.get("X"), Some(&7));
-        assert_eq!(st.get("M"), Some(&8));
-        assert_eq!(st.get("P"), Some(&9));
-        assert_eq!(st.get("L"), Some(&10));
-
-        let mut iter = (&st).into_iter();
+        assert!(!st.is_empty());
+
+        assert_eq!(st.get(&"S"), Some(&1));
+        assert_eq!(st.get(&"E"), Some(&2));
+        assert_eq!(st.get(&"A"), Some(&3));
+        assert_eq!(st.get(&"R"), Some(&4));
+        assert_eq!(st.get(&"C"), Some(&5));
+        assert_eq!(st.get(&"H"), Some(&6));
+        assert_eq!(st.get(&"X"), Some(&7));
+        assert_eq!(st.get(&"M"), Some(&8));
+        assert_eq!(st.get(&"P"), Some(&9));
+        assert_eq!(st.get(&"L"), Some(&10));
+
+        let mut iter = st.keys();
         assert_eq!(iter.next(), Some(&"A"));
         assert_eq!(iter.next(), Some(&"C"));
         assert_eq!(iter.next(), Some(&"E"));
@@ -365,7 +1260,7 @@ mod tests {
     #[test]
     fn test_example_2() {
         let mut st = RedBlackBST::new();
-        assert_eq!(st.is_empty(), true);
+        assert!(st.is_empty());
         assert_eq!(st.size(), 0);
 
         st.put("A", 1).unwrap();
@@ -380,20 +1275,20 @@ mod tests {
         st.put("X", 10).unwrap();
 
         assert_eq!(st.size(), 10);
-        assert_eq!(st.is_empty(), false);
-
-        assert_eq!(st.get("A"), Some(&1));
-        assert_eq!(st.get("C"), Some(&2));
-        assert_eq!(st.get("E"), Some(&3));
-        assert_eq!(st.get("H"), Some(&4));
-        assert_eq!(st.get("L"), Some(&5));
-        assert_eq!(st.get("M"), Some(&6));
-        assert_eq!(st.get("P"), Some(&7));
-        assert_eq!(st.get("R"), Some(&8));
-        assert_eq!(st.get("S"), Some(&9));
-        assert_eq!(st.get("X"), Some(&10));
-
-        let mut iter = (&st).into_iter();
+        assert!(!st.is_empty());
+
+        assert_eq!(st.get(&"A"), Some(&1));
+        assert_eq!(st.get(&"C"), Some(&2));
+        assert_eq!(st.get(&"E"), Some(&3));
+        assert_eq!(st.get(&"H"), Some(&4));
+        assert_eq!(st.get(&"L"), Some(&5));
+        assert_eq!(st.get(&"M"), Some(&6));
+        assert_eq!(st.get(&"P"), Some(&7));
+        assert_eq!(st.get(&"R"), Some(&8));
+        assert_eq!(st.get(&"S"), Some(&9));
+        assert_eq!(st.get(&"X"), Some(&10));
+
+        let mut iter = st.keys();
         assert_eq!(iter.next(), Some(&"A"));
         assert_eq!(iter.next(), Some(&"C"));
         assert_eq!(iter.next(), Some(&"E"));
@@ -406,4 +1301,329 @@ mod tests {
         assert_eq!(iter.next(), Some(&"X"));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_pop() {
+        let mut st = RedBlackBST::new();
+        for (i, key) in ["S", "E", "A", "R", "C", "H", "X", "M", "P", "L"]
+            .into_iter()
+            .enumerate()
+        {
+            st.put(key, i).unwrap();
+        }
+        st.check().unwrap();
+
+        assert_eq!(st.pop(&"Z"), None);
+        assert_eq!(st.size(), 10);
+
+        assert_eq!(st.pop(&"E"), Some(1));
+        st.check().unwrap();
+        assert_eq!(st.get(&"E"), None);
+        assert_eq!(st.pop(&"E"), None);
+        assert_eq!(st.size(), 9);
+
+        assert_eq!(st.pop_min(), Some(("A", 2)));
+        st.check().unwrap();
+        assert_eq!(st.pop_max(), Some(("X", 6)));
+        st.check().unwrap();
+        assert_eq!(st.size(), 7);
+
+        let keys: Vec<&str> = st.keys().copied().collect();
+        assert_eq!(keys, ["C", "H", "L", "M", "P", "R", "S"]);
+
+        for key in keys {
+            assert!(st.pop(&key).is_some());
+            st.check().unwrap();
+        }
+        assert!(st.is_empty());
+        assert_eq!(st.pop_min(), None);
+        assert_eq!(st.pop_max(), None);
+    }
+
+    #[test]
+    fn test_random_put_pop() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let mut keys: Vec<i32> = (0..200).collect();
+            shuffle_arr(&mut keys);
+
+            let mut st = RedBlackBST::new();
+            for &key in keys.iter() {
+                st.put(key, -key).unwrap();
+                st.check().unwrap();
+            }
+
+            shuffle_arr(&mut keys);
+            for &key in keys.iter() {
+                match rng.gen_range(0..4) {
+                    0 => {
+                        let (min, _) = st.pop_min().unwrap();
+                        assert_eq!(st.get(&min), None);
+                    }
+                    1 => {
+                        let (max, _) = st.pop_max().unwrap();
+                        assert_eq!(st.get(&max), None);
+                    }
+                    _ => {
+                        let expected = st.get(&key).copied();
+                        assert_eq!(st.pop(&key), expected);
+                        assert_eq!(st.get(&key), None);
+                    }
+                }
+                st.check().unwrap();
+                if st.is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let mut st = RedBlackBST::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let keys = |range: Iter<i32, i32>| range.map(|(k, _)| *k).collect::<Vec<i32>>();
+        assert_eq!(keys(st.range(30..70)), [30, 40, 50, 60]);
+        assert_eq!(keys(st.range(30..=70)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(25..75)), [30, 40, 50, 60, 70]);
+        assert_eq!(keys(st.range(..30)), [10, 20]);
+        assert_eq!(keys(st.range(85..)), [90]);
+        assert_eq!(keys(st.range(..)), [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        assert_eq!(keys(st.range(91..)), [0; 0]);
+        assert_eq!(keys(st.range(50..50)), [0; 0]);
+
+        let mut range = st.range(40..=50);
+        assert_eq!(range.next(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = RedBlackBST::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            st.put(key, -key).unwrap();
+        }
+
+        let mut iter = st.iter();
+        assert_eq!(iter.next(), Some((&10, &-10)));
+        assert_eq!(iter.next_back(), Some((&90, &-90)));
+        assert_eq!(iter.next(), Some((&20, &-20)));
+        assert_eq!(iter.next_back(), Some((&80, &-80)));
+        assert_eq!(iter.count(), 5);
+
+        let keys: Vec<i32> = st.keys().copied().collect();
+        assert_eq!(keys, [10, 20, 30, 40, 50, 60, 70, 80, 90]);
+        let keys: Vec<i32> = st.keys().rev().copied().collect();
+        assert_eq!(keys, [90, 80, 70, 60, 50, 40, 30, 20, 10]);
+        let vals: Vec<i32> = st.values().copied().collect();
+        assert_eq!(vals, [-10, -20, -30, -40, -50, -60, -70, -80, -90]);
+
+        let mut range = st.range(30..=70).rev();
+        assert_eq!(range.next(), Some((&70, &-70)));
+        assert_eq!(range.next(), Some((&60, &-60)));
+        assert_eq!(range.next_back(), Some((&30, &-30)));
+        assert_eq!(range.next_back(), Some((&40, &-40)));
+        assert_eq!(range.next(), Some((&50, &-50)));
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+
+        for (key, val) in st.iter_mut() {
+            *val = 2 * key;
+        }
+        for (key, val) in st.iter_mut().rev().take(2) {
+            *val += key;
+        }
+        for (_, val) in &mut st {
+            *val += 1;
+        }
+        let entries: Vec<(i32, i32)> = (&st).into_iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(
+            entries,
+            [
+                (10, 21),
+                (20, 41),
+                (30, 61),
+                (40, 81),
+                (50, 101),
+                (60, 121),
+                (70, 141),
+                (80, 241),
+                (90, 271)
+            ]
+        );
+
+        let mut into_iter = st.into_iter();
+        assert_eq!(into_iter.next(), Some((10, 21)));
+        assert_eq!(into_iter.next_back(), Some((90, 271)));
+        assert_eq!(
+            into_iter.rev().map(|(k, _)| k).collect::<Vec<i32>>(),
+            [80, 70, 60, 50, 40, 30, 20]
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut st = RedBlackBST::new();
+        let text = "it was the best of times it was the worst of times";
+        for word in text.split(' ') {
+            *st.entry(word).or_insert(0).unwrap() += 1;
+            st.check().unwrap();
+        }
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get(&"times"), Some(&2));
+        assert_eq!(st.get(&"best"), Some(&1));
+
+        st.entry("the").and_modify(|count| *count *= 10);
+        assert_eq!(st.get(&"the"), Some(&20));
+        st.entry("age").and_modify(|count| *count *= 10);
+        assert_eq!(st.get(&"age"), None);
+
+        // the new node can end up anywhere after rebalancing, so insert keys in
+        // increasing order and check we always get the right value back
+        let mut st = RedBlackBST::new();
+        for i in 0..100 {
+            assert_eq!(st.entry(i).or_insert_with(|| -i), Ok(&mut -i));
+            st.check().unwrap();
+        }
+        for i in 0..100 {
+            assert_eq!(st.entry(i).or_insert(0), Ok(&mut -i));
+        }
+    }
+
+    #[test]
+    fn test_entry_walks() {
+        // a key that counts every comparison, to pin down how many times
+        // entry walks down the tree
+        #[derive(Debug)]
+        struct Counted(i32, Rc<Cell<usize>>);
+        impl PartialEq for Counted {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+        impl Eq for Counted {}
+        impl PartialOrd for Counted {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Counted {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.1.set(self.1.get() + 1);
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let count = Rc::new(Cell::new(0));
+        let mut keys: Vec<i32> = (0..200).map(|i| 2 * i).collect();
+        shuffle_arr(&mut keys);
+        let mut st = RedBlackBST::new();
+        for &key in keys.iter() {
+            st.put(Counted(key, count.clone()), key).unwrap();
+        }
+        let mut compares = |f: &mut dyn FnMut(&mut RedBlackBST<Counted, i32>)| {
+            count.set(0);
+            f(&mut st);
+            count.get()
+        };
+
+        // an occupied entry is a single walk, the same as a get
+        for &key in keys.iter() {
+            let get = compares(&mut |st| assert!(st.get(&Counted(key, count.clone())).is_some()));
+            let entry = compares(&mut |st| {
+                *st.entry(Counted(key, count.clone())).or_insert(0).unwrap() += 1;
+            });
+            assert_eq!(entry, get);
+        }
+
+        // a vacant entry walks once to search and once more to insert
+        for &key in keys.iter() {
+            let get =
+                compares(&mut |st| assert!(st.get(&Counted(key + 1, count.clone())).is_none()));
+            let entry = compares(&mut |st| {
+                *st.entry(Counted(key + 1, count.clone()))
+                    .or_insert(0)
+                    .unwrap() += 1;
+            });
+            assert_eq!(entry, 2 * get);
+        }
+        assert_eq!(st.size(), 2 * keys.len());
+        for &key in keys.iter() {
+            assert_eq!(st.get(&Counted(key, count.clone())), Some(&(key + 1)));
+            assert_eq!(st.get(&Counted(key + 1, count.clone())), Some(&1));
+        }
+    }
+
+    #[test]
+    fn test_check_and_stats() {
+        let mut st = RedBlackBST::new();
+        assert_eq!(st.height(), 0);
+        assert_eq!(st.average_depth(), None);
+        st.check().unwrap();
+
+        // sorted inserts stay balanced (height <= 2 log n)
+        for key in 0..1000 {
+            st.put(key, ()).unwrap();
+        }
+        st.check().unwrap();
+        assert!(st.height() <= 20);
+        assert!(st.average_depth().unwrap() < 10.0);
+        let counts = st.depth_counts();
+        assert_eq!(counts.len(), st.height());
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts.iter().sum::<usize>(), 1000);
+
+        // 2 - 1 - 3 is a single 3-node and 2 black nodes
+        let mut st = RedBlackBST::new();
+        for key in [2, 1, 3] {
+            st.put(key, ()).unwrap();
+        }
+        assert_eq!(st.depth_counts()[..], [1, 2]);
+        assert_eq!(st.average_depth(), Some(2.0 / 3.0));
+
+        // corrupt the tree
+        st.root.as_mut().unwrap().is_red = true;
+        assert_eq!(st.check(), Err("root is red".to_string()));
+        st.root.as_mut().unwrap().is_red = false;
+        st.root.as_mut().unwrap().right.as_mut().unwrap().is_red = true;
+        assert_eq!(st.check(), Err("right leaning red link".to_string()));
+        st.root.as_mut().unwrap().right.as_mut().unwrap().is_red = false;
+        st.root.as_mut().unwrap().left.as_mut().unwrap().is_red = true;
+        assert_eq!(st.check(), Err("not black balanced".to_string()));
+        st.root.as_mut().unwrap().left.as_mut().unwrap().is_red = false;
+        st.root.as_mut().unwrap().left.as_mut().unwrap().size = 2;
+        assert_eq!(st.check(), Err("inconsistent subtree size".to_string()));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut st = RedBlackBST::new();
+        assert_eq!(st.to_dot(), "digraph {\n    node [shape=circle];\n}\n");
+
+        //     E
+        //    / \
+        //   A   S
+        //      //
+        //     R
+        for key in ["S", "E", "A", "R"] {
+            st.put(key, ()).unwrap();
+        }
+        let expected = [
+            "digraph {",
+            "    node [shape=circle];",
+            "    n0 [label=\"E\"];",
+            "    n0 -> n1 [color=black];",
+            "    n1 [label=\"A\"];",
+            "    n0 -> n2 [color=black];",
+            "    n2 [label=\"S\"];",
+            "    n2 -> n3 [color=red];",
+            "    n3 [label=\"R\"];",
+            "}",
+        ];
+        assert_eq!(st.to_dot().lines().collect::<Vec<_>>(), expected);
+    }
 }
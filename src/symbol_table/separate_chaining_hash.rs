@@ -0,0 +1,434 @@
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::list::Vector;
+
+use super::{
+    iter::{Keys, Values},
+    KeyT, Lookup, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// Initial (and minimum) number of chains.
+const INIT_CAPACITY: usize = 4;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    val: V,
+    next: Link<K, V>,
+}
+
+/// A hash table that stores key-value pairs, resolving collisions with
+/// separate chaining.
+///
+/// A hash function maps each key to one of m chains, where each chain is a
+/// linked list of the entries whose keys hash to that chain. To search for a
+/// key, we hash it to find it's chain, and then sequentially search the chain.
+///
+/// If the hash function spreads the keys uniformly across the chains, the
+/// average chain length is n/m. We keep this between 2 and 10 by doubling
+/// the number of chains when the table gets too full (and halving it when it
+/// gets too sparse), so put, get, and pop are O(1) on average.
+///
+/// The hash function is pluggable via the BuildHasher `S` (see with_hasher),
+/// and defaults to the one used by the std HashMap.
+///
+/// See: https://algs4.cs.princeton.edu/34hash/
+pub struct SeparateChainingHashST<K: KeyT, V, S = RandomState> {
+    chains: Vector<Link<K, V>>,
+    size: usize,
+    hash_builder: S,
+}
+
+impl<K: KeyT + Hash, V> SeparateChainingHashST<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher + Default> Default for SeparateChainingHashST<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher> SeparateChainingHashST<K, V, S> {
+    /// Create an empty table which uses the given hash builder to hash keys.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        SeparateChainingHashST {
+            chains: empty_chains(INIT_CAPACITY),
+            size: 0,
+            hash_builder,
+        }
+    }
+
+    /// The number of chains in the table.
+    pub fn capacity(&self) -> usize {
+        self.chains.len()
+    }
+
+    /// Iterate over the entries (in no particular order).
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            chains: self.chains.iter(),
+            node: None,
+        }
+    }
+
+    /// Iterate over the entries (in no particular order), with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            chains: self.chains.iter_mut(),
+            node: None,
+        }
+    }
+
+    /// Iterate over the keys (in no particular order).
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values (in no particular order).
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Index of the chain for the given key.
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> usize {
+        (self.hash_builder.hash_one(key) % self.chains.len() as u64) as usize
+    }
+
+    /// Rehash all the entries into a new table with the given number of
+    /// chains. We move the nodes themselves rather than reallocating them.
+    fn resize(&mut self, num_chains: usize) {
+        let mut old_chains = std::mem::replace(&mut self.chains, empty_chains(num_chains));
+        for chain in old_chains.iter_mut() {
+            let mut link = chain.take();
+            while let Some(mut node) = link {
+                link = node.next.take();
+                let i = self.hash(&node.key);
+                node.next = self.chains[i].take();
+                self.chains[i] = Some(node);
+            }
+        }
+    }
+}
+
+fn empty_chains<K, V>(num_chains: usize) -> Vector<Link<K, V>> {
+    let mut chains = Vector::new();
+    for _ in 0..num_chains {
+        chains.push(None);
+    }
+    chains
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher> SymbolTable<K, V> for SeparateChainingHashST<K, V, S> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// If the average chain length has reached 10, we first double the number
+    /// of chains. Then we search the chain for the key, updating the value if
+    /// we find it, and otherwise adding a new node to the end of the chain.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        if self.size >= 10 * self.chains.len() {
+            self.resize(2 * self.chains.len());
+        }
+
+        let i = self.hash(&key);
+        let mut link = &mut self.chains[i];
+        while let Some(node) = link {
+            if node.key == key {
+                node.val = val;
+                return Ok(());
+            }
+            link = &mut node.next;
+        }
+        *link = Some(Box::new(Node {
+            key,
+            val,
+            next: None,
+        }));
+        self.size += 1;
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: KeyT + Hash, V, S: BuildHasher, Q: ?Sized + Hash + Eq> Lookup<Q, V>
+    for SeparateChainingHashST<K, V, S>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    ///
+    /// ### Implementation
+    /// Sequential search through the chain the key hashes to.
+    fn get(&self, key: &Q) -> Option<&V> {
+        let mut link = &self.chains[self.hash(key)];
+        while let Some(node) = link {
+            if node.key.borrow() == key {
+                return Some(&node.val);
+            }
+            link = &node.next;
+        }
+        None
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// Sequential search through the chain the key hashes to, and unlink the
+    /// matching node. If the average chain length drops to 2, we halve the
+    /// number of chains.
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let i = self.hash(key);
+        let mut link = &mut self.chains[i];
+        while link.as_ref().is_some_and(|node| node.key.borrow() != key) {
+            link = &mut link.as_mut().unwrap().next;
+        }
+        let node = link.take()?;
+        *link = node.next;
+        self.size -= 1;
+
+        if self.chains.len() > INIT_CAPACITY && self.size <= 2 * self.chains.len() {
+            self.resize(self.chains.len() / 2);
+        }
+        Some(node.val)
+    }
+}
+
+/// An iterator over the entries of a SeparateChainingHashST, chain by chain.
+pub struct Iter<'a, K, V> {
+    chains: std::slice::Iter<'a, Link<K, V>>,
+    node: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self.node {
+                self.node = node.next.as_deref();
+                return Some((&node.key, &node.val));
+            }
+            self.node = self.chains.next()?.as_deref();
+        }
+    }
+}
+
+/// An iterator over the entries of a SeparateChainingHashST, chain by chain,
+/// with mutable references to the values.
+pub struct IterMut<'a, K, V> {
+    chains: std::slice::IterMut<'a, Link<K, V>>,
+    node: Option<&'a mut Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self.node.take() {
+                let Node { key, val, next } = node;
+                self.node = next.as_deref_mut();
+                return Some((key, val));
+            }
+            self.node = self.chains.next()?.as_deref_mut();
+        }
+    }
+}
+
+/// An owning iterator over the entries of a SeparateChainingHashST.
+pub struct IntoIter<K, V> {
+    chains: Vector<Link<K, V>>,
+    i: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i < self.chains.len() {
+            if let Some(node) = self.chains[self.i].take() {
+                let Node { key, val, next } = *node;
+                self.chains[self.i] = next;
+                return Some((key, val));
+            }
+            self.i += 1;
+        }
+        None
+    }
+}
+
+impl<'a, K: KeyT + Hash, V, S: BuildHasher> IntoIterator for &'a SeparateChainingHashST<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT + Hash, V, S: BuildHasher> IntoIterator
+    for &'a mut SeparateChainingHashST<K, V, S>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT + Hash, V, S> IntoIterator for SeparateChainingHashST<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            chains: self.chains,
+            i: 0,
+        }
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in no particular order,
+/// and rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Hash + Serialize, V: Serialize, S: BuildHasher> Serialize
+    for SeparateChainingHashST<K, V, S>
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Hash + Deserialize<'de>, V: Deserialize<'de>, S: BuildHasher + Default>
+    Deserialize<'de> for SeparateChainingHashST<K, V, S>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasherDefault;
+
+    use super::*;
+
+    /// A terrible hash function that sends every key to the same chain.
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl std::hash::Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    #[test]
+    fn test_separate_chaining_hash_st() {
+        let mut st = SeparateChainingHashST::new();
+        assert!(st.is_empty());
+        assert_eq!(st.get("a"), None);
+        assert_eq!(st.pop("a"), None);
+
+        for (i, word) in "it was the best of times it was the worst of times"
+            .split(' ')
+            .enumerate()
+        {
+            st.put(word.to_string(), i).unwrap();
+        }
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get("it"), Some(&6));
+        assert_eq!(st.get("times"), Some(&11));
+        assert_eq!(st.get("age"), None);
+        assert_eq!(st.pop("best"), Some(3));
+        assert_eq!(st.pop("best"), None);
+        assert_eq!(st.size(), 6);
+
+        let mut keys: Vec<&str> = st.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["it", "of", "the", "times", "was", "worst"]);
+
+        for (_, val) in st.iter_mut() {
+            *val *= 10;
+        }
+        assert_eq!(st.get("worst"), Some(&90));
+
+        let mut entries: Vec<(String, usize)> = st.into_iter().collect();
+        entries.sort();
+        assert_eq!(entries[0], ("it".to_string(), 60));
+        assert_eq!(entries.len(), 6);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut st = SeparateChainingHashST::new();
+        assert_eq!(st.capacity(), INIT_CAPACITY);
+        for i in 0..1000 {
+            st.put(i, -i).unwrap();
+            // average chain length never exceeds 10
+            assert!(st.size() <= 10 * st.capacity());
+        }
+        assert!(st.capacity() >= 100);
+        for i in 0..1000 {
+            assert_eq!(st.get(&i), Some(&-i));
+        }
+        for i in 0..1000 {
+            assert_eq!(st.pop(&i), Some(-i));
+            assert_eq!(st.get(&i), None);
+            // average chain length stays above 2 (unless we're at the minimum)
+            assert!(st.capacity() == INIT_CAPACITY || st.size() > 2 * st.capacity());
+        }
+        assert!(st.is_empty());
+        assert_eq!(st.capacity(), INIT_CAPACITY);
+    }
+
+    #[test]
+    fn test_with_hasher() {
+        // every key collides, so this degrades to a single linked list, but
+        // should still behave correctly
+        let mut st: SeparateChainingHashST<i32, i32, BuildHasherDefault<ConstantHasher>> =
+            SeparateChainingHashST::default();
+        for i in 0..100 {
+            st.put(i, i * i).unwrap();
+        }
+        st.put(50, 0).unwrap();
+        assert_eq!(st.size(), 100);
+        assert_eq!(st.get(&50), Some(&0));
+        assert_eq!(st.pop(&0), Some(0));
+        assert_eq!(st.pop(&99), Some(99 * 99));
+        assert_eq!(st.pop(&42), Some(42 * 42));
+        assert_eq!(st.get(&42), None);
+        assert_eq!(st.get(&43), Some(&(43 * 43)));
+        assert_eq!(st.iter().count(), 97);
+    }
+}
@@ -0,0 +1,534 @@
+use std::{borrow::Borrow, cmp::Ordering, hash::Hash, iter::Peekable};
+
+use super::{
+    iter::Keys, red_black_bst, separate_chaining_hash, KeyT, Lookup, OrderedSymbolTable,
+    RedBlackBST, SeparateChainingHashST, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// An ordered set of keys, backed by a RedBlackBST with `()` values.
+///
+/// Supports the usual set operations, along with the ordered operations of
+/// the underlying tree (min, max, floor, ceil, rank, select).
+///
+/// ### Implementation
+/// Since iterating over the keys yields them in sorted order, the set
+/// algebra (union, intersection, difference, symmetric difference) is
+/// implemented by merging the two sorted sequences of keys, like in merge
+/// sort (see Merge). This is O(n + m) for sets of size n and m, rather than
+/// the O(n log m) it would take to look up each key of one set in the other.
+/// The operations return lazy iterators over the keys, which can be collected
+/// into a new set.
+pub struct OrderedSet<K: KeyT> {
+    st: RedBlackBST<K, ()>,
+}
+
+impl<K: KeyT> Default for OrderedSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT> OrderedSet<K> {
+    pub fn new() -> Self {
+        OrderedSet {
+            st: RedBlackBST::new(),
+        }
+    }
+
+    /// Add the key to the set, returning whether it was newly added.
+    pub fn insert(&mut self, key: K) -> bool {
+        let size = self.st.size();
+        self.st.put(key, ()).unwrap();
+        self.st.size() > size
+    }
+
+    pub fn contains<Q: ?Sized + Ord>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.st.get(key).is_some()
+    }
+
+    /// Remove the key from the set, returning whether it was in the set.
+    pub fn remove<Q: ?Sized + Ord>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.st.pop(key).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.st.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.st.size()
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn iter(&self) -> Keys<red_black_bst::Iter<'_, K, ()>> {
+        self.st.keys()
+    }
+
+    pub fn min(&self) -> Option<&K> {
+        self.st.min()
+    }
+
+    pub fn max(&self) -> Option<&K> {
+        self.st.max()
+    }
+
+    /// See OrderedSymbolTable::floor.
+    pub fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.st.floor(key)
+    }
+
+    /// See OrderedSymbolTable::ceil.
+    pub fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.st.ceil(key)
+    }
+
+    /// See OrderedSymbolTable::rank.
+    pub fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.st.rank(key)
+    }
+
+    /// See OrderedSymbolTable::select.
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.st.select(rank)
+    }
+
+    /// Iterate over the keys that are in either set in sorted order.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Merge<'a, K> {
+        Merge::new(self, other, true, true, true)
+    }
+
+    /// Iterate over the keys that are in both sets in sorted order.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Merge<'a, K> {
+        Merge::new(self, other, false, true, false)
+    }
+
+    /// Iterate over the keys that are in this set but not the other in sorted
+    /// order.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Merge<'a, K> {
+        Merge::new(self, other, true, false, false)
+    }
+
+    /// Iterate over the keys that are in exactly one of the sets in sorted
+    /// order.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> Merge<'a, K> {
+        Merge::new(self, other, true, false, true)
+    }
+
+    /// Whether every key in this set is also in the other. O(n + m).
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.size() <= other.size() && self.difference(other).next().is_none()
+    }
+
+    /// Whether every key in the other set is also in this one. O(n + m).
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether the sets have no keys in common. O(n + m).
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<K: KeyT> FromIterator<K> for OrderedSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = OrderedSet::new();
+        for key in iter {
+            set.insert(key);
+        }
+        set
+    }
+}
+
+impl<'a, K: KeyT> IntoIterator for &'a OrderedSet<K> {
+    type Item = &'a K;
+    type IntoIter = Keys<red_black_bst::Iter<'a, K, ()>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator that merges the (sorted) keys of two ordered sets, yielding
+/// the keys that are only in the left set, in both sets, and/or only in the
+/// right set, depending on the set operation.
+///
+/// ### Implementation
+/// Like the merge step of merge sort, we compare the next key of each set,
+/// and advance past the smaller one (or both if they're equal). Each step
+/// consumes at least one key, so iterating to the end is O(n + m).
+pub struct Merge<'a, K: KeyT> {
+    left: Peekable<Keys<red_black_bst::Iter<'a, K, ()>>>,
+    right: Peekable<Keys<red_black_bst::Iter<'a, K, ()>>>,
+    left_only: bool,
+    both: bool,
+    right_only: bool,
+}
+
+impl<'a, K: KeyT> Merge<'a, K> {
+    fn new(
+        left: &'a OrderedSet<K>,
+        right: &'a OrderedSet<K>,
+        left_only: bool,
+        both: bool,
+        right_only: bool,
+    ) -> Self {
+        Merge {
+            left: left.iter().peekable(),
+            right: right.iter().peekable(),
+            left_only,
+            both,
+            right_only,
+        }
+    }
+}
+
+impl<'a, K: KeyT> Iterator for Merge<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let order = match (self.left.peek(), self.right.peek()) {
+                (None, None) => return None,
+                (Some(_), None) if !self.left_only => return None,
+                (None, Some(_)) if !self.right_only => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(l), Some(r)) => l.cmp(r),
+            };
+            match order {
+                Ordering::Less => {
+                    let key = self.left.next();
+                    if self.left_only {
+                        return key;
+                    }
+                }
+                Ordering::Greater => {
+                    let key = self.right.next();
+                    if self.right_only {
+                        return key;
+                    }
+                }
+                Ordering::Equal => {
+                    let key = self.left.next();
+                    self.right.next();
+                    if self.both {
+                        return key;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An (unordered) set of keys, backed by a SeparateChainingHashST with `()`
+/// values.
+///
+/// ### Implementation
+/// The set algebra iterates over the keys of one set and looks each of them
+/// up in the other, so for sets of size n and m, intersection and difference
+/// are O(n) on average, and union and symmetric difference are O(n + m). The
+/// operations return lazy iterators over the keys (in no particular order),
+/// which can be collected into a new set.
+pub struct HashSet<K: KeyT> {
+    st: SeparateChainingHashST<K, ()>,
+}
+
+impl<K: KeyT + Hash> Default for HashSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT + Hash> HashSet<K> {
+    pub fn new() -> Self {
+        HashSet {
+            st: SeparateChainingHashST::new(),
+        }
+    }
+
+    /// Add the key to the set, returning whether it was newly added.
+    pub fn insert(&mut self, key: K) -> bool {
+        let size = self.st.size();
+        self.st.put(key, ()).unwrap();
+        self.st.size() > size
+    }
+
+    pub fn contains<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.st.get(key).is_some()
+    }
+
+    /// Remove the key from the set, returning whether it was in the set.
+    pub fn remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.st.pop(key).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.st.is_empty()
+    }
+
+    pub fn size(&self) -> usize {
+        self.st.size()
+    }
+
+    /// Iterate over the keys in no particular order.
+    pub fn iter(&self) -> Keys<separate_chaining_hash::Iter<'_, K, ()>> {
+        self.st.keys()
+    }
+
+    /// Iterate over the keys that are in either set.
+    pub fn union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> + 'a {
+        self.iter().chain(other.difference(self))
+    }
+
+    /// Iterate over the keys that are in both sets.
+    ///
+    /// ### Implementation
+    /// We iterate over the smaller of the two sets, so this is O(min(n, m)).
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> + 'a {
+        let (small, large) = if self.size() <= other.size() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small.iter().filter(|key| large.contains(*key))
+    }
+
+    /// Iterate over the keys that are in this set but not the other.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> + 'a {
+        self.iter().filter(|key| !other.contains(*key))
+    }
+
+    /// Iterate over the keys that are in exactly one of the sets.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a K> + 'a {
+        self.difference(other).chain(other.difference(self))
+    }
+
+    /// Whether every key in this set is also in the other. O(n).
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.size() <= other.size() && self.iter().all(|key| other.contains(key))
+    }
+
+    /// Whether every key in the other set is also in this one. O(m).
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether the sets have no keys in common. O(min(n, m)).
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<K: KeyT + Hash> FromIterator<K> for HashSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = HashSet::new();
+        for key in iter {
+            set.insert(key);
+        }
+        set
+    }
+}
+
+impl<'a, K: KeyT + Hash> IntoIterator for &'a HashSet<K> {
+    type Item = &'a K;
+    type IntoIter = Keys<separate_chaining_hash::Iter<'a, K, ()>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Serialized as a sequence of the keys in sorted order, and rebuilt on load
+/// by inserting them back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize> Serialize for OrderedSet<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>> Deserialize<'de> for OrderedSet<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |set, key| {
+            set.insert(key);
+            Ok(())
+        })
+    }
+}
+
+/// Serialized as a sequence of the keys in no particular order, and rebuilt on
+/// load by inserting them back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Hash + Serialize> Serialize for HashSet<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Hash + Deserialize<'de>> Deserialize<'de> for HashSet<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |set, key| {
+            set.insert(key);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_ordered_set() {
+        let mut set = OrderedSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert("b".to_string()));
+        assert!(set.insert("a".to_string()));
+        assert!(!set.insert("b".to_string()));
+        assert!(set.insert("c".to_string()));
+        assert_eq!(set.size(), 3);
+        assert!(set.contains("a"));
+        assert!(!set.contains("d"));
+        assert_eq!(set.iter().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(set.min().unwrap(), "a");
+        assert_eq!(set.ceil("bb").unwrap(), "c");
+        assert_eq!(set.rank("c"), 2);
+        assert!(set.remove("a"));
+        assert!(!set.remove("a"));
+        assert_eq!(set.select(0).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_ordered_set_algebra() {
+        let a: OrderedSet<i32> = [1, 3, 5, 7, 9, 10].into_iter().collect();
+        let b: OrderedSet<i32> = [2, 3, 4, 9, 10, 11, 12].into_iter().collect();
+        let empty = OrderedSet::new();
+
+        assert_eq!(
+            a.union(&b).copied().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 7, 9, 10, 11, 12]
+        );
+        assert_eq!(a.intersection(&b).copied().collect::<Vec<_>>(), [3, 9, 10]);
+        assert_eq!(a.difference(&b).copied().collect::<Vec<_>>(), [1, 5, 7]);
+        assert_eq!(
+            b.difference(&a).copied().collect::<Vec<_>>(),
+            [2, 4, 11, 12]
+        );
+        assert_eq!(
+            a.symmetric_difference(&b).copied().collect::<Vec<_>>(),
+            [1, 2, 4, 5, 7, 11, 12]
+        );
+        assert_eq!(a.union(&empty).count(), 6);
+        assert_eq!(a.intersection(&empty).count(), 0);
+        assert_eq!(empty.difference(&a).count(), 0);
+
+        let c: OrderedSet<i32> = a.intersection(&b).copied().collect();
+        assert!(c.is_subset(&a) && c.is_subset(&b));
+        assert!(a.is_superset(&c));
+        assert!(!a.is_subset(&b));
+        assert!(empty.is_subset(&a));
+        assert!(a.is_subset(&a));
+        assert!(!a.is_disjoint(&b));
+        let d: OrderedSet<i32> = a.difference(&b).copied().collect();
+        assert!(d.is_disjoint(&b));
+    }
+
+    /// Compare the set algebra of both set types against std's BTreeSet for
+    /// random sets.
+    #[test]
+    fn test_random_set_algebra() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let a: std::collections::BTreeSet<i32> = (0..rng.gen_range(0..50))
+                .map(|_| rng.gen_range(0..60))
+                .collect();
+            let b: std::collections::BTreeSet<i32> = (0..rng.gen_range(0..50))
+                .map(|_| rng.gen_range(0..60))
+                .collect();
+
+            let ordered: [OrderedSet<i32>; 2] =
+                [a.clone(), b.clone()].map(|s| s.into_iter().collect());
+            let hashed: [HashSet<i32>; 2] = [a.clone(), b.clone()].map(|s| s.into_iter().collect());
+            let [oa, ob] = &ordered;
+            let [ha, hb] = &hashed;
+
+            let sorted = |iter: &mut dyn Iterator<Item = &i32>| {
+                let mut keys: Vec<i32> = iter.copied().collect();
+                keys.sort();
+                keys
+            };
+
+            let expected: Vec<_> = a.union(&b).copied().collect();
+            assert_eq!(oa.union(ob).copied().collect::<Vec<_>>(), expected);
+            assert_eq!(sorted(&mut ha.union(hb)), expected);
+
+            let expected: Vec<_> = a.intersection(&b).copied().collect();
+            assert_eq!(oa.intersection(ob).copied().collect::<Vec<_>>(), expected);
+            assert_eq!(sorted(&mut ha.intersection(hb)), expected);
+
+            let expected: Vec<_> = a.difference(&b).copied().collect();
+            assert_eq!(oa.difference(ob).copied().collect::<Vec<_>>(), expected);
+            assert_eq!(sorted(&mut ha.difference(hb)), expected);
+
+            let expected: Vec<_> = a.symmetric_difference(&b).copied().collect();
+            assert_eq!(
+                oa.symmetric_difference(ob).copied().collect::<Vec<_>>(),
+                expected
+            );
+            assert_eq!(sorted(&mut ha.symmetric_difference(hb)), expected);
+
+            assert_eq!(oa.is_subset(ob), a.is_subset(&b));
+            assert_eq!(ha.is_subset(hb), a.is_subset(&b));
+            assert_eq!(oa.is_superset(ob), a.is_superset(&b));
+            assert_eq!(ha.is_superset(hb), a.is_superset(&b));
+            assert_eq!(oa.is_disjoint(ob), a.is_disjoint(&b));
+            assert_eq!(ha.is_disjoint(hb), a.is_disjoint(&b));
+        }
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let mut set: HashSet<String> = ["x", "y"].map(String::from).into_iter().collect();
+        assert!(set.insert("z".to_string()));
+        assert!(!set.insert("x".to_string()));
+        assert_eq!(set.size(), 3);
+        assert!(set.contains("y"));
+        assert!(set.remove("y"));
+        assert!(!set.contains("y"));
+        let mut keys: Vec<_> = set.iter().collect();
+        keys.sort();
+        assert_eq!(keys, ["x", "z"]);
+    }
+}
@@ -0,0 +1,695 @@
+use std::{
+    borrow::Borrow,
+    marker::PhantomData,
+    mem,
+    ops::RangeBounds,
+    ptr::{addr_of_mut, null_mut},
+};
+
+use crate::{list::LinkedList, random::coin_flips};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// The maximum number of levels of a node (enough for 2^32 entries).
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = *mut Node<K, V>;
+
+/// A forward link at some level, along with the number of positions it skips
+/// over (the position of the next node minus the position of this one).
+struct Level<K, V> {
+    next: Link<K, V>,
+    span: usize,
+}
+
+struct Node<K, V> {
+    key: K,
+    val: V,
+    prev: Link<K, V>,
+    levels: Vec<Level<K, V>>,
+}
+
+/// For each level, the last node (null for the head) visited at that level
+/// during a walk down the skip list, along with it's position.
+type Path<K, V> = Vec<(Link<K, V>, usize)>;
+
+/// A skip list is a sorted linked list with extra "express lanes" to skip
+/// ahead through the list.
+///
+/// Each node gets a random number of levels, where the node has 1 level with
+/// probability 1/2, 2 levels with probability 1/4, 3 levels with probability
+/// 1/8 and so on. At each level, the nodes with at least that many levels are
+/// linked together in sorted order. So, level 0 is the whole list, level 1
+/// contains roughly every 2nd node, level 2 roughly every 4th node, etc ...
+///
+/// To find a key, we start at the top level of the head of the list and move
+/// forward until the next node is >= the key, then drop down a level and
+/// repeat. Like binary search, each level roughly halves the number of
+/// candidates, so search, insert and delete are O(log n) expected.
+///
+/// ### Implementation
+/// As in LinkedList, the links are raw pointers since nodes have many
+/// incoming links. Level 0 is doubly linked (with a tail pointer) so that we
+/// can iterate backwards.
+///
+/// Each link also stores it's span, the number of positions it skips over,
+/// where the head is at position 0, the nodes are at positions 1 to n, and a
+/// null link points to position n + 1. Summing the spans along a walk gives
+/// the position of the node we end up at, which gives us rank and select in
+/// O(log n) expected time.
+///
+/// The unsafe code relies on the following invariant: every non-null link (in
+/// the head, the levels of a node, prev and tail) points to a live node that
+/// was allocated by insert with Box::into_raw. The list uniquely owns it's
+/// nodes, and a node is only freed after it has been unlinked (delete) or when
+/// the whole list is torn down (Drop and into_iter), so links never dangle.
+/// References into the nodes borrow the list, so a node can't be freed or
+/// mutated (which needs `&mut self`) while an `&` to it is live.
+///
+/// See: https://en.wikipedia.org/wiki/Skip_list
+pub struct SkipList<K: KeyT, V> {
+    head: Vec<Level<K, V>>,
+    tail: Link<K, V>,
+    size: usize,
+}
+
+impl<K: KeyT, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        SkipList {
+            head: Vec::new(),
+            tail: null_mut(),
+            size: 0,
+        }
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut list = LinkedList::new();
+        // SAFETY: level 0 links are null or live nodes (see SkipList). We hold
+        // `&mut self` for the lifetime of the iterator, so no `&` into the
+        // nodes is live, and each node is visited once, so the `&mut`s to the
+        // values don't alias each other.
+        unsafe {
+            let mut node = self.first();
+            while !node.is_null() {
+                list.push_back((&(*node).key, &mut (*node).val));
+                node = (&(*node).levels)[0].next;
+            }
+        }
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order.
+    ///
+    /// ### Implementation
+    /// We walk to the first node in the range and the last node in the range,
+    /// and iterate along level 0 from either end, stopping once we've yielded
+    /// range_count entries.
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        // SAFETY: the walks only follow links of this list, which are null or
+        // live nodes. The iterator borrows the list, so the nodes it yields
+        // can't be freed or mutated while it's live.
+        unsafe {
+            Iter {
+                front: self.next(&self.walk(|key, _| !is_after_start(key, start))),
+                back: self.last(&self.walk(|key, _| is_before_end(key, end))),
+                remaining: self.range_count((start, end)),
+                marker: PhantomData,
+            }
+        }
+    }
+
+    fn first(&self) -> Link<K, V> {
+        self.head.first().map_or(null_mut(), |level| level.next)
+    }
+
+    /// The levels of the node, or of the head if the node is null.
+    ///
+    /// # Safety
+    /// `node` must be null or a live node of this list.
+    unsafe fn levels(&self, node: Link<K, V>) -> &Vec<Level<K, V>> {
+        if node.is_null() {
+            &self.head
+        } else {
+            &(*node).levels
+        }
+    }
+
+    /// The levels of the node, or of the head if the node is null, mutably.
+    ///
+    /// # Safety
+    /// `node` must be null or a live node of this list. We only borrow the
+    /// levels (not the whole node), so a raw pointer to the node's key or
+    /// value may still be used, but no other reference to these levels may be
+    /// live.
+    unsafe fn levels_mut(&mut self, node: Link<K, V>) -> &mut Vec<Level<K, V>> {
+        if node.is_null() {
+            &mut self.head
+        } else {
+            &mut *addr_of_mut!((*node).levels)
+        }
+    }
+
+    /// Walk down the skip list from the top level of the head, at each level
+    /// moving forward while `advance` is true for the next node (given it's
+    /// key and position), and return the path (see Path).
+    ///
+    /// `advance` must be true for a prefix of the list (e.g. "key < x").
+    ///
+    /// # Safety
+    /// The list invariant must hold (see SkipList), i.e. every link we follow
+    /// is null or a live node. The returned path is only valid until the list
+    /// is next modified.
+    unsafe fn walk(&self, advance: impl Fn(&K, usize) -> bool) -> Path<K, V> {
+        let mut path = vec![(null_mut(), 0); self.head.len()];
+        let (mut node, mut pos) = (null_mut(), 0);
+        for i in (0..self.head.len()).rev() {
+            loop {
+                let level = &self.levels(node)[i];
+                if level.next.is_null() || !advance(&(*level.next).key, pos + level.span) {
+                    break;
+                }
+                node = level.next;
+                pos += level.span;
+            }
+            path[i] = (node, pos);
+        }
+        path
+    }
+
+    /// The last node on the path (null if it's the head).
+    fn last(&self, path: &Path<K, V>) -> Link<K, V> {
+        path.first().map_or(null_mut(), |&(node, _)| node)
+    }
+
+    /// The node after the path (null if it's the end of the list).
+    ///
+    /// # Safety
+    /// `path` must come from a walk of this list, with no modifications to the
+    /// list since.
+    unsafe fn next(&self, path: &Path<K, V>) -> Link<K, V> {
+        path.first()
+            .map_or(null_mut(), |&(node, _)| self.levels(node)[0].next)
+    }
+
+    /// The node with the given key (null if it does not exist).
+    ///
+    /// # Safety
+    /// The list invariant must hold (see walk). The returned node is only valid
+    /// until it's removed from the list.
+    unsafe fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let node = self.next(&self.walk(|k, _| k.borrow() < key));
+        if !node.is_null() && (*node).key.borrow() == key {
+            node
+        } else {
+            null_mut()
+        }
+    }
+
+    /// Insert a key that is not in the list.
+    ///
+    /// We pick the number of levels for the new node, and walk to the last
+    /// node < the key at each level. For each of the new node's levels, we
+    /// splice it in after that node, splitting the span. For the levels
+    /// above, the link now skips over one more node.
+    ///
+    /// # Safety
+    /// The list invariant must hold (see walk), and the key must not already
+    /// be in the list. No references into the nodes may be live, which holds
+    /// since we take `&mut self`.
+    unsafe fn insert(&mut self, key: K, val: V) {
+        let height = 1 + coin_flips(MAX_LEVEL - 1);
+        while self.head.len() < height {
+            self.head.push(Level {
+                next: null_mut(),
+                span: self.size + 1,
+            });
+        }
+
+        let path = self.walk(|k, _| k < &key);
+        let pos = path[0].1 + 1;
+        let node = Box::into_raw(Box::new(Node {
+            key,
+            val,
+            prev: path[0].0,
+            levels: Vec::with_capacity(height),
+        }));
+
+        for (i, &(prev, prev_pos)) in path.iter().enumerate() {
+            let level = &mut self.levels_mut(prev)[i];
+            if i < height {
+                (*node).levels.push(Level {
+                    next: level.next,
+                    span: prev_pos + level.span + 1 - pos,
+                });
+                level.next = node;
+                level.span = pos - prev_pos;
+            } else {
+                level.span += 1;
+            }
+        }
+
+        let next = (&(*node).levels)[0].next;
+        if next.is_null() {
+            self.tail = node;
+        } else {
+            (*next).prev = node;
+        }
+        self.size += 1;
+    }
+
+    /// Remove the node after the path (which must exist) and return it.
+    ///
+    /// For each level of the node, we link the node before it to the node
+    /// after it, merging the spans. For the levels above, the link now skips
+    /// over one less node. Then we drop any empty levels from the head.
+    ///
+    /// # Safety
+    /// `path` must come from a walk of this list, with no modifications to the
+    /// list since, and the node after it must not be null. Once unlinked, the
+    /// node is no longer reachable from the list, so it's safe to hand back
+    /// ownership of it as a Box.
+    unsafe fn delete(&mut self, path: Path<K, V>) -> Box<Node<K, V>> {
+        let node = self.next(&path);
+        for (i, &(prev, _)) in path.iter().enumerate() {
+            let level = &mut self.levels_mut(prev)[i];
+            if level.next == node {
+                let node_level = &(&(*node).levels)[i];
+                level.next = node_level.next;
+                level.span += node_level.span - 1;
+            } else {
+                level.span -= 1;
+            }
+        }
+
+        let next = (&(*node).levels)[0].next;
+        if next.is_null() {
+            self.tail = (*node).prev;
+        } else {
+            (*next).prev = (*node).prev;
+        }
+        while self.head.last().is_some_and(|level| level.next.is_null()) {
+            self.head.pop();
+        }
+        self.size -= 1;
+        Box::from_raw(node)
+    }
+}
+
+impl<K: KeyT, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        let mut node = self.first();
+        while !node.is_null() {
+            // SAFETY: each node was allocated with Box::into_raw and is linked
+            // exactly once along level 0, so it's freed exactly once. Nothing
+            // can borrow the nodes while the list is being dropped.
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.levels[0].next;
+        }
+    }
+}
+
+impl<K: KeyT, V> SymbolTable<K, V> for SkipList<K, V> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// If the key exists we update it's value, otherwise we insert a new node
+    /// with a random number of levels. O(log n) expected.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        // SAFETY: search returns null or a live node, and we only insert the
+        // key when it's not in the list. We hold `&mut self`, so no `&` to the
+        // value is live while we overwrite it.
+        unsafe {
+            let node = self.search(&key);
+            if node.is_null() {
+                self.insert(key, val);
+            } else {
+                (*node).val = val;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for SkipList<K, V>
+where
+    K: Borrow<Q>,
+{
+    fn get(&self, key: &Q) -> Option<&V> {
+        // SAFETY: search returns null or a live node, and the returned
+        // reference borrows the list, so the node outlives it.
+        unsafe { self.search(key).as_ref().map(|node| &node.val) }
+    }
+
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        // SAFETY: the path comes from a walk of the unmodified list, and we
+        // check the node after it is not null before deleting it.
+        unsafe {
+            let path = self.walk(|k, _| k.borrow() < key);
+            let node = self.next(&path);
+            if node.is_null() || (*node).key.borrow() != key {
+                return None;
+            }
+            Some(self.delete(path).val)
+        }
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for SkipList<K, V> {
+    fn min(&self) -> Option<&K> {
+        // SAFETY: the first node is null or live, and the returned reference
+        // borrows the list.
+        unsafe { self.first().as_ref().map(|node| &node.key) }
+    }
+
+    fn max(&self) -> Option<&K> {
+        // SAFETY: the tail is null or live, and the returned reference borrows
+        // the list.
+        unsafe { self.tail.as_ref().map(|node| &node.key) }
+    }
+
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        // SAFETY: the walk follows links of this list, which are null or live
+        // nodes, and the returned reference borrows the list.
+        unsafe {
+            let node = self.last(&self.walk(|k, _| k.borrow() <= key));
+            node.as_ref().map(|node| &node.key)
+        }
+    }
+
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        // SAFETY: the walk follows links of this list, which are null or live
+        // nodes, and the returned reference borrows the list.
+        unsafe {
+            let node = self.next(&self.walk(|k, _| k.borrow() < key));
+            node.as_ref().map(|node| &node.key)
+        }
+    }
+
+    /// Get the number of keys in the list that are strictly less than the
+    /// given key.
+    ///
+    /// ### Implementation
+    /// The position of the last node < the key.
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        // SAFETY: the walk follows links of this list, which are null or live
+        // nodes.
+        unsafe {
+            let path = self.walk(|k, _| k.borrow() < key);
+            path.first().map_or(0, |&(_, pos)| pos)
+        }
+    }
+
+    /// Get the key with the given rank (None if rank >= size).
+    ///
+    /// ### Implementation
+    /// The node at position rank + 1, which we find by walking forward while
+    /// the position of the next node is <= rank.
+    fn select(&self, rank: usize) -> Option<&K> {
+        // SAFETY: the walk follows links of this list, which are null or live
+        // nodes, and the returned reference borrows the list.
+        unsafe {
+            let node = self.next(&self.walk(|_, pos| pos <= rank));
+            node.as_ref().map(|node| &node.key)
+        }
+    }
+
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: the list is not empty, so the first node (after the path
+        // that stays at the head) exists.
+        let node = unsafe { self.delete(self.walk(|_, _| false)) };
+        Some((node.key, node.val))
+    }
+
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        let size = self.size;
+        // SAFETY: the list is not empty, so the last node (after the path to
+        // position size - 1) exists.
+        let node = unsafe { self.delete(self.walk(|_, pos| pos < size)) };
+        Some((node.key, node.val))
+    }
+}
+
+/// An iterator over the entries of a SkipList in sorted order, which walks
+/// along level 0 from either end.
+pub struct Iter<'a, K: KeyT, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    remaining: usize,
+    marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: there are entries remaining, so front is a live node in the
+        // range. The iterator borrows the list for 'a, so the node can't be
+        // freed or mutated while the reference is live.
+        unsafe {
+            let node = &*self.front;
+            self.front = node.levels[0].next;
+            Some((&node.key, &node.val))
+        }
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: as in next, back is a live node in the range.
+        unsafe {
+            let node = &*self.back;
+            self.back = node.prev;
+            Some((&node.key, &node.val))
+        }
+    }
+}
+
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
+impl<'a, K: KeyT, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut SkipList<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for SkipList<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume the list, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// We move the entries into a linked list, taking ownership of the nodes
+    /// so that they are not freed again when the skip list is dropped.
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut list = LinkedList::new();
+        let mut node = self.first();
+        mem::take(&mut self.head);
+        while !node.is_null() {
+            // SAFETY: we emptied the head, so Drop won't free the nodes again,
+            // and each node is linked exactly once along level 0, so each is
+            // reclaimed exactly once.
+            let Node {
+                key, val, levels, ..
+            } = *unsafe { Box::from_raw(node) };
+            list.push_back((key, val));
+            node = levels[0].next;
+        }
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for SkipList<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for SkipList<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbol_table::tests::test_random_ops;
+
+    use super::*;
+
+    /// Checks that each level is sorted and the spans match the positions of
+    /// the nodes, and that level 0 is correctly doubly linked.
+    fn check_invariants<K: KeyT, V>(st: &SkipList<K, V>) {
+        // SAFETY: we only follow links of the list, which are null or live
+        // nodes, and only read through them.
+        unsafe {
+            let mut nodes = vec![null_mut()];
+            let mut node = st.first();
+            while !node.is_null() {
+                assert_eq!((*node).prev, *nodes.last().unwrap(), "bad prev link");
+                nodes.push(node);
+                node = (&(*node).levels)[0].next;
+            }
+            assert_eq!(nodes.len(), st.size() + 1);
+            assert_eq!(st.tail, *nodes.last().unwrap());
+
+            for (pos, &node) in nodes.iter().enumerate() {
+                for level in st.levels(node) {
+                    let next_pos = if level.next.is_null() {
+                        st.size() + 1
+                    } else {
+                        nodes.iter().position(|&n| n == level.next).unwrap()
+                    };
+                    assert!(next_pos > pos, "level out of order");
+                    assert_eq!(level.span, next_pos - pos, "inconsistent span");
+                }
+            }
+            for window in nodes[1..].windows(2) {
+                assert!((*window[0]).key < (*window[1]).key, "keys out of order");
+            }
+            assert!(st.head.last().is_none_or(|level| !level.next.is_null()));
+        }
+    }
+
+    #[test]
+    fn test_skip_list() {
+        let mut st = SkipList::new();
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            st.put(key, i).unwrap();
+            check_invariants(&st);
+        }
+        assert_eq!(st.size(), 10);
+        assert_eq!(st.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(st.get(&'E'), Some(&12));
+        assert_eq!(st.get(&'Z'), None);
+
+        for key in "EXAMPLE".chars() {
+            st.pop(&key);
+            check_invariants(&st);
+        }
+        assert_eq!(st.keys().collect::<String>(), "CHRS");
+        assert_eq!(st.pop(&'E'), None);
+    }
+
+    #[test]
+    fn test_against_btree_map() {
+        test_random_ops(&mut SkipList::new(), check_invariants);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = SkipList::new();
+        for key in [5, 1, 4, 2, 3] {
+            st.put(key, key * 10).unwrap();
+        }
+        assert_eq!(
+            st.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+            [5, 4, 3, 2, 1]
+        );
+        assert_eq!(st.range(2..4).map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(
+            st.range(2..=4).rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+            [4, 3, 2]
+        );
+        assert_eq!(st.range(6..).count(), 0);
+        for (_, val) in st.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(
+            st.into_iter().collect::<Vec<_>>(),
+            [(1, 11), (2, 21), (3, 31), (4, 41), (5, 51)]
+        );
+    }
+}
@@ -0,0 +1,810 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    mem,
+    ops::{Deref, DerefMut, RangeBounds},
+};
+
+use crate::{
+    list::LinkedList,
+    stack::{Stack, StackLinkedList},
+};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+pub struct Node<K, V> {
+    key: K,
+    val: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    size: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, val: V) -> Self {
+        Node {
+            key,
+            val,
+            left: None,
+            right: None,
+            size: 1,
+        }
+    }
+
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Number of nodes in the subtree (0 for a null link).
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// A splay tree is a self-adjusting binary search tree, where every access
+/// moves the accessed node to the root of the tree (called splaying) using
+/// rotations.
+///
+/// A splay tree is not kept balanced, and a single operation can take O(n)
+/// time, but any sequence of m operations takes O(m log n) time, so the
+/// operations are O(log n) amortized. On top of that, recently accessed keys
+/// are near the root, so they're cheap to access again. For access patterns
+/// skewed towards a small set of keys, this is faster than a balanced tree.
+///
+/// Since splaying modifies the tree, the lookups that splay take `&mut self`.
+/// Use access/access_mut for lookups that should adapt the tree. The `get`
+/// of the SymbolTable trait takes `&self`, so it's a plain BST search that
+/// leaves the tree as is (as are the `&self` ordered operations).
+///
+/// ### Implementation
+/// Splaying is done top-down in a single pass down the search path, so it
+/// needs no recursion (see splay). Put splays the key to the root,
+/// and if it's not there, adds a new root with the old root split between it's
+/// children. Pop splays the key to the root, and replaces it with the join of
+/// it's children.
+///
+/// Each node also stores the number of nodes in it's subtree, for rank and
+/// select (see BinarySearchTree).
+///
+/// See: https://algs4.cs.princeton.edu/33balanced/SplayBST.java.html
+pub struct SplayTree<K: KeyT, V> {
+    root: Link<K, V>,
+}
+
+impl<K: KeyT, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V> SplayTree<K, V> {
+    pub fn new() -> Self {
+        SplayTree { root: None }
+    }
+
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist), splaying the key (or the last node on it's search
+    /// path) to the root. O(log n) amortized.
+    pub fn access<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        Some(&*self.access_mut(key)?)
+    }
+
+    /// Same as access, but with a mutable reference to the value.
+    pub fn access_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.root = splay(self.root.take(), &|k: &K| key.cmp(k.borrow()));
+        self.root
+            .as_mut()
+            .filter(|root| root.key.borrow() == key)
+            .map(|root| &mut root.val)
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values (see BinarySearchTree::iter_mut).
+    ///
+    /// ### Implementation
+    /// The in-order traversal uses an explicit stack (like Iter) rather than
+    /// recursion, since the tree can be a path of n nodes (e.g. after sorted
+    /// inserts). We walk down the left spine, pushing each node's entry and
+    /// right subtree, and then pop them off in sorted order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut list = LinkedList::new();
+        let mut stack = StackLinkedList::new();
+        let mut link = &mut self.root;
+        loop {
+            while let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = node.deref_mut();
+                stack.push((&*key, val, right)).unwrap();
+                link = left;
+            }
+            let Some((key, val, right)) = stack.pop() else {
+                break;
+            };
+            list.push_back((key, val));
+            link = right;
+        }
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order (see
+    /// BinarySearchTree::range).
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&Node<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(node),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+}
+
+/// Splay the subtree, so that the node we're searching for (or if it does not
+/// exist, the last node on it's search path) becomes the root. `cmp` compares
+/// the key we're searching for to the key of a node.
+///
+/// This is the top-down splay of Sleator and Tarjan, which works in a single
+/// pass down the search path, so it's iterative. The nodes we pass are split
+/// into a left tree (all < the key) and a right tree (all > the key). At each
+/// step, if the next node is a grandchild in the same direction twice
+/// (zig-zig), we first rotate the grandparent, which is what roughly halves
+/// the depth of the nodes along the search path and gives the amortized
+/// O(log n) bound. Then we link the current node (and the subtree on the far
+/// side of the search path) into the right tree if we're going left, or the
+/// left tree if we're going right, and move down. Once we reach the node, we
+/// reassemble the tree with the node at the root, and the left and right trees
+/// as it's left and right subtrees.
+///
+/// Nodes are linked onto the right spine of the left tree (and the left spine
+/// of the right tree), so we just keep them in a stack each, and rebuild the
+/// spines from the bottom up at the end, updating the sizes as we go.
+///
+/// See: https://www.cs.cmu.edu/~sleator/papers/self-adjusting.pdf
+fn splay<K, V>(link: Link<K, V>, cmp: &impl Fn(&K) -> Ordering) -> Link<K, V> {
+    let mut n = link?;
+    let mut left_tree = Vec::new();
+    let mut right_tree = Vec::new();
+    loop {
+        match cmp(&n.key) {
+            Ordering::Equal => break,
+            Ordering::Less => {
+                let Some(mut left) = n.left.take() else {
+                    break;
+                };
+                if cmp(&left.key) == Ordering::Less {
+                    // zig-zig, rotate right
+                    n.left = left.right.take();
+                    n.update_size();
+                    left.right = Some(n);
+                    n = left;
+                    let Some(next) = n.left.take() else {
+                        break;
+                    };
+                    left = next;
+                }
+                // link right
+                right_tree.push(mem::replace(&mut n, left));
+            }
+            Ordering::Greater => {
+                let Some(mut right) = n.right.take() else {
+                    break;
+                };
+                if cmp(&right.key) == Ordering::Greater {
+                    // zig-zig, rotate left
+                    n.right = right.left.take();
+                    n.update_size();
+                    right.left = Some(n);
+                    n = right;
+                    let Some(next) = n.right.take() else {
+                        break;
+                    };
+                    right = next;
+                }
+                // link left
+                left_tree.push(mem::replace(&mut n, right));
+            }
+        }
+    }
+
+    let mut left = n.left.take();
+    while let Some(mut node) = left_tree.pop() {
+        node.right = left;
+        node.update_size();
+        left = Some(node);
+    }
+    let mut right = n.right.take();
+    while let Some(mut node) = right_tree.pop() {
+        node.left = right;
+        node.update_size();
+        right = Some(node);
+    }
+    n.left = left;
+    n.right = right;
+    n.update_size();
+    Some(n)
+}
+
+/// Join two subtrees, where all the keys in left are < all the keys in right,
+/// by splaying the max of left to the root (so it has no right child), and
+/// making right it's right child.
+fn join<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    let Some(mut root) = splay(left, &|_: &K| Ordering::Greater) else {
+        return right;
+    };
+    root.right = right;
+    root.update_size();
+    Some(root)
+}
+
+impl<K: KeyT, V> Drop for SplayTree<K, V> {
+    /// Drop the nodes iteratively, since the default recursive drop would
+    /// overflow the stack on a deep tree (e.g. the path left by sorted
+    /// inserts). While the root has a left child, we rotate it right, and once
+    /// it has none, we drop the root and move on to it's right child.
+    fn drop(&mut self) {
+        let mut link = self.root.take();
+        while let Some(mut node) = link {
+            link = match node.left.take() {
+                Some(mut left) => {
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    Some(left)
+                }
+                None => node.right.take(),
+            };
+        }
+    }
+}
+
+impl<K: KeyT, V> SymbolTable<K, V> for SplayTree<K, V> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// We splay the key to the root. If it's there, we update it's value.
+    /// Otherwise, the root is the key's predecessor or successor, so we add a
+    /// new root above it, moving the root's left subtree (for a successor) or
+    /// right subtree (for a predecessor) over to the new root. O(log n)
+    /// amortized.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        let mut node = Box::new(Node::new(key, val));
+        let Some(mut root) = splay(self.root.take(), &|k: &K| node.key.cmp(k)) else {
+            self.root = Some(node);
+            return Ok(());
+        };
+
+        match node.key.cmp(&root.key) {
+            Ordering::Equal => root.val = node.val,
+            Ordering::Less => {
+                node.left = root.left.take();
+                root.update_size();
+                node.right = Some(root);
+                node.update_size();
+                root = node;
+            }
+            Ordering::Greater => {
+                node.right = root.right.take();
+                root.update_size();
+                node.left = Some(root);
+                node.update_size();
+                root = node;
+            }
+        }
+        self.root = Some(root);
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self) -> usize {
+        size(&self.root)
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for SplayTree<K, V>
+where
+    K: Borrow<Q>,
+{
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    ///
+    /// ### Implementation
+    /// A plain BST search that does not splay, so this is O(height). Use
+    /// access to splay the key to the root.
+    fn get(&self, key: &Q) -> Option<&V> {
+        Some(&self.search(key)?.val)
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// We splay the key to the root, and replace the root with the join of it's
+    /// children. O(log n) amortized.
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        let mut root = splay(self.root.take(), &|k: &K| key.cmp(k.borrow()))?;
+        if root.key.borrow() != key {
+            self.root = Some(root);
+            return None;
+        }
+        self.root = join(root.left.take(), root.right.take());
+        Some(root.val)
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for SplayTree<K, V> {
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let mut floor = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    floor = Some(&node.key);
+                    link = &node.right;
+                }
+            }
+        }
+        floor
+    }
+
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let mut ceil = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => {
+                    ceil = Some(&node.key);
+                    link = &node.left;
+                }
+            }
+        }
+        ceil
+    }
+
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return rank + size(&node.left),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    rank += 1 + size(&node.left);
+                    link = &node.right;
+                }
+            }
+        }
+        rank
+    }
+
+    fn select(&self, mut rank: usize) -> Option<&K> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    rank -= left_size + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Remove the entry with the smallest key and return it (None if the tree
+    /// is empty).
+    ///
+    /// ### Implementation
+    /// We splay the min to the root, and replace the root with it's right
+    /// child (it has no left child).
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let mut root = splay(self.root.take(), &|_: &K| Ordering::Less)?;
+        self.root = root.right.take();
+        Some((root.key, root.val))
+    }
+
+    /// Remove the entry with the largest key and return it (None if the tree
+    /// is empty).
+    ///
+    /// ### Implementation
+    /// Same as pop_min, but with left and right swapped.
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        let mut root = splay(self.root.take(), &|_: &K| Ordering::Greater)?;
+        self.root = root.left.take();
+        Some((root.key, root.val))
+    }
+}
+
+/// An iterator over the entries of a SplayTree in sorted order (see
+/// BinarySearchTree's Iter for details).
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
+impl<'a, K: KeyT, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut SplayTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for SplayTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume the tree, iterating over the entries in sorted order.
+    ///
+    /// ### Implementation
+    /// The same walk as Drop, without recursion: while the root has a left
+    /// child we rotate it right, and once it has none, the root is the next
+    /// entry in sorted order, so we move it into the list and move on to it's
+    /// right child.
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut list = LinkedList::new();
+        let mut link = self.root.take();
+        while let Some(mut node) = link {
+            link = match node.left.take() {
+                Some(mut left) => {
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    Some(left)
+                }
+                None => {
+                    let Node {
+                        key, val, right, ..
+                    } = *node;
+                    list.push_back((key, val));
+                    right
+                }
+            };
+        }
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for SplayTree<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for SplayTree<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::symbol_table::tests::test_random_ops;
+
+    use super::*;
+
+    /// Checks that the tree is in symmetric order and that the subtree sizes
+    /// are consistent.
+    fn check_invariants<K: KeyT, V>(st: &SplayTree<K, V>) {
+        fn visit<K: KeyT, V>(link: &Link<K, V>, lo: Option<&K>, hi: Option<&K>) -> usize {
+            match link {
+                None => 0,
+                Some(node) => {
+                    assert!(lo.is_none_or(|lo| &node.key > lo), "keys out of order");
+                    assert!(hi.is_none_or(|hi| &node.key < hi), "keys out of order");
+                    let count = 1
+                        + visit(&node.left, lo, Some(&node.key))
+                        + visit(&node.right, Some(&node.key), hi);
+                    assert_eq!(node.size, count, "inconsistent subtree size");
+                    count
+                }
+            }
+        }
+
+        visit(&st.root, None, None);
+    }
+
+    fn depth<K: KeyT, V>(st: &SplayTree<K, V>, key: &K) -> usize {
+        let mut depth = 0;
+        let mut link = &st.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Equal => return depth,
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+            }
+            depth += 1;
+        }
+        panic!("key not found");
+    }
+
+    #[test]
+    fn test_splay_tree() {
+        let mut st = SplayTree::new();
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            st.put(key, i).unwrap();
+            check_invariants(&st);
+            assert_eq!(st.root.as_ref().unwrap().key, key);
+        }
+        assert_eq!(st.size(), 10);
+        assert_eq!(st.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(st.get(&'E'), Some(&12));
+        assert_eq!(st.get(&'Z'), None);
+
+        for key in "EXAMPLE".chars() {
+            st.pop(&key);
+            check_invariants(&st);
+        }
+        assert_eq!(st.keys().collect::<String>(), "CHRS");
+        assert_eq!(st.pop(&'E'), None);
+        check_invariants(&st);
+    }
+
+    #[test]
+    fn test_access() {
+        let mut st = SplayTree::new();
+        for i in 0..100 {
+            st.put(i, i).unwrap();
+        }
+        // sorted inserts leave a path, which a single access roughly halves
+        assert_eq!(depth(&st, &0), 99);
+        assert_eq!(st.access(&0), Some(&0));
+        check_invariants(&st);
+        assert_eq!(depth(&st, &0), 0);
+        assert!(depth(&st, &1) < 60);
+
+        // get does not splay
+        assert_eq!(st.get(&50), Some(&50));
+        assert_eq!(depth(&st, &0), 0);
+
+        *st.access_mut(&50).unwrap() = -50;
+        assert_eq!(depth(&st, &50), 0);
+        assert_eq!(st.get(&50), Some(&-50));
+
+        // a missing key splays it's neighbour instead
+        st.pop(&30);
+        assert_eq!(st.access(&30), None);
+        check_invariants(&st);
+        let root = &st.root.as_ref().unwrap().key;
+        assert!(*root == 29 || *root == 31);
+
+        // recently accessed keys stay near the root
+        for _ in 0..10 {
+            for key in [10, 20, 40] {
+                st.access(&key);
+            }
+        }
+        for key in [10, 20, 40] {
+            assert!(depth(&st, &key) <= 3);
+        }
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        // sorted inserts leave a path of a million nodes, which must not
+        // overflow the stack when iterated, splayed or dropped
+        const SIZE: i32 = 1_000_000;
+        let mut st = SplayTree::new();
+        for i in 0..SIZE {
+            st.put(i, i).unwrap();
+        }
+        for (key, val) in st.iter_mut() {
+            *val = -key;
+        }
+        assert!(st.iter().map(|(&key, &val)| key + val).all(|sum| sum == 0));
+        assert!(st.into_iter().map(|(key, _)| key).eq(0..SIZE));
+
+        let mut st = SplayTree::new();
+        for i in 0..SIZE {
+            st.put(i, i).unwrap();
+        }
+        assert_eq!(st.access(&0), Some(&0));
+        assert_eq!(st.access(&999_999), Some(&999_999));
+        assert_eq!(st.pop_min(), Some((0, 0)));
+        assert_eq!(st.size(), 999_999);
+    }
+
+    #[test]
+    fn test_against_btree_map() {
+        let mut st = SplayTree::new();
+        test_random_ops(&mut st, check_invariants);
+
+        // access splays too, so check it against what's left in the tree
+        let model: BTreeMap<i32, i32> = st.iter().map(|(&k, &v)| (k, v)).collect();
+        for key in -10..210 {
+            assert_eq!(st.access(&key), model.get(&key));
+            check_invariants(&st);
+        }
+        assert!(st.iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = SplayTree::new();
+        for key in [5, 1, 4, 2, 3] {
+            st.put(key, key * 10).unwrap();
+        }
+        assert_eq!(
+            st.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+            [5, 4, 3, 2, 1]
+        );
+        assert_eq!(st.range(2..4).map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+        for (_, val) in st.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(
+            st.into_iter().collect::<Vec<_>>(),
+            [(1, 11), (2, 21), (3, 31), (4, 41), (5, 51)]
+        );
+    }
+}
@@ -0,0 +1,772 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Deref, DerefMut, RangeBounds},
+};
+
+use crate::{
+    list::LinkedList,
+    random::random_u64,
+    stack::{Stack, StackLinkedList},
+};
+
+use super::{
+    is_after_start, is_before_end,
+    iter::{Keys, ListIter, Values},
+    KeyT, Lookup, OrderedSymbolTable, SymbolTable,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+pub struct Node<K, V> {
+    key: K,
+    val: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    priority: u64,
+    size: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, val: V) -> Self {
+        Node {
+            key,
+            val,
+            left: None,
+            right: None,
+            priority: random_u64(),
+            size: 1,
+        }
+    }
+
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Number of nodes in the subtree (0 for a null link).
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// A treap (tree + heap) is a binary search tree where each node is also given
+/// a random priority, and the nodes are kept in heap order by priority (i.e.
+/// the priority of a node is >= the priorities of it's children).
+///
+/// For a given set of keys and priorities there is exactly one treap, which is
+/// the same tree we would get by inserting the keys into a plain
+/// BinarySearchTree in decreasing order of priority. Since the priorities are
+/// random, that's a random insertion order, so the tree has O(log n) expected
+/// height no matter what order the keys are actually inserted in.
+///
+/// ### Implementation
+/// All the updates are built on two operations:
+///
+///   - split: splits a treap into the treap of keys < a given key and the
+///     treap of keys >= the given key
+///   - join: joins two treaps, where all the keys in the first are < all
+///     the keys in the second
+///
+/// Both walk down a single path of the tree, so they are O(log n) expected.
+/// Insert walks down the tree until it reaches a node with a lower priority
+/// than the new node, and replaces that subtree with the new node, with the
+/// subtree split by the new key as it's children. Delete replaces the node
+/// with the join of it's children.
+///
+/// Each node also stores the number of nodes in it's subtree, for rank and
+/// select (see BinarySearchTree).
+///
+/// See: https://en.wikipedia.org/wiki/Treap
+pub struct Treap<K: KeyT, V> {
+    root: Link<K, V>,
+}
+
+impl<K: KeyT, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyT, V> Treap<K, V> {
+    pub fn new() -> Self {
+        Treap { root: None }
+    }
+
+    /// Split the treap into the treap of keys < the given key and the treap of
+    /// keys >= the given key. O(log n) expected.
+    pub fn split<Q: ?Sized + Ord>(mut self, key: &Q) -> (Self, Self)
+    where
+        K: Borrow<Q>,
+    {
+        let (left, right) = split(self.root.take(), key);
+        (Treap { root: left }, Treap { root: right })
+    }
+
+    /// Join two treaps, where all the keys in this treap must be < all the keys
+    /// in the other treap (otherwise returns an Err with both treaps, so they
+    /// aren't lost). O(log n) expected.
+    pub fn join(mut self, mut other: Self) -> Result<Self, (Self, Self)> {
+        if let (Some(max), Some(min)) = (self.max(), other.min()) {
+            if max >= min {
+                return Err((self, other));
+            }
+        }
+        Ok(Treap {
+            root: join(self.root.take(), other.root.take()),
+        })
+    }
+
+    /// Iterate over the entries in sorted order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Iterate over the entries in sorted order, with mutable references to
+    /// the values (see BinarySearchTree::iter_mut).
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        fn visit<'a, K: KeyT, V>(
+            link: &'a mut Link<K, V>,
+            list: &mut LinkedList<(&'a K, &'a mut V)>,
+        ) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = node.deref_mut();
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(&mut self.root, &mut list);
+        ListIter::new(list)
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(&self) -> Keys<Iter<'_, K, V>> {
+        Keys::new(self.iter())
+    }
+
+    /// Iterate over the values in the sorted order of their keys.
+    pub fn values(&self) -> Values<Iter<'_, K, V>> {
+        Values::new(self.iter())
+    }
+
+    /// Iterate over the entries whose keys fall in the given range (e.g.
+    /// `lo..hi`, `lo..=hi`, `lo..`) in sorted order (see
+    /// BinarySearchTree::range).
+    pub fn range<Q: ?Sized + Ord, R: RangeBounds<Q>>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let mut iter = Iter {
+            front: StackLinkedList::new(),
+            back: StackLinkedList::new(),
+            remaining: self.range_count((start, end)),
+        };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_after_start(&node.key, start) {
+                iter.front.push(node.deref()).unwrap();
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if is_before_end(&node.key, end) {
+                iter.back.push(node.deref()).unwrap();
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        iter
+    }
+
+    fn search<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&Node<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(node),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    fn search_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(&mut node.val),
+                Ordering::Less => link = &mut node.left,
+                Ordering::Greater => link = &mut node.right,
+            }
+        }
+        None
+    }
+}
+
+/// Split the subtree into the subtree of keys < the given key and the subtree
+/// of keys >= the given key.
+///
+/// If the root is < the key, then it and it's left subtree belong to the
+/// first half, and we split it's right subtree, keeping the lower half as it's
+/// right child. Otherwise, it's the mirror image. The heap order is preserved
+/// since each node only ever gets a descendant as a child.
+fn split<K: Borrow<Q>, Q: ?Sized + Ord, V>(link: Link<K, V>, key: &Q) -> (Link<K, V>, Link<K, V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.key.borrow() < key {
+                let right;
+                (node.right, right) = split(node.right.take(), key);
+                node.update_size();
+                (Some(node), right)
+            } else {
+                let left;
+                (left, node.left) = split(node.left.take(), key);
+                node.update_size();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+/// Join two subtrees, where all the keys in left are < all the keys in right.
+///
+/// The root with the higher priority becomes the root of the joined subtree,
+/// and we join the other subtree with it's inner child (the right child of
+/// left's root, or the left child of right's root).
+fn join<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority >= right.priority {
+                left.right = join(left.right.take(), Some(right));
+                left.update_size();
+                Some(left)
+            } else {
+                right.left = join(Some(left), right.left.take());
+                right.update_size();
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Insert the node (whose key must not be in the subtree) into the subtree.
+fn insert<K: KeyT, V>(link: Link<K, V>, mut node_to_insert: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    match link {
+        None => node_to_insert,
+        Some(mut node) => {
+            if node_to_insert.priority > node.priority {
+                let key = &node_to_insert.key;
+                (node_to_insert.left, node_to_insert.right) = split(Some(node), key);
+                node_to_insert.update_size();
+                node_to_insert
+            } else {
+                if node_to_insert.key < node.key {
+                    node.left = Some(insert(node.left.take(), node_to_insert));
+                } else {
+                    node.right = Some(insert(node.right.take(), node_to_insert));
+                }
+                node.update_size();
+                node
+            }
+        }
+    }
+}
+
+/// Remove the node with the given key from the subtree, replacing it with the
+/// join of it's children, and return it.
+fn delete<K: Borrow<Q>, Q: ?Sized + Ord, V>(
+    link: &mut Link<K, V>,
+    key: &Q,
+) -> Option<Box<Node<K, V>>> {
+    let node = link.as_mut()?;
+    let deleted = match key.cmp(node.key.borrow()) {
+        Ordering::Less => delete(&mut node.left, key),
+        Ordering::Greater => delete(&mut node.right, key),
+        Ordering::Equal => {
+            let mut deleted = link.take().unwrap();
+            *link = join(deleted.left.take(), deleted.right.take());
+            return Some(deleted);
+        }
+    };
+    if deleted.is_some() {
+        node.size -= 1;
+    }
+    deleted
+}
+
+/// Removes the leftmost node in the subtree, replacing it with it's right
+/// subtree (which keeps the heap order), and returns it.
+fn delete_min<K, V>(link: &mut Link<K, V>) -> Option<Box<Node<K, V>>> {
+    let node = link.as_mut()?;
+    if node.left.is_none() {
+        let mut min = link.take().unwrap();
+        *link = min.right.take();
+        return Some(min);
+    }
+    node.size -= 1;
+    delete_min(&mut node.left)
+}
+
+/// Removes the rightmost node in the subtree, replacing it with it's left
+/// subtree (which keeps the heap order), and returns it.
+fn delete_max<K, V>(link: &mut Link<K, V>) -> Option<Box<Node<K, V>>> {
+    let node = link.as_mut()?;
+    if node.right.is_none() {
+        let mut max = link.take().unwrap();
+        *link = max.left.take();
+        return Some(max);
+    }
+    node.size -= 1;
+    delete_max(&mut node.right)
+}
+
+impl<K: KeyT, V> SymbolTable<K, V> for Treap<K, V> {
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// If the key exists we update it's value. Otherwise, we walk down the tree
+    /// until we reach a node with a lower priority than the new node (or a null
+    /// link), and replace it with the new node, whose children are that subtree
+    /// split by the new key. O(log n) expected.
+    fn put(&mut self, key: K, val: V) -> Result<(), String> {
+        if let Some(old_val) = self.search_mut(&key) {
+            *old_val = val;
+            return Ok(());
+        }
+        self.root = Some(insert(self.root.take(), Box::new(Node::new(key, val))));
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn size(&self) -> usize {
+        size(&self.root)
+    }
+}
+
+impl<K: KeyT, V, Q: ?Sized + Ord> Lookup<Q, V> for Treap<K, V>
+where
+    K: Borrow<Q>,
+{
+    fn get(&self, key: &Q) -> Option<&V> {
+        Some(&self.search(key)?.val)
+    }
+
+    /// Remove the entry that matches the key, and return it's value (return
+    /// None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// We replace the matching node with the join of it's children. O(log n)
+    /// expected.
+    fn pop(&mut self, key: &Q) -> Option<V> {
+        Some(delete(&mut self.root, key)?.val)
+    }
+}
+
+impl<K: KeyT, V> OrderedSymbolTable<K, V> for Treap<K, V> {
+    fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    fn floor<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let mut floor = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    floor = Some(&node.key);
+                    link = &node.right;
+                }
+            }
+        }
+        floor
+    }
+
+    fn ceil<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        let mut ceil = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Greater => link = &node.right,
+                Ordering::Less => {
+                    ceil = Some(&node.key);
+                    link = &node.left;
+                }
+            }
+        }
+        ceil
+    }
+
+    fn rank<Q: ?Sized + Ord>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => return rank + size(&node.left),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    rank += 1 + size(&node.left);
+                    link = &node.right;
+                }
+            }
+        }
+        rank
+    }
+
+    fn select(&self, mut rank: usize) -> Option<&K> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => {
+                    rank -= left_size + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let min = delete_min(&mut self.root)?;
+        Some((min.key, min.val))
+    }
+
+    fn pop_max(&mut self) -> Option<(K, V)> {
+        let max = delete_max(&mut self.root)?;
+        Some((max.key, max.val))
+    }
+}
+
+/// An iterator over the entries of a Treap in sorted order (see
+/// BinarySearchTree's Iter for details).
+pub struct Iter<'a, K: KeyT, V> {
+    front: StackLinkedList<&'a Node<K, V>>,
+    back: StackLinkedList<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: KeyT, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.right;
+        while let Some(next) = link {
+            self.front.push(next.deref()).unwrap();
+            link = &next.left;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+impl<K: KeyT, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.remaining -= 1;
+
+        let mut link = &node.left;
+        while let Some(next) = link {
+            self.back.push(next.deref()).unwrap();
+            link = &next.right;
+        }
+
+        Some((&node.key, &node.val))
+    }
+}
+
+pub type IterMut<'a, K, V> = ListIter<(&'a K, &'a mut V)>;
+
+pub type IntoIter<K, V> = ListIter<(K, V)>;
+
+impl<'a, K: KeyT, V> IntoIterator for &'a Treap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: KeyT, V> IntoIterator for &'a mut Treap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: KeyT, V> IntoIterator for Treap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        fn visit<K: KeyT, V>(link: Link<K, V>, list: &mut LinkedList<(K, V)>) {
+            if let Some(node) = link {
+                let Node {
+                    key,
+                    val,
+                    left,
+                    right,
+                    ..
+                } = *node;
+                visit(left, list);
+                list.push_back((key, val));
+                visit(right, list);
+            }
+        }
+
+        let mut list = LinkedList::new();
+        visit(self.root, &mut list);
+        ListIter::new(list)
+    }
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<K: KeyT + Serialize, V: Serialize> Serialize for Treap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(serializer, self.size(), self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: KeyT + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for Treap<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::default(), |st, (key, val)| {
+            st.put(key, val)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{random::shuffle_arr, symbol_table::tests::test_random_ops};
+
+    use super::*;
+
+    /// Checks that the treap is in symmetric order by key, in heap order by
+    /// priority, and that the subtree sizes are consistent.
+    fn check_invariants<K: KeyT, V>(st: &Treap<K, V>) {
+        fn visit<K: KeyT, V>(
+            link: &Link<K, V>,
+            lo: Option<&K>,
+            hi: Option<&K>,
+            max_priority: u64,
+        ) -> usize {
+            match link {
+                None => 0,
+                Some(node) => {
+                    assert!(lo.is_none_or(|lo| &node.key > lo), "keys out of order");
+                    assert!(hi.is_none_or(|hi| &node.key < hi), "keys out of order");
+                    assert!(node.priority <= max_priority, "priorities out of order");
+                    let count = 1
+                        + visit(&node.left, lo, Some(&node.key), node.priority)
+                        + visit(&node.right, Some(&node.key), hi, node.priority);
+                    assert_eq!(node.size, count, "inconsistent subtree size");
+                    count
+                }
+            }
+        }
+
+        visit(&st.root, None, None, u64::MAX);
+    }
+
+    fn height<K, V>(link: &Link<K, V>) -> usize {
+        link.as_ref()
+            .map_or(0, |node| 1 + height(&node.left).max(height(&node.right)))
+    }
+
+    #[test]
+    fn test_treap() {
+        let mut st = Treap::new();
+        for (i, key) in "SEARCHEXAMPLE".chars().enumerate() {
+            st.put(key, i).unwrap();
+            check_invariants(&st);
+        }
+        assert_eq!(st.size(), 10);
+        assert_eq!(st.keys().collect::<String>(), "ACEHLMPRSX");
+        assert_eq!(st.get(&'E'), Some(&12));
+        assert_eq!(st.get(&'Z'), None);
+
+        for key in "EXAMPLE".chars() {
+            st.pop(&key);
+            check_invariants(&st);
+        }
+        assert_eq!(st.keys().collect::<String>(), "CHRS");
+        assert_eq!(st.pop(&'E'), None);
+    }
+
+    #[test]
+    fn test_sorted_inserts() {
+        // a plain BST would degenerate into a linked list of height 1000
+        let mut st = Treap::new();
+        for i in 0..1000 {
+            st.put(i, i).unwrap();
+        }
+        check_invariants(&st);
+        assert!(height(&st.root) < 50, "height {}", height(&st.root));
+    }
+
+    #[test]
+    fn test_split_join() {
+        let mut keys: Vec<i32> = (0..100).collect();
+        shuffle_arr(&mut keys);
+        let mut st = Treap::new();
+        for &key in keys.iter() {
+            st.put(key, -key).unwrap();
+        }
+
+        let (left, right) = st.split(&40);
+        check_invariants(&left);
+        check_invariants(&right);
+        assert_eq!(
+            left.keys().copied().collect::<Vec<_>>(),
+            (0..40).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            right.keys().copied().collect::<Vec<_>>(),
+            (40..100).collect::<Vec<_>>()
+        );
+
+        let (left, empty) = left.split(&1000);
+        assert!(empty.is_empty());
+        let (empty, left) = left.split(&-1);
+        assert!(empty.is_empty());
+        assert_eq!(left.size(), 40);
+
+        let mut other = Treap::new();
+        other.put(39, 0).unwrap();
+        assert!(other.join(right).is_ok());
+        let mut other = Treap::new();
+        other.put(39, 0).unwrap();
+        let Err((left, other)) = left.join(other) else {
+            panic!("expected keys to overlap");
+        };
+        check_invariants(&left);
+        assert_eq!(
+            left.keys().copied().collect::<Vec<_>>(),
+            (0..40).collect::<Vec<_>>()
+        );
+        assert_eq!(other.get(&39), Some(&0));
+        assert_eq!(other.size(), 1);
+        let Err((other, left)) = other.join(left) else {
+            panic!("expected keys to overlap");
+        };
+        assert_eq!(left.size(), 40);
+        assert_eq!(other.size(), 1);
+
+        let mut keys: Vec<i32> = (0..40).collect();
+        shuffle_arr(&mut keys);
+        let mut st = Treap::new();
+        for &key in keys.iter() {
+            st.put(key, -key).unwrap();
+        }
+        let Ok(st) = Treap::new().join(st) else {
+            panic!("expected join to succeed");
+        };
+        let (left, right) = st.split(&20);
+        let Ok(st) = left.join(right) else {
+            panic!("expected join to succeed");
+        };
+        check_invariants(&st);
+        assert_eq!(st.size(), 40);
+        assert_eq!(st.rank(&20), 20);
+        assert_eq!(st.select(39), Some(&39));
+    }
+
+    #[test]
+    fn test_against_btree_map() {
+        test_random_ops(&mut Treap::new(), check_invariants);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut st = Treap::new();
+        for key in [5, 1, 4, 2, 3] {
+            st.put(key, key * 10).unwrap();
+        }
+        assert_eq!(
+            st.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+            [5, 4, 3, 2, 1]
+        );
+        assert_eq!(st.range(2..4).map(|(k, _)| *k).collect::<Vec<_>>(), [2, 3]);
+        for (_, val) in st.iter_mut() {
+            *val += 1;
+        }
+        assert_eq!(
+            st.into_iter().collect::<Vec<_>>(),
+            [(1, 11), (2, 21), (3, 31), (4, 41), (5, 51)]
+        );
+    }
+}
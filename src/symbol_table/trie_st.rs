@@ -0,0 +1,353 @@
+use crate::list::LinkedList;
+
+use super::iter::ListIter;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+/// The alphabet size, keys are treated as sequences of bytes (so extended
+/// ASCII characters).
+const R: usize = 256;
+
+type Link<V> = Option<Box<Node<V>>>;
+
+struct Node<V> {
+    val: Option<V>,
+    next: [Link<V>; R],
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Node {
+            val: None,
+            next: std::array::from_fn(|_| None),
+        }
+    }
+}
+
+/// An R-way trie symbol table with string keys.
+///
+/// Each node has R links, one for each possible next character, and the key
+/// for a node is given by the characters on the path from the root to that
+/// node (the keys are not stored explicitly). A node stores a value if it's key
+/// is in the table. Search and insert are O(length of the key), independent of
+/// the number of keys, and a search miss usually only looks at a few
+/// characters.
+///
+/// Since the keys are spelled out by paths from the root, the keys that
+/// start with a given prefix all live in the subtrie for that prefix, which
+/// gives us prefix operations like keys_with_prefix (e.g. for autocomplete).
+///
+/// The drawback is memory, each node has R links, most of which are null. See
+/// TST for a more space efficient alternative.
+///
+/// ### Implementation
+/// Keys are treated as sequences of bytes, with R = 256. Non-ASCII characters
+/// are stored as multiple bytes (so they take up multiple levels of the trie),
+/// and the `.` wildcard in keys_that_match matches a single byte.
+///
+/// See: https://algs4.cs.princeton.edu/52trie/
+pub struct TrieST<V> {
+    root: Link<V>,
+    size: usize,
+}
+
+impl<V> Default for TrieST<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> TrieST<V> {
+    pub fn new() -> Self {
+        TrieST {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// Follow the links for each character of the key, creating any missing
+    /// nodes along the way, and set the value of the last node.
+    pub fn put(&mut self, key: &str, val: V) {
+        let mut link = &mut self.root;
+        for &c in key.as_bytes() {
+            link = &mut link.get_or_insert_with(|| Box::new(Node::new())).next[c as usize];
+        }
+        let node = link.get_or_insert_with(|| Box::new(Node::new()));
+        if node.val.replace(val).is_none() {
+            self.size += 1;
+        }
+    }
+
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.search(key.as_bytes())?.val.as_ref()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the key and return it's value (None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// Clear the value of the node for the key, and then on the way back up,
+    /// remove any nodes that no longer have a value or any children.
+    pub fn pop(&mut self, key: &str) -> Option<V> {
+        fn delete<V>(link: &mut Link<V>, key: &[u8]) -> Option<V> {
+            let node = link.as_mut()?;
+            let val = match key.split_first() {
+                None => node.val.take(),
+                Some((&c, rest)) => delete(&mut node.next[c as usize], rest),
+            };
+            if node.val.is_none() && node.next.iter().all(Option::is_none) {
+                *link = None;
+            }
+            val
+        }
+
+        let val = delete(&mut self.root, key.as_bytes())?;
+        self.size -= 1;
+        Some(val)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Iterate over all the keys in sorted order.
+    pub fn keys(&self) -> ListIter<String> {
+        self.keys_with_prefix("")
+    }
+
+    /// Iterate over the keys that start with the given prefix in sorted order.
+    ///
+    /// ### Implementation
+    /// Find the node for the prefix, and collect the keys in it's subtrie, by
+    /// visiting the children in order of their characters. This is O(number of
+    /// nodes in the subtrie) (which is proportional to the total length of the
+    /// matching keys, times R).
+    pub fn keys_with_prefix(&self, prefix: &str) -> ListIter<String> {
+        let mut keys = LinkedList::new();
+        if let Some(node) = self.search(prefix.as_bytes()) {
+            collect(node, &mut prefix.as_bytes().to_vec(), &mut keys);
+        }
+        ListIter::new(keys)
+    }
+
+    /// Iterate over the keys that match the given pattern in sorted order,
+    /// where `.` in the pattern matches any single byte (so a non-ASCII
+    /// character, which is several bytes in UTF-8, needs one `.` per byte).
+    ///
+    /// ### Implementation
+    /// Same as keys_with_prefix, but at each level, we follow the link for the
+    /// next character of the pattern (or all of the links for a `.`), and only
+    /// collect keys with the same length as the pattern.
+    pub fn keys_that_match(&self, pattern: &str) -> ListIter<String> {
+        fn visit<V>(
+            link: &Link<V>,
+            prefix: &mut Vec<u8>,
+            pattern: &[u8],
+            keys: &mut LinkedList<String>,
+        ) {
+            let Some(node) = link else {
+                return;
+            };
+            let Some(&c) = pattern.get(prefix.len()) else {
+                if node.val.is_some() {
+                    keys.push_back(to_string(prefix));
+                }
+                return;
+            };
+            let chars = if c == b'.' {
+                0..R
+            } else {
+                c as usize..c as usize + 1
+            };
+            for next in chars {
+                prefix.push(next as u8);
+                visit(&node.next[next], prefix, pattern, keys);
+                prefix.pop();
+            }
+        }
+
+        let mut keys = LinkedList::new();
+        visit(&self.root, &mut Vec::new(), pattern.as_bytes(), &mut keys);
+        ListIter::new(keys)
+    }
+
+    /// Get the longest key in the table that is a prefix of the query (None if
+    /// no such key exists).
+    ///
+    /// ### Implementation
+    /// Follow the links for the query, keeping track of the length of the
+    /// last node we passed that has a value.
+    pub fn longest_prefix_of<'q>(&self, query: &'q str) -> Option<&'q str> {
+        let mut longest = None;
+        let mut link = &self.root;
+        for (d, &c) in query.as_bytes().iter().enumerate() {
+            let Some(node) = link else {
+                break;
+            };
+            if node.val.is_some() {
+                longest = Some(d);
+            }
+            link = &node.next[c as usize];
+        }
+        if link.as_ref().is_some_and(|node| node.val.is_some()) {
+            longest = Some(query.len());
+        }
+        longest.map(|len| &query[..len])
+    }
+
+    /// The node for the given key (None if there is no such node).
+    fn search(&self, key: &[u8]) -> Option<&Node<V>> {
+        let mut node = self.root.as_ref()?;
+        for &c in key {
+            node = node.next[c as usize].as_ref()?;
+        }
+        Some(node)
+    }
+}
+
+/// Collect the keys in the subtrie rooted at node (whose key is prefix) in
+/// sorted order.
+fn collect<V>(node: &Node<V>, prefix: &mut Vec<u8>, keys: &mut LinkedList<String>) {
+    if node.val.is_some() {
+        keys.push_back(to_string(prefix));
+    }
+    for (c, next) in node.next.iter().enumerate() {
+        if let Some(next) = next {
+            prefix.push(c as u8);
+            collect(next, prefix, keys);
+            prefix.pop();
+        }
+    }
+}
+
+/// The bytes of a key in the table, which are always valid UTF-8 since the
+/// keys were given as strings.
+fn to_string(key: &[u8]) -> String {
+    String::from_utf8(key.to_vec()).unwrap()
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<V: Serialize> Serialize for TrieST<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size(),
+            self.keys().map(|key| {
+                let val = self.get(&key).unwrap();
+                (key, val)
+            }),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for TrieST<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |st, (key, val): (String, V)| {
+            st.put(&key, val);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shells() -> TrieST<usize> {
+        let mut st = TrieST::new();
+        for (i, key) in "she sells sea shells by the sea shore"
+            .split(' ')
+            .enumerate()
+        {
+            st.put(key, i);
+        }
+        st
+    }
+
+    #[test]
+    fn test_trie_st() {
+        let mut st = shells();
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get("sea"), Some(&6));
+        assert_eq!(st.get("shells"), Some(&3));
+        assert_eq!(st.get("shell"), None);
+        assert_eq!(st.get("s"), None);
+        assert_eq!(st.get("shellsort"), None);
+        assert!(st.contains("by"));
+        assert_eq!(
+            st.keys().collect::<Vec<_>>(),
+            ["by", "sea", "sells", "she", "shells", "shore", "the"]
+        );
+
+        assert_eq!(st.pop("shells"), Some(3));
+        assert_eq!(st.pop("shells"), None);
+        assert_eq!(st.pop("shell"), None);
+        assert_eq!(st.get("she"), Some(&0));
+        assert_eq!(st.size(), 6);
+
+        // popping everything prunes the whole trie
+        for key in ["by", "sea", "sells", "she", "shore", "the"] {
+            assert!(st.pop(key).is_some());
+        }
+        assert!(st.is_empty());
+        assert!(st.root.is_none());
+
+        // the empty string and non-ASCII keys are allowed
+        st.put("", 1);
+        st.put("héllo", 2);
+        assert_eq!(st.get(""), Some(&1));
+        assert_eq!(st.get("héllo"), Some(&2));
+        assert_eq!(st.keys().collect::<Vec<_>>(), ["", "héllo"]);
+    }
+
+    #[test]
+    fn test_prefix_operations() {
+        let st = shells();
+        assert_eq!(
+            st.keys_with_prefix("sh").collect::<Vec<_>>(),
+            ["she", "shells", "shore"]
+        );
+        assert_eq!(
+            st.keys_with_prefix("she").collect::<Vec<_>>(),
+            ["she", "shells"]
+        );
+        assert_eq!(st.keys_with_prefix("x").count(), 0);
+
+        assert_eq!(
+            st.keys_that_match("s..").collect::<Vec<_>>(),
+            ["sea", "she"]
+        );
+        assert_eq!(
+            st.keys_that_match(".he").collect::<Vec<_>>(),
+            ["she", "the"]
+        );
+        assert_eq!(st.keys_that_match("......").collect::<Vec<_>>(), ["shells"]);
+        assert_eq!(st.keys_that_match("s.").count(), 0);
+        assert_eq!(st.keys_that_match("").count(), 0);
+
+        assert_eq!(st.longest_prefix_of("shellsort"), Some("shells"));
+        assert_eq!(st.longest_prefix_of("shelters"), Some("she"));
+        assert_eq!(st.longest_prefix_of("she"), Some("she"));
+        assert_eq!(st.longest_prefix_of("quicksort"), None);
+        assert_eq!(st.longest_prefix_of(""), None);
+    }
+}
@@ -0,0 +1,425 @@
+use std::cmp::Ordering;
+
+use crate::list::LinkedList;
+
+use super::iter::ListIter;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::utils::{deserialize_seq, serialize_seq};
+
+type Link<V> = Option<Box<Node<V>>>;
+
+struct Node<V> {
+    c: u8,
+    val: Option<V>,
+    left: Link<V>,
+    mid: Link<V>,
+    right: Link<V>,
+}
+
+impl<V> Node<V> {
+    fn new(c: u8) -> Self {
+        Node {
+            c,
+            val: None,
+            left: None,
+            mid: None,
+            right: None,
+        }
+    }
+}
+
+/// A ternary search trie (TST) symbol table with string keys.
+///
+/// Like TrieST, but each node stores a single character and has three links:
+/// left for keys whose next character is smaller, mid for keys whose next
+/// character matches (i.e. moving on to the next character of the key), and
+/// right for keys whose next character is larger. Each level of the trie is
+/// essentially a BST of the characters that can follow the prefix, so we
+/// avoid the R links per node of an R-way trie, and use space proportional to
+/// the total length of the keys.
+///
+/// Search and insert are O(length of the key + log n) for random keys, and
+/// TSTs support the same prefix operations as TrieST.
+///
+/// ### Implementation
+/// Keys are treated as sequences of bytes (see TrieST). Since every node
+/// stores a character, the empty key has no node, so we store it's value
+/// separately.
+///
+/// See: https://algs4.cs.princeton.edu/52trie/
+pub struct TST<V> {
+    root: Link<V>,
+    empty: Option<V>,
+    size: usize,
+}
+
+impl<V> Default for TST<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> TST<V> {
+    pub fn new() -> Self {
+        TST {
+            root: None,
+            empty: None,
+            size: 0,
+        }
+    }
+
+    /// Add (or update) the key-value pair.
+    ///
+    /// ### Implementation
+    /// For each character of the key, we search the BST at the current level
+    /// for the character (creating a node for it if it's missing), and then
+    /// move to it's mid link. We set the value of the node for the last
+    /// character.
+    pub fn put(&mut self, key: &str, val: V) {
+        let old_val = match key.as_bytes().split_last() {
+            None => self.empty.replace(val),
+            Some((&last, init)) => {
+                let mut link = &mut self.root;
+                for &c in init {
+                    link = &mut Self::find_or_insert(link, c).mid;
+                }
+                Self::find_or_insert(link, last).val.replace(val)
+            }
+        };
+        if old_val.is_none() {
+            self.size += 1;
+        }
+    }
+
+    /// Get a reference to the value for the associated key (None if the key
+    /// does not exist).
+    pub fn get(&self, key: &str) -> Option<&V> {
+        if key.is_empty() {
+            return self.empty.as_ref();
+        }
+        self.search(key.as_bytes())?.val.as_ref()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the key and return it's value (None if the key does not exist).
+    ///
+    /// ### Implementation
+    /// Clear the value of the node for the key, and then on the way back up,
+    /// remove any nodes that no longer have a value or a mid link, as long as
+    /// they have at most one other child to take their place.
+    pub fn pop(&mut self, key: &str) -> Option<V> {
+        fn delete<V>(link: &mut Link<V>, key: &[u8]) -> Option<V> {
+            let node = link.as_mut()?;
+            let val = match key[0].cmp(&node.c) {
+                Ordering::Less => delete(&mut node.left, key),
+                Ordering::Greater => delete(&mut node.right, key),
+                Ordering::Equal if key.len() == 1 => node.val.take(),
+                Ordering::Equal => delete(&mut node.mid, &key[1..]),
+            };
+            if node.val.is_none() && node.mid.is_none() {
+                match (node.left.take(), node.right.take()) {
+                    (None, child) | (child, None) => *link = child,
+                    (left, right) => (node.left, node.right) = (left, right),
+                }
+            }
+            val
+        }
+
+        let val = if key.is_empty() {
+            self.empty.take()?
+        } else {
+            delete(&mut self.root, key.as_bytes())?
+        };
+        self.size -= 1;
+        Some(val)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Iterate over all the keys in sorted order.
+    pub fn keys(&self) -> ListIter<String> {
+        self.keys_with_prefix("")
+    }
+
+    /// Iterate over the keys that start with the given prefix in sorted order.
+    ///
+    /// ### Implementation
+    /// Find the node for the prefix, and collect the keys in the subtrie rooted
+    /// at it's mid link with an in-order traversal.
+    pub fn keys_with_prefix(&self, prefix: &str) -> ListIter<String> {
+        let mut keys = LinkedList::new();
+        if prefix.is_empty() {
+            if self.empty.is_some() {
+                keys.push_back(String::new());
+            }
+            collect(&self.root, &mut Vec::new(), &mut keys);
+        } else if let Some(node) = self.search(prefix.as_bytes()) {
+            if node.val.is_some() {
+                keys.push_back(prefix.to_string());
+            }
+            collect(&node.mid, &mut prefix.as_bytes().to_vec(), &mut keys);
+        }
+        ListIter::new(keys)
+    }
+
+    /// Iterate over the keys that match the given pattern in sorted order,
+    /// where `.` in the pattern matches any single byte (so a non-ASCII
+    /// character, which is several bytes in UTF-8, needs one `.` per byte).
+    ///
+    /// ### Implementation
+    /// At each node, if the next character of the pattern is a `.` we explore
+    /// all three links, otherwise we only follow the link that the character
+    /// would take. We only collect keys with the same length as the pattern.
+    pub fn keys_that_match(&self, pattern: &str) -> ListIter<String> {
+        fn visit<V>(
+            link: &Link<V>,
+            prefix: &mut Vec<u8>,
+            pattern: &[u8],
+            keys: &mut LinkedList<String>,
+        ) {
+            let Some(node) = link else {
+                return;
+            };
+            let c = pattern[prefix.len()];
+            if c == b'.' || c < node.c {
+                visit(&node.left, prefix, pattern, keys);
+            }
+            if c == b'.' || c == node.c {
+                prefix.push(node.c);
+                if prefix.len() == pattern.len() {
+                    if node.val.is_some() {
+                        keys.push_back(to_string(prefix));
+                    }
+                } else {
+                    visit(&node.mid, prefix, pattern, keys);
+                }
+                prefix.pop();
+            }
+            if c == b'.' || c > node.c {
+                visit(&node.right, prefix, pattern, keys);
+            }
+        }
+
+        let mut keys = LinkedList::new();
+        if pattern.is_empty() {
+            if self.empty.is_some() {
+                keys.push_back(String::new());
+            }
+        } else {
+            visit(&self.root, &mut Vec::new(), pattern.as_bytes(), &mut keys);
+        }
+        ListIter::new(keys)
+    }
+
+    /// Get the longest key in the table that is a prefix of the query (None if
+    /// no such key exists).
+    ///
+    /// ### Implementation
+    /// Search for the query, keeping track of the length of the last key with
+    /// a value that we matched along the way.
+    pub fn longest_prefix_of<'q>(&self, query: &'q str) -> Option<&'q str> {
+        let query_bytes = query.as_bytes();
+        let mut longest = self.empty.as_ref().map(|_| 0);
+        let mut link = &self.root;
+        let mut d = 0;
+        while let (Some(node), Some(&c)) = (link, query_bytes.get(d)) {
+            match c.cmp(&node.c) {
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => {
+                    d += 1;
+                    if node.val.is_some() {
+                        longest = Some(d);
+                    }
+                    link = &node.mid;
+                }
+            }
+        }
+        longest.map(|len| &query[..len])
+    }
+
+    /// The node for the last character of the given (non empty) key (None if
+    /// there is no such node).
+    fn search(&self, key: &[u8]) -> Option<&Node<V>> {
+        let mut node = self.root.as_ref()?;
+        let mut d = 0;
+        loop {
+            node = match key[d].cmp(&node.c) {
+                Ordering::Less => node.left.as_ref()?,
+                Ordering::Greater => node.right.as_ref()?,
+                Ordering::Equal if d == key.len() - 1 => return Some(node),
+                Ordering::Equal => {
+                    d += 1;
+                    node.mid.as_ref()?
+                }
+            };
+        }
+    }
+
+    /// Find the node for the character in the BST rooted at link, creating a
+    /// node for it if it's missing.
+    fn find_or_insert(mut link: &mut Link<V>, c: u8) -> &mut Node<V> {
+        loop {
+            let node = link.get_or_insert_with(|| Box::new(Node::new(c)));
+            link = match c.cmp(&node.c) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return node,
+            };
+        }
+    }
+}
+
+/// Collect the keys in the subtrie rooted at link (whose key is prefix) in
+/// sorted order.
+fn collect<V>(link: &Link<V>, prefix: &mut Vec<u8>, keys: &mut LinkedList<String>) {
+    let Some(node) = link else {
+        return;
+    };
+    collect(&node.left, prefix, keys);
+    prefix.push(node.c);
+    if node.val.is_some() {
+        keys.push_back(to_string(prefix));
+    }
+    collect(&node.mid, prefix, keys);
+    prefix.pop();
+    collect(&node.right, prefix, keys);
+}
+
+/// The bytes of a key in the table, which are always valid UTF-8 since the
+/// keys were given as strings.
+fn to_string(key: &[u8]) -> String {
+    String::from_utf8(key.to_vec()).unwrap()
+}
+
+/// Serialized as a sequence of (key, value) pairs in sorted order, and
+/// rebuilt on load by putting the pairs back one at a time.
+#[cfg(feature = "serde")]
+impl<V: Serialize> Serialize for TST<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(
+            serializer,
+            self.size(),
+            self.keys().map(|key| {
+                let val = self.get(&key).unwrap();
+                (key, val)
+            }),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for TST<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_seq(deserializer, Self::new(), |st, (key, val): (String, V)| {
+            st.put(&key, val);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shells() -> TST<usize> {
+        let mut st = TST::new();
+        for (i, key) in "she sells sea shells by the sea shore"
+            .split(' ')
+            .enumerate()
+        {
+            st.put(key, i);
+        }
+        st
+    }
+
+    #[test]
+    fn test_tst() {
+        let mut st = shells();
+        assert_eq!(st.size(), 7);
+        assert_eq!(st.get("sea"), Some(&6));
+        assert_eq!(st.get("shells"), Some(&3));
+        assert_eq!(st.get("shell"), None);
+        assert_eq!(st.get("s"), None);
+        assert_eq!(st.get("shellsort"), None);
+        assert_eq!(st.get(""), None);
+        assert!(st.contains("by"));
+        assert_eq!(
+            st.keys().collect::<Vec<_>>(),
+            ["by", "sea", "sells", "she", "shells", "shore", "the"]
+        );
+
+        assert_eq!(st.pop("shells"), Some(3));
+        assert_eq!(st.pop("shells"), None);
+        assert_eq!(st.pop("shell"), None);
+        assert_eq!(st.get("she"), Some(&0));
+        assert_eq!(st.size(), 6);
+
+        // popping everything prunes the whole trie
+        for key in ["she", "by", "shore", "sea", "the", "sells"] {
+            assert!(st.pop(key).is_some());
+            assert!(st.keys().all(|k| st.contains(&k)));
+        }
+        assert!(st.is_empty());
+        assert!(st.root.is_none());
+
+        // the empty string and non-ASCII keys are allowed
+        st.put("", 1);
+        st.put("héllo", 2);
+        assert_eq!(st.get(""), Some(&1));
+        assert_eq!(st.get("héllo"), Some(&2));
+        assert_eq!(st.keys().collect::<Vec<_>>(), ["", "héllo"]);
+        assert_eq!(st.pop(""), Some(1));
+        assert_eq!(st.size(), 1);
+    }
+
+    #[test]
+    fn test_prefix_operations() {
+        let mut st = shells();
+        assert_eq!(
+            st.keys_with_prefix("sh").collect::<Vec<_>>(),
+            ["she", "shells", "shore"]
+        );
+        assert_eq!(
+            st.keys_with_prefix("she").collect::<Vec<_>>(),
+            ["she", "shells"]
+        );
+        assert_eq!(st.keys_with_prefix("x").count(), 0);
+
+        assert_eq!(
+            st.keys_that_match("s..").collect::<Vec<_>>(),
+            ["sea", "she"]
+        );
+        assert_eq!(
+            st.keys_that_match(".he").collect::<Vec<_>>(),
+            ["she", "the"]
+        );
+        assert_eq!(st.keys_that_match("......").collect::<Vec<_>>(), ["shells"]);
+        assert_eq!(st.keys_that_match("s.").count(), 0);
+        assert_eq!(st.keys_that_match("").count(), 0);
+
+        assert_eq!(st.longest_prefix_of("shellsort"), Some("shells"));
+        assert_eq!(st.longest_prefix_of("shelters"), Some("she"));
+        assert_eq!(st.longest_prefix_of("she"), Some("she"));
+        assert_eq!(st.longest_prefix_of("quicksort"), None);
+        assert_eq!(st.longest_prefix_of(""), None);
+
+        st.put("", 0);
+        assert_eq!(st.longest_prefix_of("quicksort"), Some(""));
+        assert_eq!(st.keys_that_match("").collect::<Vec<_>>(), [""]);
+        assert_eq!(st.keys_with_prefix("").count(), 8);
+    }
+}
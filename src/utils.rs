@@ -11,10 +11,10 @@
 /// ```
 pub fn insert_and_shift<T>(arr: &mut [T], item: T, i: usize) -> T {
     let mut prev = item;
-    for j in i..arr.len() {
-        prev = std::mem::replace(&mut arr[j], prev);
+    for x in arr[i..].iter_mut() {
+        prev = std::mem::replace(x, prev);
     }
-    return prev;
+    prev
 }
 
 /// Pops the entry at index `i` in the array, and shifts the array to the
@@ -34,6 +34,98 @@ pub fn pop_and_shift<T>(arr: &mut [T], last: T, i: usize) -> T {
     std::mem::replace(&mut arr[len - 1], last)
 }
 
+/// Formats `item` as a quoted label for a Graphviz DOT file, escaping any
+/// quotes and backslashes.
+///
+/// ### Example
+/// ```ignore
+/// assert_eq!(dot_label(&r#"say "hi""#), r#""say \"hi\"""#);
+/// ```
+pub fn dot_label<T: std::fmt::Display>(item: &T) -> String {
+    let label = item.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", label)
+}
+
+/// Serialize the items as a sequence of the given length. Unlike
+/// `Serializer::collect_seq`, this doesn't rely on the iterator's size hint,
+/// so formats that need the length up front (e.g. bincode) work for any
+/// iterator.
+///
+/// ### Example
+/// ```ignore
+/// serialize_seq(serializer, self.size(), self.iter())
+/// ```
+#[cfg(feature = "serde")]
+pub fn serialize_seq<S, I>(serializer: S, len: usize, items: I) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    I: IntoIterator,
+    I::Item: serde::Serialize,
+{
+    use serde::ser::SerializeSeq;
+
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    for item in items {
+        seq.serialize_element(&item)?;
+    }
+    seq.end()
+}
+
+/// Deserializes a sequence into `collection`, adding the items one at a time
+/// with `push` (e.g. a put or push method), and failing on the first error
+/// (e.g. if a fixed capacity collection is full).
+///
+/// ### Example
+/// ```ignore
+/// deserialize_seq(deserializer, RedBlackBST::new(), |st, (key, val)| {
+///     st.put(key, val)
+/// })
+/// ```
+#[cfg(feature = "serde")]
+pub fn deserialize_seq<'de, D, T, C, F>(
+    deserializer: D,
+    collection: C,
+    push: F,
+) -> Result<C, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+    F: FnMut(&mut C, T) -> Result<(), String>,
+{
+    use serde::de::{Error, SeqAccess, Visitor};
+
+    struct SeqVisitor<T, C, F> {
+        collection: C,
+        push: F,
+        item: std::marker::PhantomData<T>,
+    }
+
+    impl<'de, T, C, F> Visitor<'de> for SeqVisitor<T, C, F>
+    where
+        T: serde::Deserialize<'de>,
+        F: FnMut(&mut C, T) -> Result<(), String>,
+    {
+        type Value = C;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<C, A::Error> {
+            while let Some(item) = seq.next_element()? {
+                (self.push)(&mut self.collection, item).map_err(A::Error::custom)?;
+            }
+            Ok(self.collection)
+        }
+    }
+
+    deserializer.deserialize_seq(SeqVisitor {
+        collection,
+        push,
+        item: std::marker::PhantomData,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +155,12 @@ mod tests {
         assert_eq!(pop_and_shift(&mut arr, 200, 1), 2);
         assert_eq!(arr, [1, 4, 5, 6, 7, 8, 9, -2, 100, 200]);
     }
+
+    #[test]
+    fn test_dot_label() {
+        assert_eq!(dot_label(&42), r#""42""#);
+        assert_eq!(dot_label(&"a b"), r#""a b""#);
+        assert_eq!(dot_label(&r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(dot_label(&r"C:\dir"), r#""C:\\dir""#);
+    }
 }